@@ -5,7 +5,9 @@ use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use prismswap::asset::PairInfo;
 use prismswap::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, PairsConfigResponse, PairsResponse, QueryMsg,
+    AssetStatusResponse, ConfigResponse, DeregisteredPairsResponse, ExecuteMsg, InstantiateMsg,
+    PairCodeIdsResponse, PairTypesResponse, PairsConfigResponse, PairsResponse, PauseInfoResponse,
+    QueryMsg,
 };
 
 fn main() {
@@ -21,4 +23,9 @@ fn main() {
     export_schema(&schema_for!(PairsResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(PairsConfigResponse), &out_dir);
+    export_schema(&schema_for!(PairTypesResponse), &out_dir);
+    export_schema(&schema_for!(AssetStatusResponse), &out_dir);
+    export_schema(&schema_for!(PairCodeIdsResponse), &out_dir);
+    export_schema(&schema_for!(PauseInfoResponse), &out_dir);
+    export_schema(&schema_for!(DeregisteredPairsResponse), &out_dir);
 }
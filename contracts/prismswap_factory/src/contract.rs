@@ -1,23 +1,36 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError,
-    StdResult, SubMsg, WasmMsg,
+    to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, QuerierWrapper, Reply,
+    ReplyOn, Response, StdError, StdResult, Storage, SubMsg, WasmMsg,
 };
 
-use crate::migration::migrate_config;
-use crate::parse_reply::parse_reply_instantiate_data;
+use cw2::{set_contract_version, CONTRACT};
+
+use crate::migration::{migrate_config, migrate_pairs, LEGACY_PAIR_CODE_ID};
 use crate::querier::query_pair_info;
 use crate::state::{
-    pair_key, read_pairs, Config, PairConfig, TmpPairInfo, CONFIG, PAIRS, TMP_PAIR_INFO,
+    pair_key, read_deregistered_pairs, read_pair_entries, read_pair_types, read_pairs, Config,
+    DeregisteredPair, PairConfig, TmpPairInfo, ASSET_STATUS, CONFIG, DEREGISTERED_PAIRS,
+    GLOBAL_PAUSED, PAIRS, PAIR_TYPES, PAUSED_PAIRS, TMP_PAIR_INFO,
 };
+use prismswap::parse_reply::parse_reply_instantiate_data;
 
-use prismswap::asset::{AssetInfo, PairInfo, PrismSwapAssetInfo};
+use prismswap::assert::assert_migration_version;
+use prismswap::asset::{Asset, AssetInfo, PairInfo, PrismSwapAsset, PrismSwapAssetInfo};
 use prismswap::factory::{
-    ConfigResponse, ExecuteMsg, FeeConfig, FeeInfoResponse, InstantiateMsg, MigrateMsg,
-    PairConfigResponse, PairsConfigResponse, PairsResponse, QueryMsg,
+    AssetStatus, AssetStatusResponse, ConfigResponse, DeregisteredPairResponse,
+    DeregisteredPairsResponse, ExecuteMsg, FeeConfig, FeeInfoResponse, InstantiateMsg, MigrateMsg,
+    PairCodeIdResponse, PairCodeIdsResponse, PairConfigResponse, PairType, PairTypeConfig,
+    PairTypesResponse, PairsConfigResponse, PairsResponse, PauseInfoResponse, QueryMsg,
 };
-use prismswap::pair::InstantiateMsg as PairInstantiateMsg;
+use prismswap::pair::{ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg};
+use terra_cosmwasm::TerraQuerier;
+
+/// contract name that is used for migration
+pub const CONTRACT_NAME: &str = "crates.io:prismswap-factory";
+/// contract version that is used for migration
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -29,12 +42,31 @@ pub fn instantiate(
     let config = Config {
         owner: deps.api.addr_validate(msg.owner.as_str())?,
         token_code_id: msg.token_code_id,
-        pair_code_id: msg.pair_code_id,
         collector: deps.api.addr_validate(msg.collector.as_str())?,
         pairs_admin: deps.api.addr_validate(msg.pairs_admin.as_str())?,
+        pauser: deps.api.addr_validate(msg.pauser.as_str())?,
+        permissionless: msg.permissionless,
+        creation_fee: msg.creation_fee,
     };
 
+    if let Some(creation_fee) = &config.creation_fee {
+        creation_fee.info.check(deps.api)?;
+    }
+
     CONFIG.save(deps.storage, &config)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    for pair_config in msg.pair_configs {
+        let key = pair_config.pair_type.to_string();
+        if PAIR_TYPES.has(deps.storage, &key) {
+            return Err(StdError::generic_err(format!(
+                "Pair type {} is registered twice",
+                key
+            )));
+        }
+
+        save_pair_type_config(deps.storage, &pair_config)?;
+    }
 
     Ok(Response::new())
 }
@@ -45,25 +77,47 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
         ExecuteMsg::UpdateConfig {
             owner,
             token_code_id,
-            pair_code_id,
             collector,
             pairs_admin,
+            pauser,
+            permissionless,
         } => execute_update_config(
             deps,
             info,
             owner,
             token_code_id,
-            pair_code_id,
             collector,
             pairs_admin,
+            pauser,
+            permissionless,
         ),
+        ExecuteMsg::UpdateCreationFee { creation_fee } => {
+            execute_update_creation_fee(deps, info, creation_fee)
+        }
+        ExecuteMsg::UpdateAssetStatus { asset_info, status } => {
+            asset_info.check(deps.api)?;
+            execute_update_asset_status(deps, info, asset_info, status)
+        }
+        ExecuteMsg::UpdatePairTypeConfig { config } => {
+            execute_update_pair_type_config(deps, info, config)
+        }
         ExecuteMsg::CreatePair {
+            pair_type,
             asset_infos,
             fee_config,
+            init_params,
         } => {
             asset_infos[0].check(deps.api)?;
             asset_infos[1].check(deps.api)?;
-            execute_create_pair(deps, info, env, asset_infos, fee_config)
+            execute_create_pair(
+                deps,
+                info,
+                env,
+                pair_type,
+                asset_infos,
+                fee_config,
+                init_params,
+            )
         }
         ExecuteMsg::UpdatePairConfig {
             asset_infos,
@@ -73,23 +127,47 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             asset_infos[1].check(deps.api)?;
             execute_update_pair_config(deps, info, asset_infos, fee_config)
         }
-        ExecuteMsg::Deregister { asset_infos } => {
+        ExecuteMsg::MigratePairs {
+            code_id,
+            start_after,
+            limit,
+            msg,
+        } => execute_migrate_pairs(deps, env, info, code_id, start_after, limit, msg),
+        ExecuteMsg::SyncPairs { start_after, limit } => {
+            execute_sync_pairs(deps, info, start_after, limit)
+        }
+        ExecuteMsg::UpdatePauseStatus {
+            asset_infos,
+            paused,
+        } => {
+            if let Some(asset_infos) = &asset_infos {
+                asset_infos[0].check(deps.api)?;
+                asset_infos[1].check(deps.api)?;
+            }
+            execute_update_pause_status(deps, info, asset_infos, paused)
+        }
+        ExecuteMsg::Deregister {
+            asset_infos,
+            reason,
+        } => {
             asset_infos[0].check(deps.api)?;
             asset_infos[1].check(deps.api)?;
-            execute_deregister(deps, info, asset_infos)
+            execute_deregister(deps, env, info, asset_infos, reason)
         }
     }
 }
 
 // Only owner can execute it
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     deps: DepsMut,
     info: MessageInfo,
     owner: Option<Addr>,
     token_code_id: Option<u64>,
-    pair_code_id: Option<u64>,
     collector: Option<Addr>,
     pairs_admin: Option<Addr>,
+    pauser: Option<Addr>,
+    permissionless: Option<bool>,
 ) -> StdResult<Response> {
     let mut config: Config = CONFIG.load(deps.storage)?;
 
@@ -107,10 +185,8 @@ pub fn execute_update_config(
         config.token_code_id = token_code_id;
     }
 
-    if let Some(pair_code_id) = pair_code_id {
-        config.pair_code_id = pair_code_id;
-    }
-
+    // the registered pairs keep sending the protocol fee to the old collector until the owner
+    // pushes the new one with SyncPairs over every page of pairs
     if let Some(collector) = collector {
         deps.api.addr_validate(collector.as_str())?;
         config.collector = collector;
@@ -121,28 +197,168 @@ pub fn execute_update_config(
         config.pairs_admin = pairs_admin;
     }
 
+    if let Some(pauser) = pauser {
+        deps.api.addr_validate(pauser.as_str())?;
+        config.pauser = pauser;
+    }
+
+    if let Some(permissionless) = permissionless {
+        config.permissionless = permissionless;
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
-// Only owner can create pairs
+/// ## Description
+/// Returns the message pushing the given fee settings to the pair.
+/// ## Params
+/// * **pair_address** is the object of type [`Addr`] of the pair to update.
+///
+/// * **fee_config** is an [`Option`] field of type [`FeeConfig`] with the new fee settings.
+///
+/// * **collector** is an [`Option`] field of type [`Addr`] with the new protocol fee collector.
+fn update_fee_config_msg(
+    pair_address: &Addr,
+    fee_config: Option<FeeConfig>,
+    collector: Option<Addr>,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair_address.to_string(),
+        msg: to_binary(&PairExecuteMsg::UpdateFeeConfig {
+            fee_config,
+            collector,
+        })?,
+        funds: vec![],
+    }))
+}
+
+/// ## Description
+/// Returns the message pushing the given pause and deregistration status to the pair.
+/// ## Params
+/// * **pair_address** is the object of type [`Addr`] of the pair to update.
+///
+/// * **paused** is whether swaps and deposits are halted on the pair.
+///
+/// * **deregistered** is whether the pair is no longer registered.
+fn update_pair_status_msg(
+    pair_address: &Addr,
+    paused: bool,
+    deregistered: bool,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair_address.to_string(),
+        msg: to_binary(&PairExecuteMsg::UpdatePairStatus {
+            paused,
+            deregistered,
+        })?,
+        funds: vec![],
+    }))
+}
+
+/// ## Description
+/// Returns whether the pair runs the code of the pairs created before the pair type registry,
+/// which rejects the fee and status pushes. The pair reads them from the factory once migrated.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **code_id** is the code identifier the pair is running.
+fn is_legacy_pair(storage: &dyn Storage, code_id: u64) -> StdResult<bool> {
+    Ok(LEGACY_PAIR_CODE_ID.may_load(storage)? == Some(code_id))
+}
+
+/// ## Description
+/// Returns whether the pair is paused by itself. The global pause is not pushed to the pairs,
+/// they query it from the factory.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **pair_key** is the key of the pair in the registry.
+fn is_pair_paused(storage: &dyn Storage, pair_key: &[u8]) -> StdResult<bool> {
+    Ok(PAUSED_PAIRS.may_load(storage, pair_key)?.unwrap_or(false))
+}
+
+// Only owner can execute it
+pub fn execute_update_pair_type_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_config: PairTypeConfig,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    save_pair_type_config(deps.storage, &pair_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_pair_type_config"),
+        ("pair_type", &pair_config.pair_type.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Validates the fee configuration of the given [`PairTypeConfig`] and stores it in the registry.
+fn save_pair_type_config(storage: &mut dyn Storage, pair_config: &PairTypeConfig) -> StdResult<()> {
+    if !pair_config.fee_config.is_valid() {
+        return Err(StdError::generic_err(
+            "The given fee configuration is not valid",
+        ));
+    }
+
+    PAIR_TYPES.save(storage, &pair_config.pair_type.to_string(), pair_config)
+}
+
+// Only owner can create pairs, unless the factory is permissionless
 pub fn execute_create_pair(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
+    pair_type: PairType,
     asset_infos: [AssetInfo; 2],
     fee_config: Option<FeeConfig>,
+    init_params: Option<Binary>,
 ) -> StdResult<Response> {
     let config: Config = CONFIG.load(deps.storage)?;
 
     // permission check
+    let mut messages: Vec<CosmosMsg> = vec![];
     if info.sender != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        if !config.permissionless {
+            return Err(StdError::generic_err("unauthorized"));
+        }
+
+        if fee_config.is_some() {
+            return Err(StdError::generic_err(
+                "Only the owner can set a custom fee configuration",
+            ));
+        }
+
+        assert_assets_listed(deps.storage, &asset_infos)?;
+
+        if let Some(creation_fee) = &config.creation_fee {
+            messages.push(collect_creation_fee(
+                &deps.querier,
+                &info,
+                creation_fee,
+                &config.collector,
+            )?);
+        }
+    }
+
+    let pair_type_config: PairTypeConfig = PAIR_TYPES
+        .load(deps.storage, &pair_type.to_string())
+        .map_err(|_| StdError::generic_err("Pair type not found"))?;
+
+    if !pair_type_config.allow_creation {
+        return Err(StdError::generic_err("Pair type creation is disabled"));
     }
 
     // validate the given fee configuration
-    let fee_config: FeeConfig = fee_config.unwrap_or_default();
+    let fee_config: FeeConfig = fee_config.unwrap_or(pair_type_config.fee_config);
     if !fee_config.is_valid() {
         return Err(StdError::generic_err(
             "The given fee configuration is not valid",
@@ -158,27 +374,36 @@ pub fn execute_create_pair(
         deps.storage,
         &TmpPairInfo {
             pair_key,
-            fee_config,
+            fee_config: fee_config.clone(),
+            pair_type: pair_type.clone(),
+            creator: info.sender.clone(),
+            code_id: pair_type_config.code_id,
         },
     )?;
 
     Ok(Response::new()
+        .add_messages(messages)
         .add_attributes(vec![
             ("action", "create_pair"),
             ("pair", &format!("{}-{}", asset_infos[0], asset_infos[1])),
+            ("pair_type", &pair_type.to_string()),
+            ("creator", info.sender.as_str()),
         ])
         .add_submessage(SubMsg {
             id: 1,
             gas_limit: None,
             msg: WasmMsg::Instantiate {
-                code_id: config.pair_code_id,
+                code_id: pair_type_config.code_id,
                 funds: vec![],
-                admin: Some(config.pairs_admin.to_string()),
+                admin: Some(env.contract.address.to_string()),
                 label: "".to_string(),
                 msg: to_binary(&PairInstantiateMsg {
                     asset_infos,
                     token_code_id: config.token_code_id,
                     factory: env.contract.address,
+                    fee_config,
+                    collector: config.collector,
+                    init_params,
                 })?,
             }
             .into(),
@@ -186,6 +411,117 @@ pub fn execute_create_pair(
         }))
 }
 
+/// ## Description
+/// Checks that none of the assets is denied and that at least one of them is allowed.
+fn assert_assets_listed(storage: &dyn Storage, asset_infos: &[AssetInfo; 2]) -> StdResult<()> {
+    let mut allowed = false;
+    for asset_info in asset_infos.iter() {
+        match ASSET_STATUS.may_load(storage, asset_info.as_bytes())? {
+            Some(AssetStatus::Denied) => {
+                return Err(StdError::generic_err(format!(
+                    "Asset {} is denied",
+                    asset_info
+                )))
+            }
+            Some(AssetStatus::Allowed) => allowed = true,
+            None => {}
+        }
+    }
+
+    if !allowed {
+        return Err(StdError::generic_err(
+            "At least one of the assets must be allowed",
+        ));
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns the message transferring the creation fee from the creator to the collector.
+/// Native fees must be attached to the message and the collector receives them net of the
+/// stability tax, cw20 fees must be approved to the factory. Any other attached funds are
+/// rejected.
+fn collect_creation_fee(
+    querier: &QuerierWrapper,
+    info: &MessageInfo,
+    creation_fee: &Asset,
+    collector: &Addr,
+) -> StdResult<CosmosMsg> {
+    match &creation_fee.info {
+        AssetInfo::Native(denom) => {
+            if info.funds.len() != 1
+                || info.funds[0].denom != *denom
+                || info.funds[0].amount != creation_fee.amount
+            {
+                return Err(StdError::generic_err(format!(
+                    "Creation fee of {} must be sent",
+                    creation_fee
+                )));
+            }
+
+            creation_fee.transfer_msg_with_tax(querier, collector)
+        }
+        AssetInfo::Cw20(_) => {
+            if !info.funds.is_empty() {
+                return Err(StdError::generic_err(
+                    "Native funds cannot be sent with a cw20 creation fee",
+                ));
+            }
+
+            creation_fee.transfer_from_msg(&info.sender, collector)
+        }
+    }
+}
+
+// Only owner can execute it
+pub fn execute_update_creation_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    creation_fee: Option<Asset>,
+) -> StdResult<Response> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(creation_fee) = &creation_fee {
+        creation_fee.info.check(deps.api)?;
+    }
+
+    config.creation_fee = creation_fee;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_creation_fee"))
+}
+
+// Only owner can execute it
+pub fn execute_update_asset_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    status: Option<AssetStatus>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    match status {
+        Some(status) => ASSET_STATUS.save(deps.storage, asset_info.as_bytes(), &status)?,
+        None => ASSET_STATUS.remove(deps.storage, asset_info.as_bytes()),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_asset_status"),
+        ("asset_info", &asset_info.to_string()),
+    ]))
+}
+
 // Only owner can execute it
 pub fn execute_update_pair_config(
     deps: DepsMut,
@@ -212,18 +548,211 @@ pub fn execute_update_pair_config(
         .load(deps.storage, &pair_key)
         .map_err(|_| StdError::generic_err("There is no pair registered with the provided info"))?;
 
-    pair_config.fee_config = fee_config;
+    pair_config.fee_config = fee_config.clone();
 
     PAIRS.save(deps.storage, &pair_key, &pair_config)?;
 
-    Ok(Response::new().add_attribute("action", "update_pair_config"))
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !is_legacy_pair(deps.storage, pair_config.code_id)? {
+        messages.push(update_fee_config_msg(
+            &pair_config.pair_address,
+            Some(fee_config),
+            None,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_pair_config")
+        .add_messages(messages))
+}
+
+/// ## Description
+/// Migrates a page of the pairs of the types registered with the code id to that code. The
+/// factory can only migrate the pairs it administers: the pairs created before the pair type
+/// registry are administered by the pairs admin, which hands them over by updating their admin
+/// to the factory. The other pairs are skipped and reported in the `skipped_pairs` attribute.
+/// Only the pairs admin can execute it.
+/// ## Params
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **code_id** is the code identifier of a registered pair type to migrate to.
+///
+/// * **start_after** is an [`Option`] field of the assets of the last pair of the previous page.
+///
+/// * **limit** is an [`Option`] field with the number of pairs to read.
+///
+/// * **msg** is the binary serialised migration message sent to every pair.
+pub fn execute_migrate_pairs(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    code_id: u64,
+    start_after: Option<[AssetInfo; 2]>,
+    limit: Option<u32>,
+    msg: Binary,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.pairs_admin {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    // only pairs of the types registered with the code id are migrated
+    let pair_types: Vec<PairType> = read_pair_types(deps.storage)?
+        .into_iter()
+        .filter(|pair_type_config| pair_type_config.code_id == code_id)
+        .map(|pair_type_config| pair_type_config.pair_type)
+        .collect();
+    if pair_types.is_empty() {
+        return Err(StdError::generic_err(format!(
+            "No pair type is registered with code id {}",
+            code_id
+        )));
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut skipped_pairs: Vec<String> = vec![];
+    for (pair_key, mut pair_config) in read_pair_entries(deps.storage, start_after, limit)? {
+        if pair_config.code_id == code_id || !pair_types.contains(&pair_config.pair_type) {
+            continue;
+        }
+
+        // a migration of a pair administered by another address would revert the whole page
+        let admin = TerraQuerier::new(&deps.querier)
+            .query_contract_info(pair_config.pair_address.to_string())?
+            .admin;
+        if admin != Some(env.contract.address.to_string()) {
+            skipped_pairs.push(pair_config.pair_address.to_string());
+            continue;
+        }
+
+        messages.push(CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: pair_config.pair_address.to_string(),
+            new_code_id: code_id,
+            msg: msg.clone(),
+        }));
+
+        pair_config.code_id = code_id;
+        PAIRS.save(deps.storage, &pair_key, &pair_config)?;
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "migrate_pairs"),
+            ("code_id", &code_id.to_string()),
+            ("migrated_pairs", &messages.len().to_string()),
+            ("skipped_pairs", &skipped_pairs.join(",")),
+        ])
+        .add_messages(messages))
+}
+
+/// ## Description
+/// Pushes the collector of the config to a page of the registered pairs. Only the owner can
+/// execute it, the pairs keep the previous collector until it has run over every page.
+/// ## Params
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **start_after** is an [`Option`] field of the assets of the last pair of the previous page.
+///
+/// * **limit** is an [`Option`] field with the number of pairs to update.
+pub fn execute_sync_pairs(
+    deps: DepsMut,
+    info: MessageInfo,
+    start_after: Option<[AssetInfo; 2]>,
+    limit: Option<u32>,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let pairs = read_pair_entries(deps.storage, start_after, limit)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (_, pair_config) in pairs.iter() {
+        if is_legacy_pair(deps.storage, pair_config.code_id)? {
+            continue;
+        }
+
+        messages.push(update_fee_config_msg(
+            &pair_config.pair_address,
+            None,
+            Some(config.collector.clone()),
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "sync_pairs"),
+            ("synced_pairs", &messages.len().to_string()),
+        ])
+        .add_messages(messages))
+}
+
+// Only pauser can execute it
+pub fn execute_update_pause_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_infos: Option<[AssetInfo; 2]>,
+    paused: bool,
+) -> StdResult<Response> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.pauser {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let target = match &asset_infos {
+        Some(asset_infos) => {
+            let pair_key = pair_key(asset_infos);
+            if paused {
+                PAUSED_PAIRS.save(deps.storage, &pair_key, &true)?;
+            } else {
+                PAUSED_PAIRS.remove(deps.storage, &pair_key);
+            }
+
+            // push the status to the registered pair, a legacy pair reads it when migrated
+            if let Some(pair_config) = PAIRS.may_load(deps.storage, &pair_key)? {
+                if !is_legacy_pair(deps.storage, pair_config.code_id)? {
+                    messages.push(update_pair_status_msg(
+                        &pair_config.pair_address,
+                        is_pair_paused(deps.storage, &pair_key)?,
+                        false,
+                    )?);
+                }
+            }
+
+            format!("{}-{}", asset_infos[0], asset_infos[1])
+        }
+        None => {
+            // the pairs query the global pause from the factory
+            GLOBAL_PAUSED.save(deps.storage, &paused)?;
+            "all".to_string()
+        }
+    };
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "update_pause_status"),
+            ("pair", &target),
+            ("paused", &paused.to_string()),
+        ])
+        .add_messages(messages))
 }
 
 // Only owner can execute it
 pub fn execute_deregister(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     asset_infos: [AssetInfo; 2],
+    reason: Option<String>,
 ) -> StdResult<Response> {
     let config: Config = CONFIG.load(deps.storage)?;
 
@@ -235,14 +764,39 @@ pub fn execute_deregister(
     let pair_key = pair_key(&asset_infos);
 
     // check if pair exists
-    PAIRS
+    let pair_config: PairConfig = PAIRS
         .load(deps.storage, &pair_key)
         .map_err(|_| StdError::generic_err("There is no pair registered with the provided info"))?;
 
-    // delete the pair from storage
+    // move the pair from the registry to the archive, a new pair for the assets starts unpaused
     PAIRS.remove(deps.storage, &pair_key);
+    PAUSED_PAIRS.remove(deps.storage, &pair_key);
+    DEREGISTERED_PAIRS.save(
+        deps.storage,
+        pair_config.pair_address.as_bytes(),
+        &DeregisteredPair {
+            asset_infos,
+            pair_config: pair_config.clone(),
+            deregistered_at: env.block.height,
+            reason,
+        },
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !is_legacy_pair(deps.storage, pair_config.code_id)? {
+        messages.push(update_pair_status_msg(
+            &pair_config.pair_address,
+            false,
+            true,
+        )?);
+    }
 
-    Ok(Response::new().add_attribute("action", "deregister"))
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "deregister"),
+            ("pair_contract_addr", pair_config.pair_address.as_str()),
+        ])
+        .add_messages(messages))
 }
 
 /// This just stores the result for future query
@@ -254,16 +808,30 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
         .map_err(|err| StdError::generic_err(format!("{}", err)))?;
     let pair_contract = res.contract_address;
 
+    let pair_address = deps.api.addr_validate(&pair_contract)?;
     PAIRS.save(
         deps.storage,
         &tmp_pair_info.pair_key,
         &PairConfig {
-            pair_address: deps.api.addr_validate(&pair_contract)?,
+            pair_address: pair_address.clone(),
             fee_config: tmp_pair_info.fee_config,
+            pair_type: tmp_pair_info.pair_type,
+            creator: tmp_pair_info.creator,
+            code_id: tmp_pair_info.code_id,
         },
     )?;
 
-    Ok(Response::new().add_attributes(vec![("pair_contract_addr", pair_contract)]))
+    // pairs are instantiated active, push the status if the pair is created while paused
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if is_pair_paused(deps.storage, &tmp_pair_info.pair_key)?
+        && !is_legacy_pair(deps.storage, tmp_pair_info.code_id)?
+    {
+        messages.push(update_pair_status_msg(&pair_address, true, false)?);
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![("pair_contract_addr", pair_contract)])
+        .add_messages(messages))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -274,7 +842,19 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::Pairs { start_after, limit } => {
             to_binary(&query_pairs(deps, start_after, limit)?)
         }
-        QueryMsg::FeeInfo { asset_infos } => to_binary(&query_fee_config(deps, asset_infos)?),
+        QueryMsg::PairCodeIds { start_after, limit } => {
+            to_binary(&query_pair_code_ids(deps, start_after, limit)?)
+        }
+        QueryMsg::PairTypes {} => to_binary(&query_pair_types(deps)?),
+        QueryMsg::AssetStatus { asset_info } => to_binary(&query_asset_status(deps, asset_info)?),
+        QueryMsg::FeeInfo {
+            asset_infos,
+            pair_address,
+        } => to_binary(&query_fee_config(deps, asset_infos, pair_address)?),
+        QueryMsg::PauseInfo { asset_infos } => to_binary(&query_pause_info(deps, asset_infos)?),
+        QueryMsg::DeregisteredPairs { start_after, limit } => {
+            to_binary(&query_deregistered_pairs(deps, start_after, limit)?)
+        }
         QueryMsg::PairConfig { asset_infos } => to_binary(&query_pair_config(deps, asset_infos)?),
         QueryMsg::PairsConfig { start_after, limit } => {
             to_binary(&query_pairs_config(deps, start_after, limit)?)
@@ -287,14 +867,45 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let resp = ConfigResponse {
         owner: config.owner,
         token_code_id: config.token_code_id,
-        pair_code_id: config.pair_code_id,
         collector: config.collector,
         pairs_admin: config.pairs_admin,
+        pauser: config.pauser,
+        permissionless: config.permissionless,
+        creation_fee: config.creation_fee,
     };
 
     Ok(resp)
 }
 
+pub fn query_pair_code_ids(
+    deps: Deps,
+    start_after: Option<[AssetInfo; 2]>,
+    limit: Option<u32>,
+) -> StdResult<PairCodeIdsResponse> {
+    let pairs: Vec<PairCodeIdResponse> = read_pairs(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|pair| PairCodeIdResponse {
+            pair_address: pair.pair_address,
+            pair_type: pair.pair_type,
+            code_id: pair.code_id,
+        })
+        .collect();
+
+    Ok(PairCodeIdsResponse { pairs })
+}
+
+pub fn query_pair_types(deps: Deps) -> StdResult<PairTypesResponse> {
+    Ok(PairTypesResponse {
+        pair_types: read_pair_types(deps.storage)?,
+    })
+}
+
+pub fn query_asset_status(deps: Deps, asset_info: AssetInfo) -> StdResult<AssetStatusResponse> {
+    let status = ASSET_STATUS.may_load(deps.storage, asset_info.as_bytes())?;
+
+    Ok(AssetStatusResponse { asset_info, status })
+}
+
 pub fn query_pair(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PairInfo> {
     let pair_key = pair_key(&asset_infos);
     let pair_config: PairConfig = PAIRS.load(deps.storage, &pair_key)?;
@@ -317,12 +928,38 @@ pub fn query_pairs(
     Ok(PairsResponse { pairs: pair_infos })
 }
 
-pub fn query_fee_config(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<FeeInfoResponse> {
+/// ## Description
+/// Returns the fee settings of a pair in a [`FeeInfoResponse`] object. The settings of a
+/// deregistered pair are read from the archive when its address is given, so that it can still
+/// be migrated.
+/// ## Params
+/// * **asset_infos** are the assets of the pair.
+///
+/// * **pair_address** is an [`Option`] field with the address of the pair.
+pub fn query_fee_config(
+    deps: Deps,
+    asset_infos: [AssetInfo; 2],
+    pair_address: Option<String>,
+) -> StdResult<FeeInfoResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let pair_key = pair_key(&asset_infos);
-    let fee_config: FeeConfig = match PAIRS.load(deps.storage, &pair_key) {
-        Ok(config) => config.fee_config,
-        _ => FeeConfig::default(),
+    let deregistered_pair = match pair_address {
+        Some(pair_address) => {
+            let pair_address = deps.api.addr_validate(&pair_address)?;
+            DEREGISTERED_PAIRS.may_load(deps.storage, pair_address.as_bytes())?
+        }
+        None => None,
+    };
+
+    let fee_config: FeeConfig = match deregistered_pair {
+        Some(deregistered_pair) => deregistered_pair.pair_config.fee_config,
+        None => {
+            PAIRS
+                .load(deps.storage, &pair_key(&asset_infos))
+                .map_err(|_| {
+                    StdError::generic_err("There is no pair registered with the provided info")
+                })?
+                .fee_config
+        }
     };
 
     Ok(FeeInfoResponse {
@@ -331,6 +968,48 @@ pub fn query_fee_config(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<Fe
     })
 }
 
+pub fn query_pause_info(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PauseInfoResponse> {
+    let pair_key = pair_key(&asset_infos);
+    let global_paused = GLOBAL_PAUSED.may_load(deps.storage)?.unwrap_or(false);
+    let pair_paused = PAUSED_PAIRS
+        .may_load(deps.storage, &pair_key)?
+        .unwrap_or(false);
+    let registered_pair = PAIRS
+        .may_load(deps.storage, &pair_key)?
+        .map(|pair_config| pair_config.pair_address);
+
+    Ok(PauseInfoResponse {
+        global_paused,
+        pair_paused,
+        registered_pair,
+    })
+}
+
+pub fn query_deregistered_pairs(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<DeregisteredPairsResponse> {
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+
+    let pairs: Vec<DeregisteredPairResponse> =
+        read_deregistered_pairs(deps.storage, start_after, limit)?
+            .into_iter()
+            .map(|pair| DeregisteredPairResponse {
+                pair_address: pair.pair_config.pair_address,
+                asset_infos: pair.asset_infos,
+                pair_type: pair.pair_config.pair_type,
+                fee_config: pair.pair_config.fee_config,
+                deregistered_at: pair.deregistered_at,
+                reason: pair.reason,
+            })
+            .collect();
+
+    Ok(DeregisteredPairsResponse { pairs })
+}
+
 pub fn query_pair_config(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PairConfigResponse> {
     let pair_key = pair_key(&asset_infos);
     let pair_config: PairConfig = PAIRS.load(deps.storage, &pair_key)?;
@@ -340,6 +1019,8 @@ pub fn query_pair_config(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<P
     Ok(PairConfigResponse {
         pair_info,
         fee_config: pair_config.fee_config,
+        pair_type: pair_config.pair_type,
+        creator: pair_config.creator,
     })
 }
 
@@ -358,6 +1039,8 @@ pub fn query_pairs_config(
             Ok(PairConfigResponse {
                 pair_info,
                 fee_config: pair.fee_config.clone(),
+                pair_type: pair.pair_type.clone(),
+                creator: pair.creator.clone(),
             })
         })
         .collect::<StdResult<Vec<PairConfigResponse>>>()?;
@@ -367,8 +1050,35 @@ pub fn query_pairs_config(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
-    let pairs_admin: Addr = deps.api.addr_validate(msg.pairs_admin.as_str())?;
-    migrate_config(deps.storage, pairs_admin)?;
+    // the legacy config with a single pair code id is only stored by the unversioned code
+    let previous_version = match CONTRACT.may_load(deps.storage)? {
+        Some(stored) => {
+            assert_migration_version(&stored, CONTRACT_NAME, CONTRACT_VERSION)
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+            stored.version
+        }
+        None => {
+            migrate_config(deps.storage)?;
+            "unversioned".to_string()
+        }
+    };
+
+    // the legacy pairs are converted page by page, the factory is migrated again until none
+    // remains and they cannot be read in the meantime
+    let legacy_pairs_pending = migrate_pairs(deps.storage, msg.limit)?;
+
+    if let Some(pairs_admin) = msg.pairs_admin {
+        let mut config: Config = CONFIG.load(deps.storage)?;
+        config.pairs_admin = deps.api.addr_validate(pairs_admin.as_str())?;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    Ok(Response::default())
+    Ok(Response::new().add_attributes(vec![
+        ("action", "migrate"),
+        ("previous_version", previous_version.as_str()),
+        ("new_version", CONTRACT_VERSION),
+        ("legacy_pairs_pending", &legacy_pairs_pending.to_string()),
+    ]))
 }
@@ -1,6 +1,5 @@
 pub mod contract;
 pub mod migration;
-mod parse_reply;
 mod querier;
 pub mod state;
 
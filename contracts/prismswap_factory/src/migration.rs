@@ -1,11 +1,24 @@
-use cosmwasm_std::{Addr, StdResult, Storage};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
+use prismswap::factory::{FeeConfig, PairType, PairTypeConfig};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::{Config, CONFIG};
+use crate::state::{Config, PairConfig, CONFIG, PAIRS, PAIR_TYPES};
 
 pub const LEGACY: Item<LegacyConfig> = Item::new("config");
+pub const LEGACY_PAIRS: Map<&[u8], LegacyPairConfig> = Map::new("pair_config");
+
+/// the code identifier of the pairs created before the pair type registry, which do not accept
+/// the fee and status pushes of the factory
+pub const LEGACY_PAIR_CODE_ID: Item<u64> = Item::new("legacy_pair_code_id");
+
+/// the key of the last converted legacy pair, stored until every legacy pair is converted
+pub const LEGACY_PAIRS_CURSOR: Item<Vec<u8>> = Item::new("legacy_pairs_cursor");
+
+// settings for pagination
+const MAX_LIMIT: u32 = 100;
+const DEFAULT_LIMIT: u32 = 30;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct LegacyConfig {
@@ -13,29 +26,116 @@ pub struct LegacyConfig {
     pub pair_code_id: u64,
     pub token_code_id: u64,
     pub collector: Addr,
+    pub pairs_admin: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyPairConfig {
+    pub pair_address: Addr,
+    pub fee_config: FeeConfig,
 }
 
-pub fn migrate_config(storage: &mut dyn Storage, pairs_admin: Addr) -> StdResult<()> {
+/// ## Description
+/// Moves the single pair code id of the legacy config into the pair type registry as xyk.
+/// The owner is assigned as the pauser. The code id is kept to recognize the legacy pairs,
+/// which are then converted page by page by [`migrate_pairs`].
+pub fn migrate_config(storage: &mut dyn Storage) -> StdResult<()> {
     let legacy_config: LegacyConfig = LEGACY.load(storage)?;
     let config = Config {
-        owner: legacy_config.owner,
+        owner: legacy_config.owner.clone(),
         token_code_id: legacy_config.token_code_id,
-        pair_code_id: legacy_config.pair_code_id,
         collector: legacy_config.collector,
-        pairs_admin,
+        pairs_admin: legacy_config.pairs_admin,
+        pauser: legacy_config.owner.clone(),
+        permissionless: false,
+        creation_fee: None,
+    };
+
+    let pair_type_config = PairTypeConfig {
+        pair_type: PairType::Xyk {},
+        code_id: legacy_config.pair_code_id,
+        fee_config: FeeConfig::default(),
+        allow_creation: true,
     };
 
     CONFIG.save(storage, &config)?;
+    LEGACY_PAIR_CODE_ID.save(storage, &legacy_config.pair_code_id)?;
+    LEGACY_PAIRS_CURSOR.save(storage, &vec![])?;
+    PAIR_TYPES.save(
+        storage,
+        &pair_type_config.pair_type.to_string(),
+        &pair_type_config,
+    )?;
     Ok(())
 }
 
+/// ## Description
+/// Marks a page of the pairs created before the pair type registry as xyk, created by the
+/// factory owner and running the legacy pair code id. The page starts after the last converted
+/// pair, the pairs created since the registry are kept. Returns whether legacy pairs remain.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **limit** is an [`Option`] field with the number of pairs to convert.
+pub fn migrate_pairs(storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<bool> {
+    let cursor: Vec<u8> = match LEGACY_PAIRS_CURSOR.may_load(storage)? {
+        Some(cursor) => cursor,
+        None => return Ok(false),
+    };
+
+    let config: Config = CONFIG.load(storage)?;
+    let xyk_config: PairTypeConfig = PAIR_TYPES.load(storage, &PairType::Xyk {}.to_string())?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let start = if cursor.is_empty() {
+        None
+    } else {
+        Some(Bound::exclusive(cursor))
+    };
+    let pair_keys: Vec<Vec<u8>> = LEGACY_PAIRS
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect();
+
+    for pair_key in pair_keys.iter().take(limit) {
+        // the legacy and the registry layouts share the storage namespace
+        if PAIRS.load(storage, pair_key).is_ok() {
+            continue;
+        }
+
+        let legacy_pair: LegacyPairConfig = LEGACY_PAIRS.load(storage, pair_key)?;
+        PAIRS.save(
+            storage,
+            pair_key,
+            &PairConfig {
+                pair_address: legacy_pair.pair_address,
+                fee_config: legacy_pair.fee_config,
+                pair_type: PairType::Xyk {},
+                creator: config.owner.clone(),
+                code_id: xyk_config.code_id,
+            },
+        )?;
+    }
+
+    if pair_keys.len() > limit {
+        LEGACY_PAIRS_CURSOR.save(storage, &pair_keys[limit - 1])?;
+        Ok(true)
+    } else {
+        LEGACY_PAIRS_CURSOR.remove(storage);
+        Ok(false)
+    }
+}
+
 #[cfg(test)]
 mod migrate_tests {
-    use cosmwasm_std::{testing::mock_dependencies, Api};
+    use cosmwasm_std::{testing::mock_dependencies, Addr, Api, Decimal};
+    use prismswap::factory::{FeeConfig, PairType, PairTypeConfig};
 
     use crate::{
-        migration::{migrate_config, LegacyConfig, LEGACY},
-        state::{Config, CONFIG},
+        migration::{
+            migrate_config, migrate_pairs, LegacyConfig, LegacyPairConfig, LEGACY, LEGACY_PAIRS,
+            LEGACY_PAIRS_CURSOR, LEGACY_PAIR_CODE_ID,
+        },
+        state::{read_pair_types, Config, PairConfig, CONFIG, PAIRS, PAIR_TYPES},
     };
 
     #[test]
@@ -50,15 +150,12 @@ mod migrate_tests {
                     token_code_id: 2,
                     pair_code_id: 33,
                     collector: deps.api.addr_validate("collector0000").unwrap(),
+                    pairs_admin: deps.api.addr_validate("admin0000").unwrap(),
                 },
             )
             .unwrap();
 
-        migrate_config(
-            &mut deps.storage,
-            deps.api.addr_validate("admin0000").unwrap(),
-        )
-        .unwrap();
+        migrate_config(&mut deps.storage).unwrap();
 
         let config: Config = CONFIG.load(&deps.storage).unwrap();
         assert_eq!(
@@ -66,10 +163,121 @@ mod migrate_tests {
             Config {
                 owner: deps.api.addr_validate("owner0000").unwrap(),
                 token_code_id: 2,
-                pair_code_id: 33,
                 collector: deps.api.addr_validate("collector0000").unwrap(),
                 pairs_admin: deps.api.addr_validate("admin0000").unwrap(),
+                pauser: deps.api.addr_validate("owner0000").unwrap(),
+                permissionless: false,
+                creation_fee: None,
             }
-        )
+        );
+        assert_eq!(
+            read_pair_types(&deps.storage).unwrap(),
+            vec![PairTypeConfig {
+                pair_type: PairType::Xyk {},
+                code_id: 33,
+                fee_config: FeeConfig::default(),
+                allow_creation: true,
+            }]
+        );
+        assert_eq!(LEGACY_PAIR_CODE_ID.load(&deps.storage).unwrap(), 33);
+        assert_eq!(
+            LEGACY_PAIRS_CURSOR.load(&deps.storage).unwrap(),
+            Vec::<u8>::new()
+        );
+    }
+
+    #[test]
+    fn test_pairs_migration() {
+        let mut deps = mock_dependencies(&[]);
+
+        CONFIG
+            .save(
+                &mut deps.storage,
+                &Config {
+                    owner: Addr::unchecked("owner0000"),
+                    token_code_id: 2,
+                    collector: Addr::unchecked("collector0000"),
+                    pairs_admin: Addr::unchecked("admin0000"),
+                    pauser: Addr::unchecked("owner0000"),
+                    permissionless: false,
+                    creation_fee: None,
+                },
+            )
+            .unwrap();
+
+        PAIR_TYPES
+            .save(
+                &mut deps.storage,
+                "xyk",
+                &PairTypeConfig {
+                    pair_type: PairType::Xyk {},
+                    code_id: 33,
+                    fee_config: FeeConfig::default(),
+                    allow_creation: true,
+                },
+            )
+            .unwrap();
+
+        let fee_config = FeeConfig {
+            total_fee: Decimal::permille(5),
+            protocol_fee: Decimal::percent(20),
+        };
+        for pair in ["pair0000", "pair0002"] {
+            LEGACY_PAIRS
+                .save(
+                    &mut deps.storage,
+                    pair.as_bytes(),
+                    &LegacyPairConfig {
+                        pair_address: Addr::unchecked(pair),
+                        fee_config: fee_config.clone(),
+                    },
+                )
+                .unwrap();
+        }
+
+        // a pair created before the legacy pairs are all converted
+        let stable_pair = PairConfig {
+            pair_address: Addr::unchecked("pair0001"),
+            fee_config: FeeConfig::default(),
+            pair_type: PairType::Stable {},
+            creator: Addr::unchecked("addr0000"),
+            code_id: 34,
+        };
+        PAIRS
+            .save(&mut deps.storage, b"pair0001", &stable_pair)
+            .unwrap();
+        LEGACY_PAIRS_CURSOR
+            .save(&mut deps.storage, &vec![])
+            .unwrap();
+
+        let legacy_pair = |pair: &str| PairConfig {
+            pair_address: Addr::unchecked(pair),
+            fee_config: fee_config.clone(),
+            pair_type: PairType::Xyk {},
+            creator: Addr::unchecked("owner0000"),
+            code_id: 33,
+        };
+
+        // the first page leaves legacy pairs to convert
+        assert!(migrate_pairs(&mut deps.storage, Some(1)).unwrap());
+        assert_eq!(
+            PAIRS.load(&deps.storage, b"pair0000").unwrap(),
+            legacy_pair("pair0000")
+        );
+        assert!(PAIRS.load(&deps.storage, b"pair0002").is_err());
+
+        assert!(!migrate_pairs(&mut deps.storage, Some(2)).unwrap());
+        assert_eq!(PAIRS.load(&deps.storage, b"pair0001").unwrap(), stable_pair);
+        assert_eq!(
+            PAIRS.load(&deps.storage, b"pair0002").unwrap(),
+            legacy_pair("pair0002")
+        );
+        assert!(LEGACY_PAIRS_CURSOR
+            .may_load(&deps.storage)
+            .unwrap()
+            .is_none());
+
+        // nothing is left to convert
+        assert!(!migrate_pairs(&mut deps.storage, None).unwrap());
     }
 }
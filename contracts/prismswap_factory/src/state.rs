@@ -1,6 +1,6 @@
 use prismswap::{
-    asset::{AssetInfo, PrismSwapAssetInfo},
-    factory::FeeConfig,
+    asset::{Asset, AssetInfo, PrismSwapAssetInfo},
+    factory::{AssetStatus, FeeConfig, PairType, PairTypeConfig},
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -11,29 +11,62 @@ use cw_storage_plus::{Bound, Item, Map};
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: Addr,
-    pub pair_code_id: u64,
     pub token_code_id: u64,
     pub collector: Addr,
     pub pairs_admin: Addr,
+    pub pauser: Addr,
+    pub permissionless: bool,
+    pub creation_fee: Option<Asset>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// whether swaps and deposits are halted on every pair
+pub const GLOBAL_PAUSED: Item<bool> = Item::new("global_paused");
+
+/// pairs with halted swaps and deposits, keyed by the pair key
+pub const PAUSED_PAIRS: Map<&[u8], bool> = Map::new("paused_pairs");
+
+/// listing status of assets for permissionless pair creation, keyed by the asset info bytes
+pub const ASSET_STATUS: Map<&[u8], AssetStatus> = Map::new("asset_status");
+
+/// registered pair types, keyed by the [`PairType`] name
+pub const PAIR_TYPES: Map<&str, PairTypeConfig> = Map::new("pair_types");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
     pub fee_config: FeeConfig,
+    pub pair_type: PairType,
+    pub creator: Addr,
+    pub code_id: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PairConfig {
     pub pair_address: Addr,
     pub fee_config: FeeConfig,
+    pub pair_type: PairType,
+    pub creator: Addr,
+    /// the code identifier the pair is running
+    pub code_id: u64,
 }
 
 pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
 pub const PAIRS: Map<&[u8], PairConfig> = Map::new("pair_config");
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DeregisteredPair {
+    pub asset_infos: [AssetInfo; 2],
+    pub pair_config: PairConfig,
+    /// the block height the pair was deregistered at
+    pub deregistered_at: u64,
+    pub reason: Option<String>,
+}
+
+/// archive of the deregistered pairs, keyed by the pair address
+pub const DEREGISTERED_PAIRS: Map<&[u8], DeregisteredPair> = Map::new("deregistered_pairs");
+
 pub fn pair_key(asset_infos: &[AssetInfo; 2]) -> Vec<u8> {
     let mut asset_infos = asset_infos.to_vec();
     asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
@@ -41,6 +74,16 @@ pub fn pair_key(asset_infos: &[AssetInfo; 2]) -> Vec<u8> {
     [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()].concat()
 }
 
+pub fn read_pair_types(storage: &dyn Storage) -> StdResult<Vec<PairTypeConfig>> {
+    PAIR_TYPES
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect::<StdResult<Vec<PairTypeConfig>>>()
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
@@ -49,17 +92,46 @@ pub fn read_pairs(
     start_after: Option<[AssetInfo; 2]>,
     limit: Option<u32>,
 ) -> StdResult<Vec<PairConfig>> {
+    Ok(read_pair_entries(storage, start_after, limit)?
+        .into_iter()
+        .map(|(_, v)| v)
+        .collect())
+}
+
+/// ## Description
+/// Returns a page of the registered pairs along with their storage keys.
+pub fn read_pair_entries(
+    storage: &dyn Storage,
+    start_after: Option<[AssetInfo; 2]>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Vec<u8>, PairConfig)>> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = calc_range_start(start_after).map(Bound::exclusive);
 
     PAIRS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(Vec<u8>, PairConfig)>>>()
+}
+
+/// ## Description
+/// Returns a page of the archived pairs ordered by their address.
+pub fn read_deregistered_pairs(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<DeregisteredPair>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_bytes()));
+
+    DEREGISTERED_PAIRS
         .range(storage, start, None, Order::Ascending)
         .take(limit)
         .map(|item| {
             let (_, v) = item?;
             Ok(v)
         })
-        .collect::<StdResult<Vec<PairConfig>>>()
+        .collect::<StdResult<Vec<DeregisteredPair>>>()
 }
 
 // this will set the first key after the provided key, by appending a 1 byte
@@ -1,33 +1,56 @@
 use std::str::FromStr;
 
-use crate::contract::{execute, instantiate, query, reply};
+use crate::contract::{
+    execute, instantiate, migrate, query, reply, CONTRACT_NAME, CONTRACT_VERSION,
+};
+use crate::migration::{LegacyConfig, LegacyPairConfig, LEGACY, LEGACY_PAIRS};
 use crate::mock_querier::{mock_dependencies, WasmMockQuerier};
 
-use crate::state::{pair_key, TmpPairInfo, TMP_PAIR_INFO};
+use crate::state::{pair_key, Config, TmpPairInfo, CONFIG, TMP_PAIR_INFO};
 
 use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, ContractResult, Decimal, MemoryStorage, OwnedDeps, Reply,
-    ReplyOn, StdError, SubMsg, SubMsgExecutionResponse, WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal,
+    MemoryStorage, OwnedDeps, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgExecutionResponse,
+    Uint128, WasmMsg,
 };
-use prismswap::asset::{AssetInfo, PairInfo};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::Cw20ExecuteMsg;
+use prismswap::asset::{Asset, AssetInfo, PairInfo};
 use prismswap::factory::{
-    ConfigResponse, ExecuteMsg, FeeConfig, FeeInfoResponse, InstantiateMsg, PairConfigResponse,
-    PairsConfigResponse, PairsResponse, QueryMsg, DEFAULT_PROTOCOL_FEE, DEFAULT_TOTAL_FEE,
-    MAX_PROTOCOL_FEE, MAX_TOTAL_FEE,
+    AssetStatus, AssetStatusResponse, ConfigResponse, DeregisteredPairResponse,
+    DeregisteredPairsResponse, ExecuteMsg, FeeConfig, FeeInfoResponse, InstantiateMsg, MigrateMsg,
+    PairCodeIdResponse, PairCodeIdsResponse, PairConfigResponse, PairType, PairTypeConfig,
+    PairTypesResponse, PairsConfigResponse, PairsResponse, PauseInfoResponse, QueryMsg,
+    DEFAULT_PROTOCOL_FEE, DEFAULT_TOTAL_FEE, MAX_PROTOCOL_FEE, MAX_TOTAL_FEE,
+};
+use prismswap::pair::{
+    ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg,
+    MigrateMsg as PairMigrateMsg, StablePoolParams,
 };
-use prismswap::pair::InstantiateMsg as PairInstantiateMsg;
+
+fn xyk_pair_config() -> PairTypeConfig {
+    PairTypeConfig {
+        pair_type: PairType::Xyk {},
+        code_id: 321u64,
+        fee_config: FeeConfig::default(),
+        allow_creation: true,
+    }
+}
 
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
-        pair_code_id: 321u64,
+        pair_configs: vec![xyk_pair_config()],
         token_code_id: 123u64,
         owner: Addr::unchecked("owner0000"),
         collector: Addr::unchecked("collector0000"),
         pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -38,7 +61,6 @@ fn proper_initialization() {
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(123u64, config_res.token_code_id);
-    assert_eq!(321u64, config_res.pair_code_id);
     assert_eq!("owner0000".to_string(), config_res.owner);
     assert_eq!("collector0000".to_string(), config_res.collector);
 }
@@ -48,11 +70,14 @@ fn update_config() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
-        pair_code_id: 321u64,
+        pair_configs: vec![xyk_pair_config()],
         token_code_id: 123u64,
         owner: Addr::unchecked("owner0000"),
         collector: Addr::unchecked("collector0000"),
         pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -64,10 +89,11 @@ fn update_config() {
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
         owner: Some(Addr::unchecked("addr0001")),
-        pair_code_id: None,
         token_code_id: None,
         collector: None,
         pairs_admin: None,
+        pauser: None,
+        permissionless: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -77,41 +103,97 @@ fn update_config() {
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(123u64, config_res.token_code_id);
-    assert_eq!(321u64, config_res.pair_code_id);
     assert_eq!("collector0000".to_string(), config_res.collector);
     assert_eq!("addr0001".to_string(), config_res.owner);
 
+    let asset_infos = [
+        AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        AssetInfo::Cw20(Addr::unchecked("asset0001")),
+    ];
+    simulate_pair_creation(&mut deps, "pairaddr0001", &asset_infos, None);
+
     // update left items
     let env = mock_env();
     let info = mock_info("addr0001", &[]);
     let msg = ExecuteMsg::UpdateConfig {
         owner: None,
-        pair_code_id: Some(100u64),
         token_code_id: Some(200u64),
         collector: Some(Addr::unchecked("collector0001")),
         pairs_admin: None,
+        pauser: Some(Addr::unchecked("pauser0001")),
+        permissionless: None,
     };
 
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(0, res.messages.len());
+    assert!(res.messages.is_empty());
+
+    // the new collector is pushed to the registered pairs page by page, by the owner
+    simulate_pair_creation(
+        &mut deps,
+        "pairaddr0002",
+        &[
+            AssetInfo::Cw20(Addr::unchecked("asset0002")),
+            AssetInfo::Cw20(Addr::unchecked("asset0003")),
+        ],
+        None,
+    );
+    let sync_msgs = |pair_address: &str| {
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: pair_address.to_string(),
+            msg: to_binary(&PairExecuteMsg::UpdateFeeConfig {
+                fee_config: None,
+                collector: Some(Addr::unchecked("collector0001")),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    };
+
+    let msg = ExecuteMsg::SyncPairs {
+        start_after: None,
+        limit: Some(1),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "sync_pairs"), attr("synced_pairs", "1")]
+    );
+    assert_eq!(res.messages, sync_msgs("pairaddr0001"));
+
+    let msg = ExecuteMsg::SyncPairs {
+        start_after: Some(asset_infos.clone()),
+        limit: Some(1),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(res.messages, sync_msgs("pairaddr0002"));
 
     // it worked, let's query the state
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(200u64, config_res.token_code_id);
-    assert_eq!(100u64, config_res.pair_code_id);
     assert_eq!("addr0001".to_string(), config_res.owner);
     assert_eq!("collector0001".to_string(), config_res.collector);
+    assert_eq!("pauser0001".to_string(), config_res.pauser);
 
     // Unauthorized err
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
         owner: None,
-        pair_code_id: None,
         token_code_id: None,
         collector: None,
         pairs_admin: None,
+        pauser: None,
+        permissionless: None,
     };
 
     let res = execute(deps.as_mut(), env, info, msg);
@@ -126,11 +208,14 @@ fn create_pair() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
-        pair_code_id: 321u64,
+        pair_configs: vec![xyk_pair_config()],
         token_code_id: 123u64,
         owner: Addr::unchecked("owner0000"),
         collector: Addr::unchecked("collector0000"),
         pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
     };
 
     let env = mock_env();
@@ -145,8 +230,10 @@ fn create_pair() {
     ];
 
     let msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
         asset_infos: asset_infos.clone(),
         fee_config: None,
+        init_params: None,
     };
 
     // unauthorized attempt
@@ -160,7 +247,9 @@ fn create_pair() {
         res.attributes,
         vec![
             attr("action", "create_pair"),
-            attr("pair", "cw20:asset0000-cw20:asset0001")
+            attr("pair", "cw20:asset0000-cw20:asset0001"),
+            attr("pair_type", "xyk"),
+            attr("creator", "owner0000"),
         ]
     );
     assert_eq!(
@@ -174,23 +263,569 @@ fn create_pair() {
                     factory: Addr::unchecked(MOCK_CONTRACT_ADDR),
                     asset_infos: asset_infos.clone(),
                     token_code_id: 123u64,
+                    fee_config: FeeConfig::default(),
+                    collector: Addr::unchecked("collector0000"),
+                    init_params: None,
                 })
                 .unwrap(),
                 code_id: 321u64,
                 funds: vec![],
                 label: "".to_string(),
-                admin: Some("admin0000".to_string())
+                admin: Some(MOCK_CONTRACT_ADDR.to_string())
+            }
+            .into()
+        },]
+    );
+
+    assert_eq!(
+        TMP_PAIR_INFO.load(&deps.storage).unwrap(),
+        TmpPairInfo {
+            pair_type: PairType::Xyk {},
+            creator: Addr::unchecked("owner0000"),
+            code_id: 321,
+            fee_config: FeeConfig::default(),
+            pair_key: pair_key(&asset_infos),
+        }
+    );
+}
+
+#[test]
+fn pair_type_registry() {
+    let mut deps = mock_dependencies(&[]);
+
+    let stable_pair_config = PairTypeConfig {
+        pair_type: PairType::Stable {},
+        code_id: 322u64,
+        fee_config: FeeConfig {
+            total_fee: Decimal::permille(1),
+            protocol_fee: Decimal::percent(50),
+        },
+        allow_creation: true,
+    };
+
+    // failure - pair type registered twice
+    let msg = InstantiateMsg {
+        pair_configs: vec![xyk_pair_config(), xyk_pair_config()],
+        token_code_id: 123u64,
+        owner: Addr::unchecked("owner0000"),
+        collector: Addr::unchecked("collector0000"),
+        pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
+    };
+    let err = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Pair type xyk is registered twice")
+    );
+
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![xyk_pair_config()],
+        token_code_id: 123u64,
+        owner: Addr::unchecked("owner0000"),
+        collector: Addr::unchecked("collector0000"),
+        pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::Native("uluna".to_string()),
+        AssetInfo::Cw20(Addr::unchecked("asset0000")),
+    ];
+    let init_params = Some(to_binary(&StablePoolParams { amp: 100 }).unwrap());
+    let create_stable_pair = ExecuteMsg::CreatePair {
+        pair_type: PairType::Stable {},
+        asset_infos: asset_infos.clone(),
+        fee_config: None,
+        init_params: init_params.clone(),
+    };
+
+    // failure - the pair type is not registered yet
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        create_stable_pair.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Pair type not found"));
+
+    // failure - only the owner can register pair types
+    let msg = ExecuteMsg::UpdatePairTypeConfig {
+        config: stable_pair_config.clone(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    // failure - invalid default fee config
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdatePairTypeConfig {
+            config: PairTypeConfig {
+                fee_config: FeeConfig {
+                    total_fee: Decimal::from_str(MAX_TOTAL_FEE).unwrap() + Decimal::one(),
+                    protocol_fee: Decimal::zero(),
+                },
+                ..stable_pair_config.clone()
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The given fee configuration is not valid")
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_pair_type_config"),
+            attr("pair_type", "stable"),
+        ]
+    );
+
+    let pair_types: PairTypesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PairTypes {}).unwrap()).unwrap();
+    assert_eq!(
+        pair_types.pair_types,
+        vec![stable_pair_config.clone(), xyk_pair_config()]
+    );
+
+    // the stable pair is created with its own code id, default fees and init params
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        create_stable_pair.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 1,
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+            msg: WasmMsg::Instantiate {
+                msg: to_binary(&PairInstantiateMsg {
+                    factory: Addr::unchecked(MOCK_CONTRACT_ADDR),
+                    asset_infos: asset_infos.clone(),
+                    token_code_id: 123u64,
+                    fee_config: stable_pair_config.fee_config.clone(),
+                    collector: Addr::unchecked("collector0000"),
+                    init_params,
+                })
+                .unwrap(),
+                code_id: 322u64,
+                funds: vec![],
+                label: "".to_string(),
+                admin: Some(MOCK_CONTRACT_ADDR.to_string())
             }
             .into()
         },]
     );
+    assert_eq!(
+        TMP_PAIR_INFO.load(&deps.storage).unwrap(),
+        TmpPairInfo {
+            pair_type: PairType::Stable {},
+            creator: Addr::unchecked("owner0000"),
+            code_id: stable_pair_config.code_id,
+            fee_config: stable_pair_config.fee_config.clone(),
+            pair_key: pair_key(&asset_infos),
+        }
+    );
 
+    // the pair config exposes the pair type
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(vec![10, 8, 112, 97, 105, 114, 48, 48, 48, 48].into()),
+        }),
+    };
+    let pair_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked("pair0000"),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::Stable {},
+    };
+    deps.querier
+        .with_pairs(&[(&"pair0000".to_string(), &pair_info)]);
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let pair_config: PairConfigResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PairConfig {
+                asset_infos: asset_infos.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        pair_config,
+        PairConfigResponse {
+            pair_info,
+            fee_config: stable_pair_config.fee_config.clone(),
+            pair_type: PairType::Stable {},
+            creator: Addr::unchecked("owner0000"),
+        }
+    );
+
+    // failure - creation of the pair type is disabled
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdatePairTypeConfig {
+            config: PairTypeConfig {
+                allow_creation: false,
+                ..stable_pair_config
+            },
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::CreatePair {
+            pair_type: PairType::Stable {},
+            asset_infos: [
+                AssetInfo::Native("uluna".to_string()),
+                AssetInfo::Cw20(Addr::unchecked("asset0001")),
+            ],
+            fee_config: None,
+            init_params: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Pair type creation is disabled"));
+}
+
+#[test]
+fn permissionless_create_pair() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![xyk_pair_config()],
+        token_code_id: 123u64,
+        owner: Addr::unchecked("owner0000"),
+        collector: Addr::unchecked("collector0000"),
+        pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::Native("uusd".to_string()),
+        AssetInfo::Cw20(Addr::unchecked("asset0000")),
+    ];
+    let create_pair = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
+        asset_infos: asset_infos.clone(),
+        fee_config: None,
+        init_params: None,
+    };
+    let creation_fee = Asset {
+        info: AssetInfo::Native("uusd".to_string()),
+        amount: Uint128::from(1_000_000u128),
+    };
+    let fee_funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }];
+
+    // failure - the factory is not permissionless
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &fee_funds),
+        create_pair.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    // failure - only the owner can update the creation fee and the asset status
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[]),
+        ExecuteMsg::UpdateCreationFee {
+            creation_fee: Some(creation_fee.clone()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[]),
+        ExecuteMsg::UpdateAssetStatus {
+            asset_info: AssetInfo::Native("uusd".to_string()),
+            status: Some(AssetStatus::Allowed),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateConfig {
+            owner: None,
+            token_code_id: None,
+            collector: None,
+            pairs_admin: None,
+            pauser: None,
+            permissionless: Some(true),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateCreationFee {
+            creation_fee: Some(creation_fee.clone()),
+        },
+    )
+    .unwrap();
+
+    let config_res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert!(config_res.permissionless);
+    assert_eq!(config_res.creation_fee, Some(creation_fee.clone()));
+
+    // failure - none of the assets is allowed
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &fee_funds),
+        create_pair.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("At least one of the assets must be allowed")
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateAssetStatus {
+            asset_info: AssetInfo::Native("uusd".to_string()),
+            status: Some(AssetStatus::Allowed),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateAssetStatus {
+            asset_info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+            status: Some(AssetStatus::Denied),
+        },
+    )
+    .unwrap();
+
+    let asset_status: AssetStatusResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::AssetStatus {
+                asset_info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(asset_status.status, Some(AssetStatus::Denied));
+
+    // failure - one of the assets is denied
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &fee_funds),
+        create_pair.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Asset cw20:asset0000 is denied"));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::UpdateAssetStatus {
+            asset_info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+            status: None,
+        },
+    )
+    .unwrap();
+
+    // failure - the creation fee is not sent
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[]),
+        create_pair.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Creation fee of native:uusd:1000000 must be sent")
+    );
+
+    // failure - other funds are attached to the creation fee
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "creator0000",
+            &[
+                fee_funds[0].clone(),
+                Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::from(1u128),
+                },
+            ],
+        ),
+        create_pair.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Creation fee of native:uusd:1000000 must be sent")
+    );
+
+    // failure - custom fees are reserved to the owner
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &fee_funds),
+        ExecuteMsg::CreatePair {
+            pair_type: PairType::Xyk {},
+            asset_infos: asset_infos.clone(),
+            fee_config: Some(FeeConfig::default()),
+            init_params: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Only the owner can set a custom fee configuration")
+    );
+
+    // success - the fee is forwarded to the collector net of the tax and the creator is recorded
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1_000_000u128))],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &fee_funds),
+        create_pair,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(990_099u128),
+            }],
+        }))
+    );
+    assert_eq!(
+        TMP_PAIR_INFO.load(&deps.storage).unwrap(),
+        TmpPairInfo {
+            pair_type: PairType::Xyk {},
+            creator: Addr::unchecked("creator0000"),
+            code_id: 321,
+            fee_config: FeeConfig::default(),
+            pair_key: pair_key(&asset_infos),
+        }
+    );
+
+    // cw20 creation fees are transferred from the creator
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateCreationFee {
+            creation_fee: Some(Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("prism0000")),
+                amount: Uint128::from(1_000_000u128),
+            }),
+        },
+    )
+    .unwrap();
+    let create_pair = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0001")),
+        ],
+        fee_config: None,
+        init_params: None,
+    };
+
+    // failure - native funds are attached to a cw20 creation fee
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &fee_funds),
+        create_pair.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Native funds cannot be sent with a cw20 creation fee")
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator0000", &[]),
+        create_pair,
+    )
+    .unwrap();
     assert_eq!(
-        TMP_PAIR_INFO.load(&deps.storage).unwrap(),
-        TmpPairInfo {
-            fee_config: FeeConfig::default(),
-            pair_key: pair_key(&asset_infos),
-        }
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "prism0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "creator0000".to_string(),
+                recipient: "collector0000".to_string(),
+                amount: Uint128::from(1_000_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
     );
 }
 
@@ -208,6 +843,9 @@ fn reply_test() {
         .save(
             &mut deps.storage,
             &TmpPairInfo {
+                pair_type: PairType::Xyk {},
+                creator: Addr::unchecked("owner0000"),
+                code_id: 321,
                 fee_config: FeeConfig::default(),
                 pair_key,
             },
@@ -228,6 +866,7 @@ fn reply_test() {
             asset_infos: asset_infos.clone(),
             contract_addr: Addr::unchecked("pair0000"),
             liquidity_token: Addr::unchecked("liquidity0000"),
+            pair_type: PairType::Xyk {},
         },
     )]);
 
@@ -247,6 +886,7 @@ fn reply_test() {
         pair_res,
         PairInfo {
             liquidity_token: Addr::unchecked("liquidity0000"),
+            pair_type: PairType::Xyk {},
             contract_addr: Addr::unchecked("pair0000".to_string()),
             asset_infos,
         }
@@ -260,12 +900,15 @@ fn simulate_pair_creation(
     contract_addr: &str,
     asset_infos: &[AssetInfo; 2],
     fee_config: Option<FeeConfig>,
-) {
+) -> Response {
     let pair_key = pair_key(asset_infos);
     TMP_PAIR_INFO
         .save(
             deps.as_mut().storage,
             &TmpPairInfo {
+                pair_type: PairType::Xyk {},
+                creator: Addr::unchecked("owner0000"),
+                code_id: 321,
                 pair_key,
                 fee_config: fee_config.unwrap_or_default(),
             },
@@ -284,7 +927,7 @@ fn simulate_pair_creation(
         }),
     };
 
-    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap()
 }
 
 #[test]
@@ -292,11 +935,14 @@ fn create_pair_2() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
-        pair_code_id: 321u64,
+        pair_configs: vec![xyk_pair_config()],
         token_code_id: 123u64,
         owner: Addr::unchecked("owner0000"),
         collector: Addr::unchecked("collector0000"),
         pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
     };
 
     let env = mock_env();
@@ -311,8 +957,10 @@ fn create_pair_2() {
     ];
 
     let msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
         asset_infos: asset_infos.clone(),
         fee_config: None,
+        init_params: None,
     };
 
     // unauthorized attempt
@@ -325,11 +973,13 @@ fn create_pair_2() {
 
     // failure - invalid fee config
     let invalid_fee_msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
         asset_infos: asset_infos.clone(),
         fee_config: Some(FeeConfig {
             total_fee: Decimal::from_str(MAX_TOTAL_FEE).unwrap() + Decimal::one(),
             protocol_fee: Decimal::from_str(DEFAULT_PROTOCOL_FEE).unwrap(),
         }),
+        init_params: None,
     };
     let err = execute(deps.as_mut(), mock_env(), info.clone(), invalid_fee_msg).unwrap_err();
     assert_eq!(
@@ -339,11 +989,13 @@ fn create_pair_2() {
 
     // failure - invalid fee config
     let invalid_msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
         asset_infos: asset_infos.clone(),
         fee_config: Some(FeeConfig {
             total_fee: Decimal::from_str(DEFAULT_TOTAL_FEE).unwrap(),
             protocol_fee: Decimal::from_str(MAX_PROTOCOL_FEE).unwrap() + Decimal::one(),
         }),
+        init_params: None,
     };
     let err = execute(deps.as_mut(), mock_env(), info.clone(), invalid_msg).unwrap_err();
     assert_eq!(
@@ -357,8 +1009,10 @@ fn create_pair_2() {
         AssetInfo::Cw20(Addr::unchecked("asset0001")),
     ];
     let invalid_msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
         asset_infos: asset_infos_bad,
         fee_config: None,
+        init_params: None,
     };
     let err = execute(deps.as_mut(), mock_env(), info.clone(), invalid_msg).unwrap_err();
     assert_eq!(
@@ -372,7 +1026,9 @@ fn create_pair_2() {
         res.attributes,
         vec![
             attr("action", "create_pair"),
-            attr("pair", "cw20:asset0000-cw20:asset0001")
+            attr("pair", "cw20:asset0000-cw20:asset0001"),
+            attr("pair_type", "xyk"),
+            attr("creator", "owner0000"),
         ]
     );
     assert_eq!(
@@ -386,12 +1042,15 @@ fn create_pair_2() {
                     factory: Addr::unchecked(MOCK_CONTRACT_ADDR),
                     asset_infos: asset_infos.clone(),
                     token_code_id: 123u64,
+                    fee_config: FeeConfig::default(),
+                    collector: Addr::unchecked("collector0000"),
+                    init_params: None,
                 })
                 .unwrap(),
                 code_id: 321u64,
                 funds: vec![],
                 label: "".to_string(),
-                admin: Some("admin0000".to_string()),
+                admin: Some(MOCK_CONTRACT_ADDR.to_string()),
             }
             .into()
         },]
@@ -400,6 +1059,9 @@ fn create_pair_2() {
     assert_eq!(
         TMP_PAIR_INFO.load(&deps.storage).unwrap(),
         TmpPairInfo {
+            pair_type: PairType::Xyk {},
+            creator: Addr::unchecked("owner0000"),
+            code_id: 321,
             fee_config: FeeConfig::default(),
             pair_key: pair_key(&asset_infos),
         }
@@ -412,6 +1074,7 @@ fn create_pair_2() {
             asset_infos: asset_infos.clone(),
             contract_addr: Addr::unchecked("pairaddr0001"),
             liquidity_token: Addr::unchecked("liquidity0001"),
+            pair_type: PairType::Xyk {},
         },
     )]);
 
@@ -447,8 +1110,10 @@ fn create_pair_2() {
     };
 
     let msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
         asset_infos: asset_infos.clone(),
         fee_config: Some(custom_fee_config.clone()),
+        init_params: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     simulate_pair_creation(
@@ -463,6 +1128,7 @@ fn create_pair_2() {
             asset_infos: asset_infos.clone(),
             contract_addr: Addr::unchecked("pairaddr0001"),
             liquidity_token: Addr::unchecked("liquidity0001"),
+            pair_type: PairType::Xyk {},
         },
     )]);
 
@@ -484,11 +1150,14 @@ fn test_update_pair_config() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
-        pair_code_id: 321u64,
+        pair_configs: vec![xyk_pair_config()],
         token_code_id: 123u64,
         owner: Addr::unchecked("owner0000"),
         collector: Addr::unchecked("collector0000"),
         pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
     };
 
     let env = mock_env();
@@ -507,8 +1176,10 @@ fn test_update_pair_config() {
     };
 
     let msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
         asset_infos: asset_infos.clone(),
         fee_config: None,
+        init_params: None,
     };
 
     // successful create pair
@@ -523,6 +1194,7 @@ fn test_update_pair_config() {
             asset_infos: asset_infos.clone(),
             contract_addr: Addr::unchecked("pairaddr0001"),
             liquidity_token: Addr::unchecked("liquidity0001"),
+            pair_type: PairType::Xyk {},
         },
     )]);
 
@@ -548,172 +1220,639 @@ fn test_update_pair_config() {
         fee_config: fee_config_updated.clone(),
     };
 
-    let info = mock_info("owner0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
-    assert_eq!(res.attributes, vec![attr("action", "update_pair_config"),]);
-
-    // query new pair config, verify updated correctly
-    let query_res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::PairConfig {
-            asset_infos: asset_infos.clone(),
-        },
-    )
-    .unwrap();
-    let pair_config: PairConfigResponse = from_binary(&query_res).unwrap();
-    assert_eq!(pair_config.fee_config, fee_config_updated);
-
-    // failure - unauthorized
-    let info = mock_info("addr0000", &[]);
-    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "update_pair_config"),]);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "pairaddr0001".to_string(),
+            msg: to_binary(&PairExecuteMsg::UpdateFeeConfig {
+                fee_config: Some(fee_config_updated.clone()),
+                collector: None,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // query new pair config, verify updated correctly
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairConfig {
+            asset_infos: asset_infos.clone(),
+        },
+    )
+    .unwrap();
+    let pair_config: PairConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pair_config.fee_config, fee_config_updated);
+
+    // failure - unauthorized
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    // failure - invalid fee config
+    let info = mock_info("owner0000", &[]);
+    let invalid_msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
+        asset_infos: asset_infos.clone(),
+        fee_config: Some(FeeConfig {
+            total_fee: Decimal::from_str(DEFAULT_TOTAL_FEE).unwrap(),
+            protocol_fee: Decimal::from_str(MAX_PROTOCOL_FEE).unwrap() + Decimal::one(),
+        }),
+        init_params: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, invalid_msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The given fee configuration is not valid")
+    );
+
+    // failure - no pair exists
+    let info = mock_info("owner0000", &[]);
+    let asset_infos_bad = [
+        AssetInfo::Cw20(Addr::unchecked("asset0002")),
+        AssetInfo::Cw20(Addr::unchecked("asset0003")),
+    ];
+    let msg_bad = ExecuteMsg::UpdatePairConfig {
+        asset_infos: asset_infos_bad,
+        fee_config: fee_config_updated.clone(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg_bad).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("There is no pair registered with the provided info")
+    );
+
+    // failure - invalid token
+    let asset_infos_bad = [
+        AssetInfo::Cw20(Addr::unchecked("te")),
+        AssetInfo::Cw20(Addr::unchecked("asset0001")),
+    ];
+    let msg_bad = ExecuteMsg::UpdatePairConfig {
+        asset_infos: asset_infos_bad,
+        fee_config: fee_config_updated,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg_bad).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Invalid input: human address too short")
+    );
+}
+
+#[test]
+fn test_deregister() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![xyk_pair_config()],
+        token_code_id: 123u64,
+        owner: Addr::unchecked("owner0000"),
+        collector: Addr::unchecked("collector0000"),
+        pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        AssetInfo::Cw20(Addr::unchecked("asset0001")),
+    ];
+
+    let msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
+        asset_infos: asset_infos.clone(),
+        fee_config: None,
+        init_params: None,
+    };
+
+    // successful create pair
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // simulate created pair
+    simulate_pair_creation(&mut deps, "pairaddr0001", &asset_infos, None);
+    deps.querier.with_pairs(&[(
+        &"pairaddr0001".to_string(),
+        &PairInfo {
+            asset_infos: asset_infos.clone(),
+            contract_addr: Addr::unchecked("pairaddr0001"),
+            liquidity_token: Addr::unchecked("liquidity0001"),
+            pair_type: PairType::Xyk {},
+        },
+    )]);
+
+    // query pair config, verify successful creation
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairConfig {
+            asset_infos: asset_infos.clone(),
+        },
+    )
+    .unwrap();
+    let pair_config: PairConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pair_config.fee_config, FeeConfig::default());
+
+    // failure - unauthorized
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::Deregister {
+        asset_infos: asset_infos.clone(),
+        reason: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    // failure - no pair exists
+    let info = mock_info("owner0000", &[]);
+    let asset_infos_bad = [
+        AssetInfo::Cw20(Addr::unchecked("asset0002")),
+        AssetInfo::Cw20(Addr::unchecked("asset0003")),
+    ];
+    let msg_bad = ExecuteMsg::Deregister {
+        asset_infos: asset_infos_bad,
+        reason: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg_bad).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("There is no pair registered with the provided info")
+    );
+
+    // failure - invalid token
+    let asset_infos_bad = [
+        AssetInfo::Cw20(Addr::unchecked("te")),
+        AssetInfo::Cw20(Addr::unchecked("asset0001")),
+    ];
+    let msg_bad = ExecuteMsg::Deregister {
+        asset_infos: asset_infos_bad,
+        reason: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg_bad).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Invalid input: human address too short")
+    );
+
+    // success
+    let msg = ExecuteMsg::Deregister {
+        asset_infos: asset_infos.clone(),
+        reason: Some("exploited asset".to_string()),
+    };
+    let env = mock_env();
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "pairaddr0001".to_string(),
+            msg: to_binary(&PairExecuteMsg::UpdatePairStatus {
+                paused: false,
+                deregistered: true,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deregister"),
+            attr("pair_contract_addr", "pairaddr0001"),
+        ]
+    );
+
+    // the pair is archived and no longer registered for its assets
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::DeregisteredPairs {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let deregistered_pairs: DeregisteredPairsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        deregistered_pairs.pairs,
+        vec![DeregisteredPairResponse {
+            pair_address: Addr::unchecked("pairaddr0001"),
+            asset_infos: asset_infos.clone(),
+            pair_type: PairType::Xyk {},
+            fee_config: FeeConfig::default(),
+            deregistered_at: env.block.height,
+            reason: Some("exploited asset".to_string()),
+        }]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::DeregisteredPairs {
+            start_after: Some("pairaddr0001".to_string()),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let deregistered_pairs: DeregisteredPairsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(deregistered_pairs.pairs, vec![]);
+
+    // the fee settings of the deregistered pair are only read from the archive by its address
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::FeeInfo {
+            asset_infos: asset_infos.clone(),
+            pair_address: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("There is no pair registered with the provided info")
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::FeeInfo {
+            asset_infos: asset_infos.clone(),
+            pair_address: Some("pairaddr0001".to_string()),
+        },
+    )
+    .unwrap();
+    let fee_info: FeeInfoResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        fee_info,
+        FeeInfoResponse {
+            fee_config: FeeConfig::default(),
+            collector: Addr::unchecked("collector0000"),
+        }
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PauseInfo {
+            asset_infos: asset_infos.clone(),
+        },
+    )
+    .unwrap();
+    let pause_info: PauseInfoResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pause_info.registered_pair, None);
+}
+
+#[test]
+fn pause_pairs() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![xyk_pair_config()],
+        token_code_id: 123u64,
+        owner: Addr::unchecked("owner0000"),
+        collector: Addr::unchecked("collector0000"),
+        pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        AssetInfo::Cw20(Addr::unchecked("asset0001")),
+    ];
+    let other_asset_infos = [
+        AssetInfo::Native("uusd".to_string()),
+        AssetInfo::Cw20(Addr::unchecked("asset0001")),
+    ];
+    let query_pause_info = |deps: &OwnedDeps<MemoryStorage, MockApi, WasmMockQuerier>,
+                            asset_infos: &[AssetInfo; 2]| {
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PauseInfo {
+                asset_infos: asset_infos.clone(),
+            },
+        )
+        .unwrap();
+        from_binary::<PauseInfoResponse>(&res).unwrap()
+    };
+    assert!(!query_pause_info(&deps, &asset_infos).is_paused());
+    let status_msg = |pair_address: &str, paused: bool| {
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: pair_address.to_string(),
+            msg: to_binary(&PairExecuteMsg::UpdatePairStatus {
+                paused,
+                deregistered: false,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+
+    // failure - only the pauser can pause, not even the owner
+    let msg = ExecuteMsg::UpdatePauseStatus {
+        asset_infos: Some(asset_infos.clone()),
+        paused: true,
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap_err();
     assert_eq!(err, StdError::generic_err("unauthorized"));
 
-    // failure - invalid fee config
-    let info = mock_info("owner0000", &[]);
-    let invalid_msg = ExecuteMsg::CreatePair {
-        asset_infos: asset_infos.clone(),
-        fee_config: Some(FeeConfig {
-            total_fee: Decimal::from_str(DEFAULT_TOTAL_FEE).unwrap(),
-            protocol_fee: Decimal::from_str(MAX_PROTOCOL_FEE).unwrap() + Decimal::one(),
-        }),
+    // pause a single pair, the reversed asset order identifies the same pair
+    let msg = ExecuteMsg::UpdatePauseStatus {
+        asset_infos: Some([asset_infos[1].clone(), asset_infos[0].clone()]),
+        paused: true,
     };
-    let err = execute(deps.as_mut(), mock_env(), info, invalid_msg).unwrap_err();
+    let res = execute(deps.as_mut(), mock_env(), mock_info("pauser0000", &[]), msg).unwrap();
     assert_eq!(
-        err,
-        StdError::generic_err("The given fee configuration is not valid")
+        res.attributes,
+        vec![
+            attr("action", "update_pause_status"),
+            attr("pair", "cw20:asset0001-cw20:asset0000"),
+            attr("paused", "true"),
+        ]
+    );
+    assert_eq!(
+        query_pause_info(&deps, &asset_infos),
+        PauseInfoResponse {
+            global_paused: false,
+            pair_paused: true,
+            registered_pair: None,
+        }
     );
+    assert!(!query_pause_info(&deps, &other_asset_infos).is_paused());
+    assert_eq!(res.messages, vec![]);
 
-    // failure - no pair exists
-    let info = mock_info("owner0000", &[]);
-    let asset_infos_bad = [
-        AssetInfo::Cw20(Addr::unchecked("asset0002")),
-        AssetInfo::Cw20(Addr::unchecked("asset0003")),
-    ];
-    let msg_bad = ExecuteMsg::UpdatePairConfig {
-        asset_infos: asset_infos_bad,
-        fee_config: fee_config_updated.clone(),
+    // a pair created while paused receives its status
+    let res = simulate_pair_creation(&mut deps, "pairaddr0001", &asset_infos, None);
+    assert_eq!(res.messages, vec![status_msg("pairaddr0001", true)]);
+
+    // pause every pair, the pairs query the global pause from the factory
+    let msg = ExecuteMsg::UpdatePauseStatus {
+        asset_infos: None,
+        paused: true,
     };
-    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg_bad).unwrap_err();
+    let res = execute(deps.as_mut(), mock_env(), mock_info("pauser0000", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![]);
     assert_eq!(
-        err,
-        StdError::generic_err("There is no pair registered with the provided info")
+        query_pause_info(&deps, &other_asset_infos),
+        PauseInfoResponse {
+            global_paused: true,
+            pair_paused: false,
+            registered_pair: None,
+        }
     );
 
-    // failure - invalid token
-    let asset_infos_bad = [
-        AssetInfo::Cw20(Addr::unchecked("te")),
-        AssetInfo::Cw20(Addr::unchecked("asset0001")),
-    ];
-    let msg_bad = ExecuteMsg::UpdatePairConfig {
-        asset_infos: asset_infos_bad,
-        fee_config: fee_config_updated,
+    let res = simulate_pair_creation(&mut deps, "pairaddr0002", &other_asset_infos, None);
+    assert_eq!(res.messages, vec![]);
+
+    // resume every pair, the single pair stays paused
+    let msg = ExecuteMsg::UpdatePauseStatus {
+        asset_infos: None,
+        paused: false,
     };
-    let err = execute(deps.as_mut(), mock_env(), info, msg_bad).unwrap_err();
+    execute(deps.as_mut(), mock_env(), mock_info("pauser0000", &[]), msg).unwrap();
+    assert!(!query_pause_info(&deps, &other_asset_infos).is_paused());
+    assert!(query_pause_info(&deps, &asset_infos).is_paused());
+
+    // resuming the registered pair pushes its status
+    let msg = ExecuteMsg::UpdatePauseStatus {
+        asset_infos: Some(asset_infos.clone()),
+        paused: false,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("pauser0000", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![status_msg("pairaddr0001", false)]);
+    assert!(!query_pause_info(&deps, &asset_infos).is_paused());
+
+    // deregistering a paused pair clears its pause, a new pair for the assets starts unpaused
+    let msg = ExecuteMsg::UpdatePauseStatus {
+        asset_infos: Some(other_asset_infos.clone()),
+        paused: true,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("pauser0000", &[]), msg).unwrap();
+    let msg = ExecuteMsg::Deregister {
+        asset_infos: other_asset_infos.clone(),
+        reason: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
     assert_eq!(
-        err,
-        StdError::generic_err("Invalid input: human address too short")
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "pairaddr0002".to_string(),
+            msg: to_binary(&PairExecuteMsg::UpdatePairStatus {
+                paused: false,
+                deregistered: true,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
     );
+    assert!(!query_pause_info(&deps, &other_asset_infos).is_paused());
+
+    let res = simulate_pair_creation(&mut deps, "pairaddr0003", &other_asset_infos, None);
+    assert_eq!(res.messages, vec![]);
 }
 
 #[test]
-fn test_deregister() {
+fn migrate_pairs() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
-        pair_code_id: 321u64,
+        pair_configs: vec![xyk_pair_config()],
         token_code_id: 123u64,
         owner: Addr::unchecked("owner0000"),
         collector: Addr::unchecked("collector0000"),
         pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
     };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
 
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    let asset_infos = [
+    let asset_infos_0 = [
         AssetInfo::Cw20(Addr::unchecked("asset0000")),
         AssetInfo::Cw20(Addr::unchecked("asset0001")),
     ];
+    let asset_infos_1 = [
+        AssetInfo::Cw20(Addr::unchecked("asset0002")),
+        AssetInfo::Cw20(Addr::unchecked("asset0003")),
+    ];
+    simulate_pair_creation(&mut deps, "pairaddr0001", &asset_infos_0, None);
+    simulate_pair_creation(&mut deps, "pairaddr0002", &asset_infos_1, None);
 
+    // the factory is the admin of the pairs it creates, so it can migrate them
     let msg = ExecuteMsg::CreatePair {
-        asset_infos: asset_infos.clone(),
+        pair_type: PairType::Xyk {},
+        asset_infos: [
+            AssetInfo::Cw20(Addr::unchecked("asset0004")),
+            AssetInfo::Cw20(Addr::unchecked("asset0005")),
+        ],
         fee_config: None,
+        init_params: None,
     };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { admin, .. }) => {
+            assert_eq!(admin, &Some(MOCK_CONTRACT_ADDR.to_string()))
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // successful create pair
-    let info = mock_info("owner0000", &[]);
-    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    // simulate created pair
-    simulate_pair_creation(&mut deps, "pairaddr0001", &asset_infos, None);
-    deps.querier.with_pairs(&[(
-        &"pairaddr0001".to_string(),
-        &PairInfo {
-            asset_infos: asset_infos.clone(),
-            contract_addr: Addr::unchecked("pairaddr0001"),
-            liquidity_token: Addr::unchecked("liquidity0001"),
-        },
-    )]);
-
-    // query pair config, verify successful creation
-    let query_res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::PairConfig {
-            asset_infos: asset_infos.clone(),
+    // register the new xyk code
+    let msg = ExecuteMsg::UpdatePairTypeConfig {
+        config: PairTypeConfig {
+            code_id: 400u64,
+            ..xyk_pair_config()
         },
-    )
-    .unwrap();
-    let pair_config: PairConfigResponse = from_binary(&query_res).unwrap();
-    assert_eq!(pair_config.fee_config, FeeConfig::default());
-
-    // failure - unauthorized
-    let info = mock_info("addr0000", &[]);
-    let msg = ExecuteMsg::Deregister {
-        asset_infos: asset_infos.clone(),
     };
-    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    // failure - unauthorized, even the owner
+    let msg = ExecuteMsg::MigratePairs {
+        code_id: 400u64,
+        start_after: None,
+        limit: Some(1),
+        msg: to_binary(&PairMigrateMsg {}).unwrap(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap_err();
     assert_eq!(err, StdError::generic_err("unauthorized"));
 
-    // failure - no pair exists
-    let info = mock_info("owner0000", &[]);
-    let asset_infos_bad = [
-        AssetInfo::Cw20(Addr::unchecked("asset0002")),
-        AssetInfo::Cw20(Addr::unchecked("asset0003")),
-    ];
-    let msg_bad = ExecuteMsg::Deregister {
-        asset_infos: asset_infos_bad,
+    // failure - unregistered code id
+    let msg = ExecuteMsg::MigratePairs {
+        code_id: 321u64,
+        start_after: None,
+        limit: Some(1),
+        msg: to_binary(&PairMigrateMsg {}).unwrap(),
     };
-    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg_bad).unwrap_err();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("admin0000", &[]), msg).unwrap_err();
     assert_eq!(
         err,
-        StdError::generic_err("There is no pair registered with the provided info")
+        StdError::generic_err("No pair type is registered with code id 321")
     );
 
-    // failure - invalid token
-    let asset_infos_bad = [
-        AssetInfo::Cw20(Addr::unchecked("te")),
-        AssetInfo::Cw20(Addr::unchecked("asset0001")),
-    ];
-    let msg_bad = ExecuteMsg::Deregister {
-        asset_infos: asset_infos_bad,
+    // migrate the first page
+    let msg = ExecuteMsg::MigratePairs {
+        code_id: 400u64,
+        start_after: None,
+        limit: Some(1),
+        msg: to_binary(&PairMigrateMsg {}).unwrap(),
     };
-    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg_bad).unwrap_err();
+    let res = execute(deps.as_mut(), mock_env(), mock_info("admin0000", &[]), msg).unwrap();
     assert_eq!(
-        err,
-        StdError::generic_err("Invalid input: human address too short")
+        res.attributes,
+        vec![
+            attr("action", "migrate_pairs"),
+            attr("code_id", "400"),
+            attr("migrated_pairs", "1"),
+            attr("skipped_pairs", ""),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Migrate {
+            contract_addr: "pairaddr0001".to_string(),
+            new_code_id: 400u64,
+            msg: to_binary(&PairMigrateMsg {}).unwrap(),
+        })]
     );
 
-    // success
-    let msg = ExecuteMsg::Deregister {
-        asset_infos: asset_infos.clone(),
+    let res: PairCodeIdsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PairCodeIds {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.pairs,
+        vec![
+            PairCodeIdResponse {
+                pair_address: Addr::unchecked("pairaddr0001"),
+                pair_type: PairType::Xyk {},
+                code_id: 400u64,
+            },
+            PairCodeIdResponse {
+                pair_address: Addr::unchecked("pairaddr0002"),
+                pair_type: PairType::Xyk {},
+                code_id: 321u64,
+            },
+        ]
+    );
+
+    // pairs administered by another address are skipped and keep their code id
+    deps.querier
+        .with_contract_admin("pairaddr0002", "admin0000");
+    let msg = ExecuteMsg::MigratePairs {
+        code_id: 400u64,
+        start_after: None,
+        limit: None,
+        msg: to_binary(&PairMigrateMsg {}).unwrap(),
     };
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(res.messages, vec![]);
-    assert_eq!(res.attributes, vec![attr("action", "deregister")]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_pairs"),
+            attr("code_id", "400"),
+            attr("migrated_pairs", "0"),
+            attr("skipped_pairs", "pairaddr0002"),
+        ]
+    );
+    assert!(res.messages.is_empty());
+    let res: PairCodeIdsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PairCodeIds {
+                start_after: Some(asset_infos_0.clone()),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.pairs[0].code_id, 321u64);
+
+    // pairs already on the code are skipped, the handed over pair is migrated
+    deps.querier
+        .with_contract_admin("pairaddr0002", MOCK_CONTRACT_ADDR);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Migrate {
+            contract_addr: "pairaddr0002".to_string(),
+            new_code_id: 400u64,
+            msg: to_binary(&PairMigrateMsg {}).unwrap(),
+        })]
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("admin0000", &[]), msg).unwrap();
+    assert!(res.messages.is_empty());
 }
 
 #[test]
@@ -721,11 +1860,14 @@ fn test_queries() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
-        pair_code_id: 321u64,
+        pair_configs: vec![xyk_pair_config()],
         token_code_id: 123u64,
         owner: Addr::unchecked("owner0000"),
         collector: Addr::unchecked("collector0000"),
         pairs_admin: Addr::unchecked("admin0000"),
+        pauser: Addr::unchecked("pauser0000"),
+        permissionless: false,
+        creation_fee: None,
     };
 
     let env = mock_env();
@@ -739,8 +1881,10 @@ fn test_queries() {
     ];
 
     let msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
         asset_infos: asset_infos1.clone(),
         fee_config: None,
+        init_params: None,
     };
 
     // successful create pair
@@ -761,8 +1905,10 @@ fn test_queries() {
     };
 
     let msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
         asset_infos: asset_infos2.clone(),
         fee_config: Some(fee_config2.clone()),
+        init_params: None,
     };
 
     // successful create pair
@@ -784,6 +1930,7 @@ fn test_queries() {
                 asset_infos: asset_infos1.clone(),
                 contract_addr: Addr::unchecked("pairaddr0001"),
                 liquidity_token: Addr::unchecked("liquidity0001"),
+                pair_type: PairType::Xyk {},
             },
         ),
         (
@@ -792,6 +1939,7 @@ fn test_queries() {
                 asset_infos: asset_infos2.clone(),
                 contract_addr: Addr::unchecked("pairaddr0002"),
                 liquidity_token: Addr::unchecked("liquidity0002"),
+                pair_type: PairType::Xyk {},
             },
         ),
     ]);
@@ -816,12 +1964,14 @@ fn test_queries() {
                 PairInfo {
                     asset_infos: asset_infos1.clone(),
                     contract_addr: Addr::unchecked("pairaddr0001"),
-                    liquidity_token: Addr::unchecked("liquidity0001")
+                    liquidity_token: Addr::unchecked("liquidity0001"),
+                    pair_type: PairType::Xyk {},
                 },
                 PairInfo {
                     asset_infos: asset_infos2.clone(),
                     contract_addr: Addr::unchecked("pairaddr0002"),
-                    liquidity_token: Addr::unchecked("liquidity0002")
+                    liquidity_token: Addr::unchecked("liquidity0002"),
+                    pair_type: PairType::Xyk {},
                 },
             ]
         }
@@ -846,7 +1996,8 @@ fn test_queries() {
             pairs: vec![PairInfo {
                 asset_infos: asset_infos1.clone(),
                 contract_addr: Addr::unchecked("pairaddr0001"),
-                liquidity_token: Addr::unchecked("liquidity0001")
+                liquidity_token: Addr::unchecked("liquidity0001"),
+                pair_type: PairType::Xyk {},
             },]
         }
     );
@@ -870,7 +2021,8 @@ fn test_queries() {
             pairs: vec![PairInfo {
                 asset_infos: asset_infos2.clone(),
                 contract_addr: Addr::unchecked("pairaddr0002"),
-                liquidity_token: Addr::unchecked("liquidity0002")
+                liquidity_token: Addr::unchecked("liquidity0002"),
+                pair_type: PairType::Xyk {},
             },]
         }
     );
@@ -882,6 +2034,7 @@ fn test_queries() {
             mock_env(),
             QueryMsg::FeeInfo {
                 asset_infos: asset_infos1.clone(),
+                pair_address: None,
             },
         )
         .unwrap(),
@@ -902,6 +2055,7 @@ fn test_queries() {
             mock_env(),
             QueryMsg::FeeInfo {
                 asset_infos: asset_infos2.clone(),
+                pair_address: None,
             },
         )
         .unwrap(),
@@ -936,17 +2090,23 @@ fn test_queries() {
                     pair_info: PairInfo {
                         asset_infos: asset_infos1.clone(),
                         contract_addr: Addr::unchecked("pairaddr0001"),
-                        liquidity_token: Addr::unchecked("liquidity0001")
+                        liquidity_token: Addr::unchecked("liquidity0001"),
+                        pair_type: PairType::Xyk {},
                     },
-                    fee_config: FeeConfig::default()
+                    fee_config: FeeConfig::default(),
+                    pair_type: PairType::Xyk {},
+                    creator: Addr::unchecked("owner0000"),
                 },
                 PairConfigResponse {
                     pair_info: PairInfo {
                         asset_infos: asset_infos2.clone(),
                         contract_addr: Addr::unchecked("pairaddr0002"),
-                        liquidity_token: Addr::unchecked("liquidity0002")
+                        liquidity_token: Addr::unchecked("liquidity0002"),
+                        pair_type: PairType::Xyk {},
                     },
-                    fee_config: fee_config2.clone()
+                    fee_config: fee_config2.clone(),
+                    pair_type: PairType::Xyk {},
+                    creator: Addr::unchecked("owner0000"),
                 },
             ]
         }
@@ -972,9 +2132,12 @@ fn test_queries() {
                 pair_info: PairInfo {
                     asset_infos: asset_infos1.clone(),
                     contract_addr: Addr::unchecked("pairaddr0001"),
-                    liquidity_token: Addr::unchecked("liquidity0001")
+                    liquidity_token: Addr::unchecked("liquidity0001"),
+                    pair_type: PairType::Xyk {},
                 },
-                fee_config: FeeConfig::default()
+                fee_config: FeeConfig::default(),
+                pair_type: PairType::Xyk {},
+                creator: Addr::unchecked("owner0000"),
             },]
         }
     );
@@ -999,10 +2162,156 @@ fn test_queries() {
                 pair_info: PairInfo {
                     asset_infos: asset_infos2.clone(),
                     contract_addr: Addr::unchecked("pairaddr0002"),
-                    liquidity_token: Addr::unchecked("liquidity0002")
+                    liquidity_token: Addr::unchecked("liquidity0002"),
+                    pair_type: PairType::Xyk {},
                 },
-                fee_config: fee_config2
+                fee_config: fee_config2,
+                pair_type: PairType::Xyk {},
+                creator: Addr::unchecked("owner0000"),
             },]
         }
     );
 }
+
+#[test]
+fn migration() {
+    let mut deps = mock_dependencies(&[]);
+
+    LEGACY
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                owner: Addr::unchecked("owner0000"),
+                pair_code_id: 33,
+                token_code_id: 2,
+                collector: Addr::unchecked("collector0000"),
+                pairs_admin: Addr::unchecked("admin0000"),
+            },
+        )
+        .unwrap();
+    let asset_infos = [
+        AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        AssetInfo::Cw20(Addr::unchecked("asset0001")),
+    ];
+    LEGACY_PAIRS
+        .save(
+            deps.as_mut().storage,
+            &pair_key(&asset_infos),
+            &LegacyPairConfig {
+                pair_address: Addr::unchecked("pairaddr0000"),
+                fee_config: FeeConfig::default(),
+            },
+        )
+        .unwrap();
+
+    // the unversioned code is migrated from the legacy config
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            pairs_admin: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("previous_version", "unversioned"),
+            attr("new_version", CONTRACT_VERSION),
+            attr("legacy_pairs_pending", "false"),
+        ]
+    );
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.contract, CONTRACT_NAME);
+    assert_eq!(version.version, CONTRACT_VERSION);
+
+    let config: Config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.pairs_admin, Addr::unchecked("admin0000"));
+
+    // the legacy pairs reject the pushes, they read the factory state once migrated
+    let msg = ExecuteMsg::UpdatePairConfig {
+        asset_infos: asset_infos.clone(),
+        fee_config: FeeConfig {
+            total_fee: Decimal::permille(5),
+            protocol_fee: Decimal::percent(20),
+        },
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert!(res.messages.is_empty());
+
+    let msg = ExecuteMsg::SyncPairs {
+        start_after: None,
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert!(res.messages.is_empty());
+
+    let msg = ExecuteMsg::UpdatePauseStatus {
+        asset_infos: Some(asset_infos.clone()),
+        paused: true,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert!(res.messages.is_empty());
+
+    let msg = ExecuteMsg::Deregister {
+        asset_infos,
+        reason: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert!(res.messages.is_empty());
+
+    // versioned factories keep their config, the legacy layout is not read again
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "1.1.0").unwrap();
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            pairs_admin: Some(Addr::unchecked("admin0001")),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let config: Config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.pairs_admin, Addr::unchecked("admin0001"));
+    assert_eq!(config.pauser, Addr::unchecked("owner0000"));
+
+    // refuse downgrades
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            pairs_admin: None,
+            limit: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "Cannot migrate from version 99.0.0 to the older version {}",
+            CONTRACT_VERSION
+        ))
+    );
+
+    // refuse other contracts
+    set_contract_version(deps.as_mut().storage, "crates.io:prismswap-pair", "1.0.0").unwrap();
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            pairs_admin: None,
+            limit: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "Cannot migrate from crates.io:prismswap-pair to {}",
+            CONTRACT_NAME
+        ))
+    );
+}
@@ -5,8 +5,10 @@ use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use prismswap::asset::PairInfo;
 use prismswap::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg, ReverseSimulationResponse,
-    SimulationResponse,
+    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, HooksResponse, InstantiateMsg, MigrateMsg,
+    OrderResponse, OrdersResponse, PairHookMsg, PendingProtocolFeeResponse, PoolResponse,
+    PriceImpactResponse, QueryMsg, ReverseSimulationResponse, SimulateProvideResponse,
+    SimulateWithdrawResponse, SimulationResponse, SpotPriceResponse, StatsResponse,
 };
 
 fn main() {
@@ -18,9 +20,21 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(PairInfo), &out_dir);
     export_schema(&schema_for!(PoolResponse), &out_dir);
     export_schema(&schema_for!(ReverseSimulationResponse), &out_dir);
     export_schema(&schema_for!(SimulationResponse), &out_dir);
+    export_schema(&schema_for!(SimulateProvideResponse), &out_dir);
+    export_schema(&schema_for!(SimulateWithdrawResponse), &out_dir);
+    export_schema(&schema_for!(SpotPriceResponse), &out_dir);
+    export_schema(&schema_for!(PriceImpactResponse), &out_dir);
+    export_schema(&schema_for!(CumulativePricesResponse), &out_dir);
+    export_schema(&schema_for!(PendingProtocolFeeResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(HooksResponse), &out_dir);
+    export_schema(&schema_for!(PairHookMsg), &out_dir);
+    export_schema(&schema_for!(OrderResponse), &out_dir);
+    export_schema(&schema_for!(OrdersResponse), &out_dir);
 }
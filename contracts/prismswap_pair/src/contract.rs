@@ -1,28 +1,56 @@
-use crate::error::ContractError;
-use crate::parse_reply::parse_reply_instantiate_data;
-use crate::state::{Config, CONFIG};
+use crate::migration::{migrate_config, migrate_reserves, migrate_root_k_last};
+use crate::state::{
+    read_orders_by_owner, read_orders_by_price, remove_order, store_order, Config, LimitOrder,
+    CONFIG, FLASH_SWAP, NEXT_ORDER_ID, ORDERS, ORDER_ESCROW, RESERVES, ROOT_K_LAST,
+};
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
-    QuerierWrapper, Reply, ReplyOn, Response, StdError, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Attribute, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg,
+    Uint128, WasmMsg,
 };
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cw2::{set_contract_version, CONTRACT};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw_storage_plus::U64Key;
 use integer_sqrt::IntegerSquareRoot;
-use prismswap::asset::{Asset, AssetInfo, PairInfo, PrismSwapAsset, PrismSwapAssetInfo};
-use prismswap::factory::FeeInfoResponse;
+use prismswap::assert::{
+    assert_deadline, assert_migration_version, assert_min_assets, assert_pair_active,
+};
+use prismswap::asset::{
+    format_lp_token_name, Asset, AssetInfo, PairInfo, PrismSwapAsset, PrismSwapAssetInfo,
+};
+use prismswap::error::ContractError;
+use prismswap::factory::{FeeConfig, PairType};
+use prismswap::fee_info::{self, migrate_fee_info, FeeInfo, FEE_INFO};
+use prismswap::hooks::{add_hook, hook_messages, remove_hook, HOOKS, HOOK_REPLY_ID};
 use prismswap::pair::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashSwapCallbackMsg,
+    HooksResponse, InstantiateMsg, MigrateMsg, OrderResponse, OrdersResponse, PairHookMsg,
+    PendingProtocolFeeResponse, PoolResponse, PriceImpactResponse, QueryMsg,
+    ReverseSimulationResponse, SimulateProvideResponse, SimulateWithdrawResponse,
+    SimulationResponse, SpotPriceResponse, StatsResponse, MAX_ORDER_FILLS,
+    MINIMUM_LIQUIDITY_AMOUNT, MIN_ORDER_AMOUNT, TWAP_PRECISION,
 };
-use prismswap::querier::{query_fee_info, query_supply, query_token_symbol};
+use prismswap::pair_status::{self, migrate_pair_status, PairStatus, PAIR_STATUS};
+use prismswap::parse_reply::parse_reply_instantiate_data;
+use prismswap::pool::{compute_deposit_reserves, compute_refund_amounts, reserve_assets};
+use prismswap::querier::{query_factory_config, query_supply};
+use prismswap::stats::{record_trade, Stats, STATS};
 use prismswap::token::InstantiateMsg as TokenInstantiateMsg;
 
+/// contract name that is used for migration
+pub const CONTRACT_NAME: &str = "crates.io:prismswap-pair";
+/// contract version that is used for migration
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 const INSTANTIATE_REPLY_ID: u64 = 1;
+/// the atomics of one [`Decimal`]
+const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -43,11 +71,25 @@ pub fn instantiate(
             contract_addr: env.contract.address.clone(),
             liquidity_token: Addr::unchecked(""),
             asset_infos: msg.asset_infos.clone(),
+            pair_type: PairType::Xyk {},
         },
         factory: msg.factory,
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+        block_time_last: env.block.time.seconds(),
     };
 
     CONFIG.save(deps.storage, &config)?;
+    FEE_INFO.save(
+        deps.storage,
+        &FeeInfo {
+            fee_config: msg.fee_config,
+            collector: msg.collector,
+        },
+    )?;
+    PAIR_STATUS.save(deps.storage, &PairStatus::default())?;
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
@@ -82,23 +124,45 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // the pool is unbalanced until the flash swap is finalized
+    if FLASH_SWAP.may_load(deps.storage)?.is_some()
+        && !matches!(msg, ExecuteMsg::FinalizeFlashSwap {})
+    {
+        return Err(ContractError::FlashSwapInProgress {});
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ProvideLiquidity {
             assets,
             slippage_tolerance,
             receiver,
+            deadline,
         } => {
+            assert_deadline(&env, deadline)?;
             assets[0].info.check(deps.api)?;
             assets[1].info.check(deps.api)?;
             provide_liquidity(deps, env, info, assets, slippage_tolerance, receiver)
         }
+        ExecuteMsg::ProvideSingleAsset {
+            asset,
+            max_spread,
+            min_share,
+            receiver,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            asset.info.check(deps.api)?;
+            provide_single_asset(deps, env, info, asset, max_spread, min_share, receiver)
+        }
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            deadline,
         } => {
+            assert_deadline(&env, deadline)?;
             offer_asset.info.check(deps.api)?;
             if !offer_asset.info.is_native_token() {
                 return Err(ContractError::Unauthorized {});
@@ -121,6 +185,91 @@ pub fn execute(
                 to_addr,
             )
         }
+        ExecuteMsg::SwapExactOutput {
+            offer_asset,
+            ask_amount,
+            max_offer,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            offer_asset.info.check(deps.api)?;
+            if !offer_asset.info.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(&to_addr)?)
+            } else {
+                None
+            };
+
+            swap_exact_output(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                ask_amount,
+                max_offer,
+                to_addr,
+            )
+        }
+        ExecuteMsg::UpdateConfig { .. } => Err(ContractError::NonSupported {}),
+        ExecuteMsg::UpdateFeeConfig {
+            fee_config,
+            collector,
+        } => update_fee_config(deps, info, fee_config, collector),
+        ExecuteMsg::UpdatePairStatus {
+            paused,
+            deregistered,
+        } => {
+            let config: Config = CONFIG.load(deps.storage)?;
+            pair_status::update_pair_status(
+                deps.storage,
+                &info,
+                &config.factory,
+                PairStatus {
+                    paused,
+                    deregistered,
+                },
+            )
+        }
+        ExecuteMsg::Sync {} => sync(deps, env, info),
+        ExecuteMsg::Skim { to } => {
+            let to_addr = deps.api.addr_validate(&to)?;
+            skim(deps.as_ref(), env, to_addr)
+        }
+        ExecuteMsg::FlashSwap {
+            ask_asset,
+            msg,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            ask_asset.info.check(deps.api)?;
+            flash_swap(deps, env, info, ask_asset, msg)
+        }
+        ExecuteMsg::FinalizeFlashSwap {} => finalize_flash_swap(deps, env, info),
+        ExecuteMsg::AddHook {
+            contract_addr,
+            gas_limit,
+        } => {
+            let config: Config = CONFIG.load(deps.storage)?;
+            add_hook(deps, info, &config.factory, contract_addr, gas_limit)
+        }
+        ExecuteMsg::RemoveHook { contract_addr } => {
+            let config: Config = CONFIG.load(deps.storage)?;
+            remove_hook(deps, info, &config.factory, contract_addr)
+        }
+        ExecuteMsg::SubmitOrder { offer_asset, price } => {
+            offer_asset.info.check(deps.api)?;
+            if !offer_asset.info.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            submit_order(deps, env, info.clone(), info.sender, offer_asset, price)
+        }
+        ExecuteMsg::CancelOrder { order_id } => cancel_order(deps, info, order_id),
     }
 }
 
@@ -137,7 +286,10 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            deadline,
         }) => {
+            assert_deadline(&env, deadline)?;
+
             // only asset contract can execute this message
             let mut authorized: bool = false;
             let config: Config = CONFIG.load(deps.storage)?;
@@ -174,13 +326,111 @@ pub fn receive_cw20(
                 to_addr,
             )
         }
-        Ok(Cw20HookMsg::WithdrawLiquidity {}) => withdraw_liquidity(
-            deps,
-            env,
-            info,
-            Addr::unchecked(cw20_msg.sender),
-            cw20_msg.amount,
-        ),
+        Ok(Cw20HookMsg::SwapExactOutput {
+            ask_amount,
+            max_offer,
+            to,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            // only asset contract can execute this message
+            let config: Config = CONFIG.load(deps.storage)?;
+            let offer_info = AssetInfo::Cw20(contract_addr);
+            if !config.pair_info.asset_infos.contains(&offer_info) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap_exact_output(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: offer_info,
+                    amount: cw20_msg.amount,
+                },
+                ask_amount,
+                max_offer,
+                to_addr,
+            )
+        }
+        Ok(Cw20HookMsg::SubmitOrder { price }) => {
+            // only asset contract can execute this message
+            let config: Config = CONFIG.load(deps.storage)?;
+            let offer_info = AssetInfo::Cw20(contract_addr);
+            if !config.pair_info.asset_infos.contains(&offer_info) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            submit_order(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: offer_info,
+                    amount: cw20_msg.amount,
+                },
+                price,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            min_assets,
+            receiver,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            let sender = Addr::unchecked(cw20_msg.sender);
+            let receiver = if let Some(receiver) = receiver {
+                deps.api.addr_validate(&receiver)?
+            } else {
+                sender.clone()
+            };
+
+            withdraw_liquidity(
+                deps,
+                env,
+                info,
+                sender,
+                cw20_msg.amount,
+                min_assets,
+                receiver,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawSingleAsset {
+            ask_asset,
+            min_receive,
+            receiver,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            let sender = Addr::unchecked(cw20_msg.sender);
+            let receiver = if let Some(receiver) = receiver {
+                deps.api.addr_validate(&receiver)?
+            } else {
+                sender.clone()
+            };
+
+            withdraw_single_asset(
+                deps,
+                env,
+                info,
+                sender,
+                cw20_msg.amount,
+                ask_asset,
+                min_receive,
+                receiver,
+            )
+        }
         Err(err) => Err(ContractError::Std(err)),
     }
 }
@@ -188,6 +438,14 @@ pub fn receive_cw20(
 /// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    // a failed hook is reverted on its own without reverting the pair action
+    if msg.id == HOOK_REPLY_ID {
+        let error = msg.result.into_result().err().unwrap_or_default();
+        return Ok(
+            Response::new().add_attributes(vec![("action", "pair_hook_failed"), ("error", &error)])
+        );
+    }
+
     let mut config: Config = CONFIG.load(deps.storage)?;
 
     if config.pair_info.liquidity_token != Addr::unchecked("") {
@@ -204,6 +462,7 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 }
 
 /// CONTRACT - should approve contract to use the amount of token
+/// Only the amounts matching the pool ratio are pulled or kept, the native excess is refunded.
 pub fn provide_liquidity(
     deps: DepsMut,
     env: Env,
@@ -216,10 +475,14 @@ pub fn provide_liquidity(
         asset.assert_sent_native_token_balance(&info)?;
     }
 
-    let config: Config = CONFIG.load(deps.storage)?;
-    let mut pools: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, &env.contract.address)?;
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
     let deposits: [Uint128; 2] = [
         assets
             .iter()
@@ -233,8 +496,46 @@ pub fn provide_liquidity(
             .expect("Wrong asset info is given"),
     ];
 
+    // accumulate prices against the reserves prior to the deposit
+    accumulate_prices(&env, &mut config, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+
+    // mint the protocol fee share before the deposit dilutes it
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let (protocol_fee_msg, protocol_fee_share) = mint_protocol_fee(
+        deps.storage,
+        &config,
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+    let total_share = total_share.checked_add(protocol_fee_share)?;
+
+    if !total_share.is_zero() {
+        // assert slippage tolerance
+        assert_slippage_tolerance(&slippage_tolerance, &deposits, &pools)?;
+    }
+
+    let share = compute_share(&deposits, &[pools[0].amount, pools[1].amount], total_share)?;
+
+    // only the amounts matching the pool ratio are kept, the excess is refunded
+    let refund_amounts: [Uint128; 2] = if total_share.is_zero() {
+        [Uint128::zero(); 2]
+    } else {
+        compute_excess_amounts(
+            &deposits,
+            &[pools[0].amount, pools[1].amount],
+            share,
+            total_share,
+        )
+    };
+    let deposits: [Uint128; 2] = [
+        deposits[0].checked_sub(refund_amounts[0])?,
+        deposits[1].checked_sub(refund_amounts[1])?,
+    ];
+
     let mut messages: Vec<CosmosMsg> = vec![];
-    for (i, pool) in pools.iter_mut().enumerate() {
+    let mut refund_messages: Vec<CosmosMsg> = vec![];
+    for (i, pool) in pools.iter().enumerate() {
         // If the pool is token contract, then we need to execute TransferFrom msg to receive funds
         if let AssetInfo::Cw20(contract_addr) = &pool.info {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -246,38 +547,211 @@ pub fn provide_liquidity(
                 })?,
                 funds: vec![],
             }));
-        } else {
-            // If the asset is native token, balance is already increased
-            // To calculated properly we should subtract user deposit from the pool
-            pool.amount = pool.amount.checked_sub(deposits[i])?;
+        } else if !refund_amounts[i].is_zero() {
+            refund_messages.push(
+                Asset {
+                    info: pool.info.clone(),
+                    amount: refund_amounts[i],
+                }
+                .transfer_msg_with_tax(&deps.querier, &info.sender)?,
+            );
         }
     }
+    messages.extend(protocol_fee_msg);
+
+    if total_share.is_zero() {
+        // lock the minimum liquidity in the pair forever
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.pair_info.liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: env.contract.address.to_string(),
+                amount: Uint128::from(MINIMUM_LIQUIDITY_AMOUNT),
+            })?,
+            funds: vec![],
+        }));
+    }
 
-    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
-    let share = if total_share.is_zero() {
-        // Initial share = collateral amount
-        Uint128::from((deposits[0].u128() * deposits[1].u128()).integer_sqrt())
-    } else {
-        // assert slippage tolerance
-        assert_slippage_tolerance(&slippage_tolerance, &deposits, &pools)?;
+    let reserves: [Uint128; 2] = [
+        pools[0].amount.checked_add(deposits[0])?,
+        pools[1].amount.checked_add(deposits[1])?,
+    ];
+    RESERVES.save(deps.storage, &reserves)?;
+    update_root_k_last(deps.storage, &reserves)?;
 
-        // min(1, 2)
-        // 1. sqrt(deposit_0 * exchange_rate_0_to_1 * deposit_0) * (total_share / sqrt(pool_0 * pool_1))
-        // == deposit_0 * total_share / pool_0
-        // 2. sqrt(deposit_1 * exchange_rate_1_to_0 * deposit_1) * (total_share / sqrt(pool_1 * pool_1))
-        // == deposit_1 * total_share / pool_1
-        std::cmp::min(
-            deposits[0].multiply_ratio(total_share, pools[0].amount),
-            deposits[1].multiply_ratio(total_share, pools[1].amount),
-        )
+    // mint LP token to sender
+    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: receiver.to_string(),
+            amount: share,
+        })?,
+        funds: vec![],
+    }));
+    messages.extend(refund_messages);
+    let refund_assets: [Asset; 2] = reserve_assets(&config.pair_info.asset_infos, &refund_amounts);
+
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::ProvideLiquidity {
+            sender: info.sender.clone(),
+            receiver: deps.api.addr_validate(&receiver)?,
+            assets: reserve_assets(&config.pair_info.asset_infos, &deposits),
+            share,
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "provide_liquidity"),
+            ("sender", info.sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("assets", &format!("{}, {}", assets[0], assets[1])),
+            ("share", &share.to_string()),
+            (
+                "refund_assets",
+                &format!("{}, {}", refund_assets[0], refund_assets[1]),
+            ),
+        ]))
+}
+
+/// ## Description
+/// Swaps the part of `asset` that balances the remainder against the pool, then provides
+/// the remainder and the swap return as liquidity. Up to rounding, nothing is left as donation.
+/// ## Params
+/// * **asset** is the object of type [`Asset`] to provide.
+///
+/// * **max_spread** is the optional maximum spread of the internal swap.
+///
+/// * **min_share** is the optional minimum amount of LP tokens to receive.
+///
+/// * **receiver** is the optional receiver of the LP tokens, the sender if empty.
+pub fn provide_single_asset(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    max_spread: Option<Decimal>,
+    min_share: Option<Uint128>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    asset.assert_sent_native_token_balance(&info)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_index: usize = if asset.info.eq(&pools[0].info) {
+        0
+    } else if asset.info.eq(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
     };
+    let offer_pool: Asset = pools[offer_index].clone();
+    let ask_pool: Asset = pools[1 - offer_index].clone();
+
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    if total_share.is_zero() || offer_pool.amount.is_zero() || ask_pool.amount.is_zero() {
+        return Err(ContractError::EmptyPool {});
+    }
+
+    // accumulate prices against the reserves prior to the provision
+    accumulate_prices(&env, &mut config, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+    // mint the protocol fee share before the provision
+    let (protocol_fee_msg, protocol_fee_share) = mint_protocol_fee(
+        deps.storage,
+        &config,
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+    let total_share = total_share.checked_add(protocol_fee_share)?;
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    let swap_amount = compute_single_asset_swap_amount(
+        asset.amount,
+        offer_pool.amount,
+        fee_info.fee_config.total_fee,
+    );
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        swap_amount,
+        fee_info.fee_config.total_fee,
+    );
+    assert_max_spread(
+        None,
+        max_spread,
+        swap_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+
+    let mut swapped_reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    swapped_reserves[offer_index] = swapped_reserves[offer_index].checked_add(swap_amount)?;
+    swapped_reserves[1 - offer_index] =
+        swapped_reserves[1 - offer_index].checked_sub(return_amount)?;
+
+    // provide the remainder and the swap return against the reserves after the swap
+    let deposits: [Uint128; 2] = [asset.amount.checked_sub(swap_amount)?, return_amount];
+    let share = std::cmp::min(
+        deposits[0].multiply_ratio(total_share, swapped_reserves[offer_index]),
+        deposits[1].multiply_ratio(total_share, swapped_reserves[1 - offer_index]),
+    );
 
     // prevent providing free token
     if share.is_zero() {
         return Err(ContractError::InvalidZeroAmount {});
     }
 
-    // mint LP token to sender
+    if let Some(min_share) = min_share {
+        if share < min_share {
+            return Err(ContractError::MaxSlippageAssertion {});
+        }
+    }
+
+    let mut volumes = [Uint128::zero(); 2];
+    volumes[offer_index] = swap_amount;
+    let mut commissions = [Uint128::zero(); 2];
+    commissions[1 - offer_index] = commission_amount;
+    record_trade(deps.storage, &env, &fee_info, volumes, commissions)?;
+
+    // the whole asset stays in the pool
+    let mut reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = reserves[offer_index].checked_add(asset.amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+    // the commission of the swap accrues to the protocol on the next liquidity event
+    update_root_k_last(
+        deps.storage,
+        &compute_deposit_reserves(&[pools[0].amount, pools[1].amount], share, total_share),
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let AssetInfo::Cw20(contract_addr) = &asset.info {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: asset.amount,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    messages.extend(protocol_fee_msg);
+
+    // mint LP token to receiver
     let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: config.pair_info.liquidity_token.to_string(),
@@ -288,13 +762,31 @@ pub fn provide_liquidity(
         funds: vec![],
     }));
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        ("action", "provide_liquidity"),
-        ("sender", info.sender.as_str()),
-        ("receiver", receiver.as_str()),
-        ("assets", &format!("{}, {}", assets[0], assets[1])),
-        ("share", &share.to_string()),
-    ]))
+    let mut deposits = [Uint128::zero(); 2];
+    deposits[offer_index] = asset.amount;
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::ProvideLiquidity {
+            sender: info.sender.clone(),
+            receiver: deps.api.addr_validate(&receiver)?,
+            assets: reserve_assets(&config.pair_info.asset_infos, &deposits),
+            share,
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "provide_single_asset"),
+            ("sender", info.sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("asset", &asset.to_string()),
+            ("swap_amount", &swap_amount.to_string()),
+            ("return_amount", &return_amount.to_string()),
+            ("share", &share.to_string()),
+        ]))
 }
 
 pub fn withdraw_liquidity(
@@ -303,32 +795,56 @@ pub fn withdraw_liquidity(
     info: MessageInfo,
     sender: Addr,
     amount: Uint128,
+    min_assets: Option<[Asset; 2]>,
+    receiver: Addr,
 ) -> Result<Response, ContractError> {
-    let config: Config = CONFIG.load(deps.storage).unwrap();
+    let mut config: Config = CONFIG.load(deps.storage).unwrap();
 
     if info.sender != config.pair_info.liquidity_token {
         return Err(ContractError::Unauthorized {});
     }
 
-    let pools: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, &env.contract.address)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    // accumulate prices against the reserves prior to the withdrawal
+    accumulate_prices(&env, &mut config, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+    // mint the protocol fee share before the withdrawal
     let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let (protocol_fee_msg, protocol_fee_share) = mint_protocol_fee(
+        deps.storage,
+        &config,
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+    let total_share = total_share.checked_add(protocol_fee_share)?;
 
-    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
+    let refund_amounts =
+        compute_refund_amounts(&[pools[0].amount, pools[1].amount], amount, total_share);
     let refund_assets: Vec<Asset> = pools
         .iter()
-        .map(|a| Asset {
+        .zip(refund_amounts.iter())
+        .map(|(a, refund_amount)| Asset {
             info: a.info.clone(),
-            amount: a.amount * share_ratio,
+            amount: *refund_amount,
         })
         .collect();
 
+    assert_min_assets(&refund_assets, &min_assets)?;
+
     // update pool info
+    let reserves: [Uint128; 2] = [
+        pools[0].amount.checked_sub(refund_assets[0].amount)?,
+        pools[1].amount.checked_sub(refund_assets[1].amount)?,
+    ];
+    RESERVES.save(deps.storage, &reserves)?;
+    update_root_k_last(deps.storage, &reserves)?;
+
     Ok(Response::new()
+        .add_messages(protocol_fee_msg)
         .add_messages(vec![
-            refund_assets[0].transfer_msg(&sender)?,
-            refund_assets[1].transfer_msg(&sender)?,
+            refund_assets[0].transfer_msg_with_tax(&deps.querier, &receiver)?,
+            refund_assets[1].transfer_msg_with_tax(&deps.querier, &receiver)?,
             // burn liquidity token
             CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: config.pair_info.liquidity_token.to_string(),
@@ -336,9 +852,23 @@ pub fn withdraw_liquidity(
                 funds: vec![],
             }),
         ])
+        .add_submessages(hook_messages(
+            deps.storage,
+            PairHookMsg::WithdrawLiquidity {
+                sender: sender.clone(),
+                receiver: receiver.clone(),
+                share: amount,
+                refund_assets: reserve_assets(
+                    &config.pair_info.asset_infos,
+                    &[refund_assets[0].amount, refund_assets[1].amount],
+                ),
+                reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+            },
+        )?)
         .add_attributes(vec![
             ("action", "withdraw_liquidity"),
             ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
             ("withdrawn_share", &amount.to_string()),
             (
                 "refund_assets",
@@ -347,54 +877,177 @@ pub fn withdraw_liquidity(
         ]))
 }
 
-// CONTRACT - a user must do token approval
 #[allow(clippy::too_many_arguments)]
-pub fn swap(
+pub fn withdraw_single_asset(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     sender: Addr,
-    offer_asset: Asset,
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
-    to: Option<Addr>,
+    amount: Uint128,
+    ask_asset: AssetInfo,
+    min_receive: Option<Uint128>,
+    receiver: Addr,
 ) -> Result<Response, ContractError> {
-    offer_asset.assert_sent_native_token_balance(&info)?;
-
-    let config: Config = CONFIG.load(deps.storage)?;
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
 
-    let pools: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, &env.contract.address)?;
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
 
-    // If the asset balance is already increased
-    // To calculated properly we should subtract user deposit from the pool
-    if offer_asset.info.eq(&pools[0].info) {
-        offer_pool = Asset {
-            amount: pools[0].amount.checked_sub(offer_asset.amount)?,
-            info: pools[0].info.clone(),
-        };
-        ask_pool = pools[1].clone();
-    } else if offer_asset.info.eq(&pools[1].info) {
-        offer_pool = Asset {
-            amount: pools[1].amount.checked_sub(offer_asset.amount)?,
-            info: pools[1].info.clone(),
-        };
-        ask_pool = pools[0].clone();
+    let ask_index: usize = if ask_asset.eq(&pools[0].info) {
+        0
+    } else if ask_asset.eq(&pools[1].info) {
+        1
     } else {
         return Err(ContractError::AssetMismatch {});
+    };
+    let offer_index: usize = 1 - ask_index;
+
+    // accumulate prices against the reserves prior to the withdrawal
+    accumulate_prices(&env, &mut config, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+    // mint the protocol fee share before the withdrawal
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let (protocol_fee_msg, protocol_fee_share) = mint_protocol_fee(
+        deps.storage,
+        &config,
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+    let total_share = total_share.checked_add(protocol_fee_share)?;
+
+    let refund_amounts: [Uint128; 2] =
+        compute_refund_amounts(&[pools[0].amount, pools[1].amount], amount, total_share);
+    let withdrawn_reserves: [Uint128; 2] = [
+        pools[0].amount.checked_sub(refund_amounts[0])?,
+        pools[1].amount.checked_sub(refund_amounts[1])?,
+    ];
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    // swap the other leg against the reserves after the withdrawal
+    let (return_amount, _, commission_amount) = compute_swap(
+        withdrawn_reserves[offer_index],
+        withdrawn_reserves[ask_index],
+        refund_amounts[offer_index],
+        fee_info.fee_config.total_fee,
+    );
+
+    let refund_asset = Asset {
+        info: ask_asset,
+        amount: refund_amounts[ask_index].checked_add(return_amount)?,
+    };
+    // the stability tax of a native ask asset is deducted from the sent amount, so the minimum
+    // applies to the amount received
+    let tax_amount: Uint128 = refund_asset.compute_tax(&deps.querier)?;
+    if let Some(min_receive) = min_receive {
+        if refund_asset.amount.checked_sub(tax_amount)? < min_receive {
+            return Err(ContractError::MinWithdrawAssertion {});
+        }
+    }
+
+    // the commission of the swap accrues to the protocol on the next liquidity event
+    update_root_k_last(deps.storage, &withdrawn_reserves)?;
+
+    let mut volumes = [Uint128::zero(); 2];
+    volumes[offer_index] = refund_amounts[offer_index];
+    let mut commissions = [Uint128::zero(); 2];
+    commissions[ask_index] = commission_amount;
+    record_trade(deps.storage, &env, &fee_info, volumes, commissions)?;
+
+    // the swapped leg goes back into the pool, so only the ask reserve decreases
+    let mut reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    reserves[ask_index] = reserves[ask_index].checked_sub(refund_asset.amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    messages.extend(protocol_fee_msg);
+    if !refund_asset.amount.is_zero() {
+        messages.push(refund_asset.transfer_msg_with_tax(&deps.querier, &receiver)?);
     }
 
-    // Get pool fee configuration from factory
-    let fee_info: FeeInfoResponse = query_fee_info(
+    // burn liquidity token
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    }));
+
+    let mut refunds = [Uint128::zero(); 2];
+    refunds[ask_index] = refund_asset.amount;
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::WithdrawLiquidity {
+            sender: sender.clone(),
+            receiver: receiver.clone(),
+            share: amount,
+            refund_assets: reserve_assets(&config.pair_info.asset_infos, &refunds),
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "withdraw_single_asset"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("withdrawn_share", &amount.to_string()),
+            ("swap_amount", &refund_amounts[offer_index].to_string()),
+            ("return_amount", &return_amount.to_string()),
+            ("refund_asset", &refund_asset.to_string()),
+        ]))
+}
+
+// CONTRACT - a user must do token approval
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
         &deps.querier,
         &config.factory,
         &config.pair_info.asset_infos,
     )?;
 
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_index: usize = if offer_asset.info.eq(&pools[0].info) {
+        0
+    } else if offer_asset.info.eq(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let offer_pool: Asset = pools[offer_index].clone();
+    let ask_pool: Asset = pools[1 - offer_index].clone();
+
+    // accumulate prices against the reserves prior to the swap
+    accumulate_prices(&env, &mut config, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
     let offer_amount = offer_asset.amount;
     let (return_amount, spread_amount, commission_amount) = compute_swap(
         offer_pool.amount,
@@ -418,173 +1071,1264 @@ pub fn swap(
     };
     let receiver = to.unwrap_or_else(|| sender.clone());
 
+    // the stability tax of a native return asset is deducted from the sent amount, the
+    // reported return amount is the amount received
+    let tax_amount: Uint128 = return_asset.compute_tax(&deps.querier)?;
+
     let mut messages: Vec<CosmosMsg> = vec![];
     if !return_amount.is_zero() {
-        messages.push(return_asset.transfer_msg(&receiver)?);
+        messages.push(return_asset.transfer_msg_with_tax(&deps.querier, &receiver)?);
+    }
+    let received_amount: Uint128 = return_amount.checked_sub(tax_amount)?;
+
+    // the commission stays in the pool, the protocol share is minted on the next liquidity event
+    let mut reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = reserves[offer_index].checked_add(offer_amount)?;
+    reserves[1 - offer_index] = reserves[1 - offer_index].checked_sub(return_amount)?;
+
+    let mut volumes = [Uint128::zero(); 2];
+    volumes[offer_index] = offer_amount;
+    let mut commissions = [Uint128::zero(); 2];
+    commissions[1 - offer_index] = commission_amount;
+    record_trade(deps.storage, &env, &fee_info, volumes, commissions)?;
+
+    // the swap raised the price of the ask asset, fill the orders selling it
+    let (fill_msgs, fill_attrs) = fill_orders(
+        deps.storage,
+        &deps.querier,
+        &env,
+        &config,
+        &fee_info,
+        1 - offer_index,
+        &mut reserves,
+    )?;
+    messages.extend(fill_msgs);
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::Swap {
+            sender: sender.clone(),
+            receiver: receiver.clone(),
+            offer_asset: offer_asset.clone(),
+            return_asset: Asset {
+                info: return_asset.info,
+                amount: received_amount,
+            },
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    // send collateral token from the contract to a user
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "swap"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("offer_asset", &offer_asset.info.to_string()),
+            ("ask_asset", &ask_pool.info.to_string()),
+            ("offer_amount", &offer_amount.to_string()),
+            ("return_amount", &received_amount.to_string()),
+            ("spread_amount", &spread_amount.to_string()),
+            ("commission_amount", &commission_amount.to_string()),
+            ("tax_amount", &tax_amount.to_string()),
+        ])
+        .add_attributes(fill_attrs))
+}
+
+/// ## Description
+/// Swaps the offer asset for exactly `ask_amount` of the other asset. The offer is computed
+/// against the reserves, rounded in favor of the pool, and the unused offer asset is refunded
+/// to the sender. The pool pays the stability tax of a native ask asset on top of `ask_amount`,
+/// so the receiver gets the full amount.
+/// ## Params
+/// * **offer_asset** is the object of type [`Asset`] sent to pay for the swap.
+///
+/// * **ask_amount** is the amount of the other asset to receive.
+///
+/// * **max_offer** is the optional maximum amount of the offer asset to spend.
+///
+/// * **to** is the optional receiver of the ask asset, the sender if empty.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_exact_output(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_amount: Uint128,
+    max_offer: Option<Uint128>,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    if ask_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_index: usize = if offer_asset.info.eq(&pools[0].info) {
+        0
+    } else if offer_asset.info.eq(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let offer_pool: Asset = pools[offer_index].clone();
+    let ask_pool: Asset = pools[1 - offer_index].clone();
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    let tax_amount: Uint128 = Asset {
+        info: ask_pool.info.clone(),
+        amount: ask_amount,
+    }
+    .compute_reverse_tax(&deps.querier)?;
+    let return_amount: Uint128 = ask_amount.checked_add(tax_amount)?;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        offer_pool.amount,
+        ask_pool.amount,
+        return_amount,
+        fee_info.fee_config.total_fee,
+    )?;
+
+    let max_offer: Uint128 = max_offer.map_or(offer_asset.amount, |max_offer| {
+        std::cmp::min(max_offer, offer_asset.amount)
+    });
+    if offer_amount > max_offer {
+        return Err(ContractError::MaxOfferAssertion {});
+    }
+
+    // accumulate prices against the reserves prior to the swap
+    accumulate_prices(&env, &mut config, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+
+    // the commission stays in the pool, the protocol share is minted on the next liquidity event
+    let mut reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = reserves[offer_index].checked_add(offer_amount)?;
+    reserves[1 - offer_index] = reserves[1 - offer_index].checked_sub(return_amount)?;
+
+    let mut volumes = [Uint128::zero(); 2];
+    volumes[offer_index] = offer_amount;
+    let mut commissions = [Uint128::zero(); 2];
+    commissions[1 - offer_index] = commission_amount;
+    record_trade(deps.storage, &env, &fee_info, volumes, commissions)?;
+
+    // the swap raised the price of the ask asset, fill the orders selling it
+    let (fill_msgs, fill_attrs) = fill_orders(
+        deps.storage,
+        &deps.querier,
+        &env,
+        &config,
+        &fee_info,
+        1 - offer_index,
+        &mut reserves,
+    )?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let return_asset = Asset {
+        info: ask_pool.info.clone(),
+        amount: return_amount,
+    };
+    let refund_asset = Asset {
+        info: offer_asset.info.clone(),
+        amount: offer_asset.amount.checked_sub(offer_amount)?,
+    };
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    let mut messages: Vec<CosmosMsg> =
+        vec![return_asset.transfer_msg_with_tax(&deps.querier, &receiver)?];
+    if !refund_asset.amount.is_zero() {
+        messages.push(refund_asset.transfer_msg_with_tax(&deps.querier, &sender)?);
+    }
+    messages.extend(fill_msgs);
+
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::Swap {
+            sender: sender.clone(),
+            receiver: receiver.clone(),
+            offer_asset: Asset {
+                info: offer_asset.info.clone(),
+                amount: offer_amount,
+            },
+            return_asset: Asset {
+                info: return_asset.info,
+                amount: ask_amount,
+            },
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "swap_exact_output"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("offer_asset", &offer_asset.info.to_string()),
+            ("ask_asset", &ask_pool.info.to_string()),
+            ("offer_amount", &offer_amount.to_string()),
+            ("return_amount", &ask_amount.to_string()),
+            ("refund_amount", &refund_asset.amount.to_string()),
+            ("spread_amount", &spread_amount.to_string()),
+            ("commission_amount", &commission_amount.to_string()),
+            ("tax_amount", &tax_amount.to_string()),
+        ])
+        .add_attributes(fill_attrs))
+}
+
+/// ## Description
+/// Updates the fee settings cached by the pair. Only the factory can execute it.
+/// The protocol fee accrued under the previous settings is minted to the previous collector
+/// first, and the accrual restarts from the current reserves.
+/// ## Params
+/// * **fee_config** is an [`Option`] field of type [`FeeConfig`] with the new fee settings.
+///
+/// * **collector** is an [`Option`] field of type [`Addr`] with the new protocol fee collector.
+pub fn update_fee_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_config: Option<FeeConfig>,
+    collector: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.factory {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // settle the protocol fee with the previous settings
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let (protocol_fee_msg, _) = mint_protocol_fee(deps.storage, &config, &reserves, total_share)?;
+
+    let res = fee_info::update_fee_config(deps.storage, fee_config, collector)?;
+    update_root_k_last(deps.storage, &reserves)?;
+
+    Ok(res.add_messages(protocol_fee_msg))
+}
+
+/// ## Description
+/// Sets the stored reserves to the pair balances, accumulating prices against the previous reserves.
+/// Only the factory owner can execute it.
+pub fn sync(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    let factory_config = query_factory_config(&deps.querier, &config.factory)?;
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let reserves: [Uint128; 2] = RESERVES.load(deps.storage)?;
+    accumulate_prices(&env, &mut config, reserves[0], reserves[1]);
+    CONFIG.save(deps.storage, &config)?;
+
+    let balances: [Asset; 2] = load_balances(deps.storage, &deps.querier, &env, &config)?;
+    RESERVES.save(deps.storage, &[balances[0].amount, balances[1].amount])?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "sync"),
+        ("reserves", &format!("{}, {}", balances[0], balances[1])),
+    ]))
+}
+
+/// ## Description
+/// Escrows the offer asset in a limit order selling it for at least `price` units of the other
+/// asset each. The order is filled against the pool after the swaps moving the pool price past
+/// it, starting right away if the pool price is already past it.
+/// ## Params
+/// * **sender** is the object of type [`Addr`] owning the order.
+///
+/// * **offer_asset** is the object of type [`Asset`] escrowed by the order.
+///
+/// * **price** is the minimum amount of the other asset received per unit of the offer asset.
+pub fn submit_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    price: Decimal,
+) -> Result<Response, ContractError> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    if offer_asset.amount < Uint128::from(MIN_ORDER_AMOUNT) {
+        return Err(ContractError::OrderTooSmall {});
+    }
+    if price.is_zero() {
+        return Err(ContractError::InvalidOrderPrice {});
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+
+    let offer_index: usize = config
+        .pair_info
+        .asset_infos
+        .iter()
+        .position(|info| info == &offer_asset.info)
+        .ok_or(ContractError::AssetMismatch {})?;
+
+    let order_id: u64 = NEXT_ORDER_ID.may_load(deps.storage)?.unwrap_or_default();
+    NEXT_ORDER_ID.save(deps.storage, &(order_id + 1))?;
+    store_order(
+        deps.storage,
+        order_id,
+        &LimitOrder {
+            owner: sender.clone(),
+            offer_index: offer_index as u8,
+            offer_amount: offer_asset.amount,
+            price,
+            filled_amount: Uint128::zero(),
+            received_amount: Uint128::zero(),
+        },
+    )?;
+
+    let mut escrow: [Uint128; 2] = ORDER_ESCROW.may_load(deps.storage)?.unwrap_or_default();
+    escrow[offer_index] = escrow[offer_index].checked_add(offer_asset.amount)?;
+    ORDER_ESCROW.save(deps.storage, &escrow)?;
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+    let mut reserves: [Uint128; 2] = RESERVES.load(deps.storage)?;
+    let (fill_msgs, fill_attrs) = fill_orders(
+        deps.storage,
+        &deps.querier,
+        &env,
+        &config,
+        &fee_info,
+        offer_index,
+        &mut reserves,
+    )?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    Ok(Response::new()
+        .add_messages(fill_msgs)
+        .add_attributes(vec![
+            ("action", "submit_order"),
+            ("owner", sender.as_str()),
+            ("order_id", &order_id.to_string()),
+            ("offer_asset", &offer_asset.to_string()),
+            ("price", &price.to_string()),
+        ])
+        .add_attributes(fill_attrs))
+}
+
+/// ## Description
+/// Cancels a limit order and refunds its unfilled part to the owner. Only the order owner can
+/// execute it, also while the pair is paused.
+/// ## Params
+/// * **order_id** is the identifier of the order.
+pub fn cancel_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    order_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let order: LimitOrder = ORDERS.load(deps.storage, U64Key::new(order_id))?;
+    if order.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let offer_index = order.offer_index as usize;
+    let mut escrow: [Uint128; 2] = ORDER_ESCROW.may_load(deps.storage)?.unwrap_or_default();
+    escrow[offer_index] = escrow[offer_index].checked_sub(order.offer_amount)?;
+    ORDER_ESCROW.save(deps.storage, &escrow)?;
+    remove_order(deps.storage, order_id, &order);
+
+    let refund_asset = Asset {
+        info: config.pair_info.asset_infos[offer_index].clone(),
+        amount: order.offer_amount,
+    };
+
+    Ok(Response::new()
+        .add_message(refund_asset.transfer_msg_with_tax(&deps.querier, &order.owner)?)
+        .add_attributes(vec![
+            ("action", "cancel_order"),
+            ("owner", order.owner.as_str()),
+            ("order_id", &order_id.to_string()),
+            ("refund_asset", &refund_asset.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Transfers the pair balances exceeding the stored reserves to the recipient.
+/// ## Params
+/// * **to** is the object of type [`Addr`] receiving the excess balances.
+pub fn skim(deps: Deps, env: Env, to: Addr) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let reserves: [Uint128; 2] = RESERVES.load(deps.storage)?;
+
+    let balances: [Asset; 2] = load_balances(deps.storage, &deps.querier, &env, &config)?;
+    let excess_assets: Vec<Asset> = balances
+        .iter()
+        .zip(reserves.iter())
+        .map(|(balance, reserve)| Asset {
+            info: balance.info.clone(),
+            amount: balance
+                .amount
+                .checked_sub(*reserve)
+                .unwrap_or_else(|_| Uint128::zero()),
+        })
+        .collect();
+
+    let messages: Vec<CosmosMsg> = excess_assets
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| asset.transfer_msg_with_tax(&deps.querier, &to))
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "skim"),
+        ("to", to.as_str()),
+        (
+            "skimmed_assets",
+            &format!("{}, {}", excess_assets[0], excess_assets[1]),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Lends `ask_asset` to the caller and executes the flash swap callback on it. The callback
+/// carries the amount received net of the stability tax, while the pool accounts for the full
+/// lent amount. The pair is locked until the final self call checks the pool invariant after
+/// fees is restored.
+/// ## Params
+/// * **ask_asset** is the object of type [`Asset`] lent to the caller.
+///
+/// * **msg** is the binary serialised message passed back to the caller.
+pub fn flash_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    ask_asset: Asset,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let ask_pool: &Asset = pools
+        .iter()
+        .find(|pool| pool.info == ask_asset.info)
+        .ok_or(ContractError::AssetMismatch {})?;
+
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if ask_asset.amount >= ask_pool.amount {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    FLASH_SWAP.save(deps.storage, &ask_asset)?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            ask_asset.transfer_msg_with_tax(&deps.querier, &info.sender)?,
+            FlashSwapCallbackMsg {
+                ask_asset: ask_asset.deduct_tax(&deps.querier)?,
+                msg,
+            }
+            .into_cosmos_msg(&info.sender)?,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::FinalizeFlashSwap {})?,
+                funds: vec![],
+            }),
+        ])
+        .add_attributes(vec![
+            ("action", "flash_swap"),
+            ("sender", info.sender.as_str()),
+            ("ask_asset", &ask_asset.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Checks the pair balances after the flash swap callback keep the product of the reserves,
+/// with the total fee charged on the amounts paid in, and stores them as the new reserves.
+/// Only the pair itself can execute it.
+pub fn finalize_flash_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let ask_asset: Asset = FLASH_SWAP.load(deps.storage)?;
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let reserves: [Uint128; 2] = RESERVES.load(deps.storage)?;
+    let balances: [Asset; 2] = load_balances(deps.storage, &deps.querier, &env, &config)?;
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    let mut offer_assets: Vec<Asset> = vec![];
+    let mut commissions = [Uint128::zero(); 2];
+    let mut adjusted_balances: Vec<Uint256> = vec![];
+    for (i, (balance, reserve)) in balances.iter().zip(reserves.iter()).enumerate() {
+        let lent_amount = if balance.info == ask_asset.info {
+            ask_asset.amount
+        } else {
+            Uint128::zero()
+        };
+
+        // anything above the reserve left after the loan has been paid in
+        let offer_amount = balance
+            .amount
+            .checked_sub(reserve.checked_sub(lent_amount)?)
+            .unwrap_or_else(|_| Uint128::zero());
+        let commission_amount = offer_amount * fee_info.fee_config.total_fee;
+        commissions[i] = commission_amount;
+
+        adjusted_balances.push(Uint256::from(
+            balance.amount.checked_sub(commission_amount)?,
+        ));
+        offer_assets.push(Asset {
+            info: balance.info.clone(),
+            amount: offer_amount,
+        });
+    }
+
+    if adjusted_balances[0] * adjusted_balances[1]
+        < Uint256::from(reserves[0]) * Uint256::from(reserves[1])
+    {
+        return Err(ContractError::FlashSwapInvariant {});
     }
 
-    let protocol_fee_asset = Asset {
-        info: ask_pool.info.clone(),
-        amount: commission_amount * fee_info.fee_config.protocol_fee,
-    };
-    if !protocol_fee_asset.amount.is_zero() {
-        messages.push(protocol_fee_asset.transfer_msg(fee_info.collector)?);
-    }
+    FLASH_SWAP.remove(deps.storage);
+
+    // accumulate prices against the reserves prior to the flash swap
+    accumulate_prices(&env, &mut config, reserves[0], reserves[1]);
+    CONFIG.save(deps.storage, &config)?;
+
+    // the commission stays in the pool, the protocol share is minted on the next liquidity event
+    let new_reserves: [Uint128; 2] = [balances[0].amount, balances[1].amount];
+    RESERVES.save(deps.storage, &new_reserves)?;
+
+    let volumes = [offer_assets[0].amount, offer_assets[1].amount];
+    record_trade(deps.storage, &env, &fee_info, volumes, commissions)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "finalize_flash_swap"),
+        (
+            "offer_assets",
+            &format!("{}, {}", offer_assets[0], offer_assets[1]),
+        ),
+        (
+            "reserves",
+            &format!("{}, {}", new_reserves[0], new_reserves[1]),
+        ),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps)?)?),
+        QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
+        QueryMsg::Simulation { offer_asset } => {
+            Ok(to_binary(&query_simulation(deps, offer_asset)?)?)
+        }
+        QueryMsg::ReverseSimulation { ask_asset } => {
+            Ok(to_binary(&query_reverse_simulation(deps, ask_asset)?)?)
+        }
+        QueryMsg::SimulateProvide { assets } => {
+            Ok(to_binary(&query_simulate_provide(deps, assets)?)?)
+        }
+        QueryMsg::SimulateWithdraw { lp_amount } => {
+            Ok(to_binary(&query_simulate_withdraw(deps, lp_amount)?)?)
+        }
+        QueryMsg::SpotPrice { base_asset } => Ok(to_binary(&query_spot_price(deps, base_asset)?)?),
+        QueryMsg::PriceImpact { offer_asset } => {
+            Ok(to_binary(&query_price_impact(deps, offer_asset)?)?)
+        }
+        QueryMsg::CumulativePrices {} => Ok(to_binary(&query_cumulative_prices(deps, env)?)?),
+        QueryMsg::PendingProtocolFee {} => Ok(to_binary(&query_pending_protocol_fee(deps)?)?),
+        QueryMsg::Stats {} => Ok(to_binary(&query_stats(deps)?)?),
+        QueryMsg::Hooks {} => Ok(to_binary(&query_hooks(deps)?)?),
+        QueryMsg::Order { order_id } => Ok(to_binary(&query_order(deps, order_id)?)?),
+        QueryMsg::OrdersByOwner {
+            owner,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_orders_by_owner(
+            deps,
+            owner,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::OrdersByPrice {
+            offer_asset_info,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_orders_by_price(
+            deps,
+            offer_asset_info,
+            start_after,
+            limit,
+        )?)?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        pair_info: config.pair_info,
+        factory: config.factory,
+        fee_config: fee_info.fee_config,
+        collector: fee_info.collector,
+        params: None,
+    })
+}
+
+pub fn query_pair_info(deps: Deps) -> Result<PairInfo, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    Ok(config.pair_info)
+}
+
+pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let assets: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+
+    let resp = PoolResponse {
+        assets,
+        total_share,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_simulation(
+    deps: Deps,
+    offer_asset: Asset,
+) -> Result<SimulationResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_pool: Asset;
+    let ask_pool: Asset;
+    if offer_asset.info.eq(&pools[0].info) {
+        offer_pool = pools[0].clone();
+        ask_pool = pools[1].clone();
+    } else if offer_asset.info.eq(&pools[1].info) {
+        offer_pool = pools[1].clone();
+        ask_pool = pools[0].clone();
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        fee_info.fee_config.total_fee,
+    );
+
+    let tax_amount: Uint128 = Asset {
+        info: ask_pool.info,
+        amount: return_amount,
+    }
+    .compute_tax(&deps.querier)?;
+
+    Ok(SimulationResponse {
+        return_amount: return_amount.checked_sub(tax_amount)?,
+        spread_amount,
+        commission_amount,
+        tax_amount,
+    })
+}
+
+pub fn query_reverse_simulation(
+    deps: Deps,
+    ask_asset: Asset,
+) -> Result<ReverseSimulationResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_pool: Asset;
+    let ask_pool: Asset;
+    if ask_asset.info.eq(&pools[0].info) {
+        ask_pool = pools[0].clone();
+        offer_pool = pools[1].clone();
+    } else if ask_asset.info.eq(&pools[1].info) {
+        ask_pool = pools[1].clone();
+        offer_pool = pools[0].clone();
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    // the pool pays the stability tax on top of the asked amount
+    let tax_amount: Uint128 = ask_asset.compute_reverse_tax(&deps.querier)?;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        offer_pool.amount,
+        ask_pool.amount,
+        ask_asset.amount.checked_add(tax_amount)?,
+        fee_info.fee_config.total_fee,
+    )?;
+
+    Ok(ReverseSimulationResponse {
+        offer_amount,
+        spread_amount,
+        commission_amount,
+        tax_amount,
+    })
+}
+
+/// ## Description
+/// Returns the LP tokens minted for the deposit, the deposited amounts beyond the pool ratio
+/// and the slippage of the deposit ratio in a [`SimulateProvideResponse`] object. The protocol
+/// fee share minted before the deposit is accounted for.
+/// ## Params
+/// * **assets** are the objects of type [`Asset`] deposited.
+pub fn query_simulate_provide(
+    deps: Deps,
+    assets: [Asset; 2],
+) -> Result<SimulateProvideResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let mut deposits = [Uint128::zero(); 2];
+    for (i, pool) in pools.iter().enumerate() {
+        deposits[i] = assets
+            .iter()
+            .find(|a| a.info == pool.info)
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})?;
+    }
+
+    let reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let total_share = total_share.checked_add(load_protocol_fee_share(
+        deps.storage,
+        &reserves,
+        total_share,
+    )?)?;
+    let share = compute_share(&deposits, &reserves, total_share)?;
+
+    let (excess_amounts, slippage) = if total_share.is_zero() {
+        ([Uint128::zero(); 2], Decimal::zero())
+    } else {
+        // the deposit passes the slippage tolerance check down to
+        // 1 - min(p0 * d1, p1 * d0) / max(p0 * d1, p1 * d0)
+        let a: Uint256 = Uint256::from(reserves[0]) * Uint256::from(deposits[1]);
+        let b: Uint256 = Uint256::from(reserves[1]) * Uint256::from(deposits[0]);
+        let slippage: Decimal256 = if a > b {
+            Decimal256::from_ratio(a - b, a)
+        } else {
+            Decimal256::from_ratio(b - a, b)
+        };
+
+        (
+            compute_excess_amounts(&deposits, &reserves, share, total_share),
+            slippage.into(),
+        )
+    };
+
+    Ok(SimulateProvideResponse {
+        share,
+        excess_assets: reserve_assets(&config.pair_info.asset_infos, &excess_amounts),
+        slippage,
+    })
+}
+
+/// ## Description
+/// Returns the assets refunded for burning `lp_amount` LP tokens in a
+/// [`SimulateWithdrawResponse`] object. The protocol fee share minted before the withdrawal is
+/// accounted for.
+/// ## Params
+/// * **lp_amount** is the amount of LP tokens burnt.
+pub fn query_simulate_withdraw(
+    deps: Deps,
+    lp_amount: Uint128,
+) -> Result<SimulateWithdrawResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let total_share = total_share.checked_add(load_protocol_fee_share(
+        deps.storage,
+        &reserves,
+        total_share,
+    )?)?;
+    if lp_amount > total_share {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    Ok(SimulateWithdrawResponse {
+        refund_assets: reserve_assets(
+            &config.pair_info.asset_infos,
+            &compute_refund_amounts(&reserves, lp_amount, total_share),
+        ),
+    })
+}
+
+/// ## Description
+/// Returns the mid-price of the base asset in the other asset, the ratio of the reserves, in a
+/// [`SpotPriceResponse`] object.
+/// ## Params
+/// * **base_asset** is the object of type [`AssetInfo`] priced in the other asset.
+pub fn query_spot_price(
+    deps: Deps,
+    base_asset: AssetInfo,
+) -> Result<SpotPriceResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let base_index: usize = pools
+        .iter()
+        .position(|pool| pool.info == base_asset)
+        .ok_or(ContractError::AssetMismatch {})?;
+    let base_pool: &Asset = &pools[base_index];
+    let quote_pool: &Asset = &pools[1 - base_index];
+    if base_pool.amount.is_zero() || quote_pool.amount.is_zero() {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    Ok(SpotPriceResponse {
+        price: Decimal::from_ratio(quote_pool.amount, base_pool.amount),
+    })
+}
+
+/// ## Description
+/// Returns the execution price of the swap computed by [`compute_swap`] with the pair fee
+/// settings, the mid-price and the price impact `1 - execution_price / mid_price` in a
+/// [`PriceImpactResponse`] object. The impact includes the commission.
+/// ## Params
+/// * **offer_asset** is the object of type [`Asset`] offered to the pool.
+pub fn query_price_impact(
+    deps: Deps,
+    offer_asset: Asset,
+) -> Result<PriceImpactResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_index: usize = pools
+        .iter()
+        .position(|pool| pool.info == offer_asset.info)
+        .ok_or(ContractError::AssetMismatch {})?;
+    let offer_pool: &Asset = &pools[offer_index];
+    let ask_pool: &Asset = &pools[1 - offer_index];
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if offer_pool.amount.is_zero() || ask_pool.amount.is_zero() {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+    let (return_amount, _, _) = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        fee_info.fee_config.total_fee,
+    );
+
+    // 1 - (return / offer) / (ask_pool / offer_pool), the return never exceeds the mid-price value
+    let mid_value: Uint256 = Uint256::from(offer_asset.amount) * Uint256::from(ask_pool.amount);
+    let return_value: Uint256 = Uint256::from(return_amount) * Uint256::from(offer_pool.amount);
+    let price_impact: Decimal256 = Decimal256::from_ratio(mid_value - return_value, mid_value);
 
-    // 1. send collateral token from the contract to a user
-    // 2. send inactive commission to collector
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        ("action", "swap"),
-        ("sender", sender.as_str()),
-        ("receiver", receiver.as_str()),
-        ("offer_asset", &offer_asset.info.to_string()),
-        ("ask_asset", &ask_pool.info.to_string()),
-        ("offer_amount", &offer_amount.to_string()),
-        ("return_amount", &return_amount.to_string()),
-        ("spread_amount", &spread_amount.to_string()),
-        ("commission_amount", &commission_amount.to_string()),
-        (
-            "protocol_fee_amount",
-            &protocol_fee_asset.amount.to_string(),
-        ),
-    ]))
+    Ok(PriceImpactResponse {
+        execution_price: Decimal::from_ratio(return_amount, offer_asset.amount),
+        mid_price: Decimal::from_ratio(ask_pool.amount, offer_pool.amount),
+        price_impact: price_impact.into(),
+    })
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
-    match msg {
-        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
-        QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps)?)?),
-        QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
-        QueryMsg::Simulation { offer_asset } => {
-            Ok(to_binary(&query_simulation(deps, offer_asset)?)?)
-        }
-        QueryMsg::ReverseSimulation { ask_asset } => {
-            Ok(to_binary(&query_reverse_simulation(deps, ask_asset)?)?)
-        }
-    }
+/// ## Description
+/// Returns the price accumulators of the pool as of the current block in a
+/// [`CumulativePricesResponse`] object.
+pub fn query_cumulative_prices(
+    deps: Deps,
+    env: Env,
+) -> Result<CumulativePricesResponse, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    let assets: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+
+    accumulate_prices(&env, &mut config, assets[0].amount, assets[1].amount);
+
+    Ok(CumulativePricesResponse {
+        assets,
+        total_share,
+        price0_cumulative_last: config.price0_cumulative_last,
+        price1_cumulative_last: config.price1_cumulative_last,
+        block_time_last: config.block_time_last,
+    })
 }
 
-pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
+/// ## Description
+/// Returns the LP tokens minted to the collector on the next liquidity event along with the
+/// pool assets backing them.
+pub fn query_pending_protocol_fee(deps: Deps) -> Result<PendingProtocolFeeResponse, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
 
-    Ok(ConfigResponse {
-        pair_info: config.pair_info,
-        factory: config.factory,
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let share = load_protocol_fee_share(
+        deps.storage,
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+
+    let total_share = total_share.checked_add(share)?;
+    let assets: Vec<Asset> = pools
+        .iter()
+        .map(|pool| Asset {
+            info: pool.info.clone(),
+            amount: if share.is_zero() {
+                Uint128::zero()
+            } else {
+                pool.amount.multiply_ratio(share, total_share)
+            },
+        })
+        .collect();
+
+    Ok(PendingProtocolFeeResponse {
+        share,
+        assets: [assets[0].clone(), assets[1].clone()],
     })
 }
 
-pub fn query_pair_info(deps: Deps) -> Result<PairInfo, ContractError> {
+/// ## Description
+/// Returns the cumulative trading statistics of the pair in a [`StatsResponse`] object.
+pub fn query_stats(deps: Deps) -> Result<StatsResponse, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
+    let stats: Stats = STATS.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(StatsResponse {
+        volumes: reserve_assets(&config.pair_info.asset_infos, &stats.volumes),
+        lp_commissions: reserve_assets(&config.pair_info.asset_infos, &stats.lp_commissions),
+        protocol_commissions: reserve_assets(
+            &config.pair_info.asset_infos,
+            &stats.protocol_commissions,
+        ),
+        swap_count: stats.swap_count,
+        last_trade_time: stats.last_trade_time,
+    })
+}
 
-    Ok(config.pair_info)
+/// ## Description
+/// Returns the hook contracts registered on the pair in a [`HooksResponse`] object.
+pub fn query_hooks(deps: Deps) -> Result<HooksResponse, ContractError> {
+    Ok(HooksResponse {
+        hooks: HOOKS.may_load(deps.storage)?.unwrap_or_default(),
+    })
 }
 
-pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
+/// ## Description
+/// Returns a limit order in an [`OrderResponse`] object.
+pub fn query_order(deps: Deps, order_id: u64) -> Result<OrderResponse, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.pair_info.contract_addr.clone();
+    let order: LimitOrder = ORDERS.load(deps.storage, U64Key::new(order_id))?;
 
-    let assets: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, &contract_addr)?;
-    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    Ok(order_response(&config, order_id, order))
+}
 
-    let resp = PoolResponse {
-        assets,
-        total_share,
-    };
+/// ## Description
+/// Returns a page of the open limit orders of an owner in an [`OrdersResponse`] object.
+/// ## Params
+/// * **owner** is the order owner address.
+///
+/// * **start_after** is the optional id of the last order of the previous page.
+///
+/// * **limit** is the optional maximum number of orders returned.
+pub fn query_orders_by_owner(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<OrdersResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let owner = deps.api.addr_validate(&owner)?;
 
-    Ok(resp)
+    let orders = read_orders_by_owner(deps.storage, &owner, start_after, limit)?
+        .into_iter()
+        .map(|(order_id, order)| order_response(&config, order_id, order))
+        .collect();
+
+    Ok(OrdersResponse { orders })
 }
 
-pub fn query_simulation(
+/// ## Description
+/// Returns a page of the open limit orders selling an asset by ascending price in an
+/// [`OrdersResponse`] object.
+/// ## Params
+/// * **offer_asset_info** is the object of type [`AssetInfo`] sold by the orders.
+///
+/// * **start_after** is the optional price and id of the last order of the previous page.
+///
+/// * **limit** is the optional maximum number of orders returned.
+pub fn query_orders_by_price(
     deps: Deps,
-    offer_asset: Asset,
-) -> Result<SimulationResponse, ContractError> {
+    offer_asset_info: AssetInfo,
+    start_after: Option<(Decimal, u64)>,
+    limit: Option<u32>,
+) -> Result<OrdersResponse, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.pair_info.contract_addr.clone();
-
-    let pools: [Asset; 2] = config
+    let offer_index: usize = config
         .pair_info
-        .query_pools(&deps.querier, &contract_addr)?;
+        .asset_infos
+        .iter()
+        .position(|info| info == &offer_asset_info)
+        .ok_or(ContractError::AssetMismatch {})?;
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
-    if offer_asset.info.eq(&pools[0].info) {
-        offer_pool = pools[0].clone();
-        ask_pool = pools[1].clone();
-    } else if offer_asset.info.eq(&pools[1].info) {
-        offer_pool = pools[1].clone();
-        ask_pool = pools[0].clone();
-    } else {
-        return Err(ContractError::AssetMismatch {});
+    let orders = read_orders_by_price(deps.storage, offer_index as u8, start_after, limit)?
+        .into_iter()
+        .map(|(order_id, order)| order_response(&config, order_id, order))
+        .collect();
+
+    Ok(OrdersResponse { orders })
+}
+
+fn order_response(config: &Config, order_id: u64, order: LimitOrder) -> OrderResponse {
+    let offer_index = order.offer_index as usize;
+
+    OrderResponse {
+        order_id,
+        owner: order.owner,
+        offer_asset: Asset {
+            info: config.pair_info.asset_infos[offer_index].clone(),
+            amount: order.offer_amount,
+        },
+        ask_asset_info: config.pair_info.asset_infos[1 - offer_index].clone(),
+        price: order.price,
+        filled_amount: order.filled_amount,
+        received_amount: order.received_amount,
     }
+}
 
-    // Get pool fee configuration from factory
-    let fee_info: FeeInfoResponse = query_fee_info(
-        &deps.querier,
-        &config.factory,
-        &config.pair_info.asset_infos,
+/// ## Description
+/// Fills the orders selling the asset at `offer_index` against the pool, by ascending price.
+/// Each order sells to the pool until the marginal pool price after the commission reaches the
+/// order price, `R' = sqrt((1 - f) * k / p)` for the reserve `R'` of the sold asset, and at most
+/// [`MAX_ORDER_FILLS`] orders are filled. An order whose full fill rounds below its price is
+/// removed and refunded, so that it does not block the orders behind it. Returns the messages
+/// paying the owners and the fill attributes.
+/// ## Params
+/// * **offer_index** is the index of the asset sold by the orders in the pair asset infos.
+///
+/// * **reserves** are the pool reserves, updated with the fills.
+fn fill_orders(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    config: &Config,
+    fee_info: &FeeInfo,
+    offer_index: usize,
+    reserves: &mut [Uint128; 2],
+) -> Result<(Vec<CosmosMsg>, Vec<Attribute>), ContractError> {
+    let ask_index = 1 - offer_index;
+    let commission_rate: Decimal256 = fee_info.fee_config.total_fee.into();
+    let orders = read_orders_by_price(
+        storage,
+        offer_index as u8,
+        None,
+        Some(MAX_ORDER_FILLS as u32),
     )?;
 
-    let (return_amount, spread_amount, commission_amount) = compute_swap(
-        offer_pool.amount,
-        ask_pool.amount,
-        offer_asset.amount,
-        fee_info.fee_config.total_fee,
-    );
+    let mut escrow: [Uint128; 2] = ORDER_ESCROW.may_load(storage)?.unwrap_or_default();
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attributes: Vec<Attribute> = vec![];
+    for (order_id, mut order) in orders {
+        if reserves[0].is_zero() || reserves[1].is_zero() {
+            break;
+        }
 
-    Ok(SimulationResponse {
-        return_amount,
-        spread_amount,
-        commission_amount,
-    })
-}
+        let k: Uint256 = Uint256::from(reserves[0]) * Uint256::from(reserves[1]);
+        let target_reserve: Uint256 =
+            sqrt(k * (Decimal256::one() - commission_rate) / Decimal256::from(order.price));
+        let offer_reserve: Uint256 = reserves[offer_index].into();
+        if target_reserve <= offer_reserve {
+            break;
+        }
 
-pub fn query_reverse_simulation(
-    deps: Deps,
-    ask_asset: Asset,
-) -> Result<ReverseSimulationResponse, ContractError> {
-    let config: Config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.pair_info.contract_addr.clone();
+        let fill_amount: Uint128 = std::cmp::min(
+            Uint256::from(order.offer_amount),
+            target_reserve - offer_reserve,
+        )
+        .into();
+        let (return_amount, _, commission_amount) = compute_swap(
+            reserves[offer_index],
+            reserves[ask_index],
+            fill_amount,
+            fee_info.fee_config.total_fee,
+        );
+        if return_amount.is_zero() || return_amount < fill_amount * order.price {
+            if fill_amount < order.offer_amount {
+                // the pool price reached the order price
+                break;
+            }
 
-    let pools: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, &contract_addr)?;
+            escrow[offer_index] = escrow[offer_index].checked_sub(order.offer_amount)?;
+            messages.push(
+                Asset {
+                    info: config.pair_info.asset_infos[offer_index].clone(),
+                    amount: order.offer_amount,
+                }
+                .transfer_msg_with_tax(querier, &order.owner)?,
+            );
+            attributes.push(attr("refunded_order_id", order_id.to_string()));
+            remove_order(storage, order_id, &order);
+            continue;
+        }
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
-    if ask_asset.info.eq(&pools[0].info) {
-        ask_pool = pools[0].clone();
-        offer_pool = pools[1].clone();
-    } else if ask_asset.info.eq(&pools[1].info) {
-        ask_pool = pools[1].clone();
-        offer_pool = pools[0].clone();
-    } else {
-        return Err(ContractError::AssetMismatch {});
+        reserves[offer_index] = reserves[offer_index].checked_add(fill_amount)?;
+        reserves[ask_index] = reserves[ask_index].checked_sub(return_amount)?;
+        escrow[offer_index] = escrow[offer_index].checked_sub(fill_amount)?;
+
+        let mut volumes = [Uint128::zero(); 2];
+        volumes[offer_index] = fill_amount;
+        let mut commissions = [Uint128::zero(); 2];
+        commissions[ask_index] = commission_amount;
+        record_trade(storage, env, fee_info, volumes, commissions)?;
+
+        messages.push(
+            Asset {
+                info: config.pair_info.asset_infos[ask_index].clone(),
+                amount: return_amount,
+            }
+            .transfer_msg_with_tax(querier, &order.owner)?,
+        );
+        attributes.extend(vec![
+            attr("filled_order_id", order_id.to_string()),
+            attr("filled_amount", fill_amount.to_string()),
+            attr("fill_return_amount", return_amount.to_string()),
+        ]);
+
+        order.offer_amount = order.offer_amount.checked_sub(fill_amount)?;
+        order.filled_amount = order.filled_amount.checked_add(fill_amount)?;
+        order.received_amount = order.received_amount.checked_add(return_amount)?;
+        if order.offer_amount.is_zero() {
+            remove_order(storage, order_id, &order);
+        } else {
+            // a partially filled order stops at its price, the following orders ask for more
+            store_order(storage, order_id, &order)?;
+            break;
+        }
     }
+    ORDER_ESCROW.save(storage, &escrow)?;
 
-    let fee_info: FeeInfoResponse = query_fee_info(
-        &deps.querier,
-        &config.factory,
-        &config.pair_info.asset_infos,
-    )?;
+    Ok((messages, attributes))
+}
 
-    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
-        offer_pool.amount,
-        ask_pool.amount,
-        ask_asset.amount,
-        fee_info.fee_config.total_fee,
-    )?;
+/// ## Description
+/// Returns the pair balances without the amounts escrowed by the limit orders.
+fn load_balances(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    config: &Config,
+) -> StdResult<[Asset; 2]> {
+    let mut balances: [Asset; 2] = config
+        .pair_info
+        .query_pools(querier, &env.contract.address)?;
+    let escrow: [Uint128; 2] = ORDER_ESCROW.may_load(storage)?.unwrap_or_default();
+    for (balance, escrowed) in balances.iter_mut().zip(escrow.iter()) {
+        balance.amount = balance.amount.checked_sub(*escrowed)?;
+    }
 
-    Ok(ReverseSimulationResponse {
-        offer_amount,
-        spread_amount,
-        commission_amount,
-    })
+    Ok(balances)
+}
+
+/// ## Description
+/// Returns the stored reserves of the pool in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **config** is the object of type [`Config`].
+pub fn load_pools(storage: &dyn Storage, config: &Config) -> StdResult<[Asset; 2]> {
+    let reserves: [Uint128; 2] = RESERVES.load(storage)?;
+
+    Ok([
+        Asset {
+            info: config.pair_info.asset_infos[0].clone(),
+            amount: reserves[0],
+        },
+        Asset {
+            info: config.pair_info.asset_infos[1].clone(),
+            amount: reserves[1],
+        },
+    ])
 }
 
-fn compute_swap(
+/// ## Description
+/// Accumulates the pool prices weighted by the seconds elapsed since the last accumulation.
+/// Must be called with the reserves as they were before the current state change.
+/// ## Params
+/// * **env** is the object of type [`Env`].
+///
+/// * **config** is the object of type [`Config`] holding the accumulators.
+///
+/// * **x** is the reserve of the first asset.
+///
+/// * **y** is the reserve of the second asset.
+pub fn accumulate_prices(env: &Env, config: &mut Config, x: Uint128, y: Uint128) {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return;
+    }
+
+    // we have to shift the block time when any of the reserves is zero
+    if !x.is_zero() && !y.is_zero() {
+        let time_elapsed = (block_time - config.block_time_last) as u128;
+        let scaled_time = Uint128::from(time_elapsed * 10u128.pow(TWAP_PRECISION.into()));
+
+        config.price0_cumulative_last = config
+            .price0_cumulative_last
+            .wrapping_add(scaled_time.multiply_ratio(y, x));
+        config.price1_cumulative_last = config
+            .price1_cumulative_last
+            .wrapping_add(scaled_time.multiply_ratio(x, y));
+    }
+
+    config.block_time_last = block_time;
+}
+
+pub fn compute_swap(
     offer_pool: Uint128,
     ask_pool: Uint128,
     offer_amount: Uint128,
@@ -616,7 +2360,237 @@ fn compute_swap(
     )
 }
 
-fn compute_offer_amount(
+/// ## Description
+/// Returns the amount of a single provided asset to swap so that the remainder and the swap
+/// return match the pool ratio after the swap. With the commission charged on the return,
+/// it is the positive root of `s^2 + (R * (2 - f) - a * f) * s - a * R = 0`.
+/// ## Params
+/// * **amount** is the provided amount `a`.
+///
+/// * **offer_pool** is the reserve `R` of the provided asset.
+///
+/// * **commission_rate** is the commission rate `f` of the pool.
+pub fn compute_single_asset_swap_amount(
+    amount: Uint128,
+    offer_pool: Uint128,
+    commission_rate: Decimal,
+) -> Uint128 {
+    let amount: Uint256 = amount.into();
+    let offer_pool: Uint256 = offer_pool.into();
+    let commission_rate: Decimal256 = commission_rate.into();
+
+    let two = Uint256::from(2u64);
+    let pool_term: Uint256 = offer_pool * (Decimal256::from_uint256(two) - commission_rate);
+    let amount_term: Uint256 = amount * commission_rate;
+    let c: Uint256 = Uint256::from(4u64) * amount * offer_pool;
+
+    // the linear coefficient may be negative for deposits much larger than the pool
+    let swap_amount: Uint256 = if pool_term >= amount_term {
+        let b = pool_term - amount_term;
+        (sqrt(b * b + c) - b).multiply_ratio(Uint256::one(), two)
+    } else {
+        let b = amount_term - pool_term;
+        (sqrt(b * b + c) + b).multiply_ratio(Uint256::one(), two)
+    };
+
+    swap_amount.into()
+}
+
+/// ## Description
+/// Returns the LP tokens minted for the deposits. The first deposit mints the geometric mean of
+/// the deposits less the locked minimum liquidity, the next ones the lowest of the deposit shares
+/// of each reserve.
+/// ## Params
+/// * **deposits** are the deposited amounts, in the order of the pair asset infos.
+///
+/// * **reserves** are the pool reserves before the deposit.
+///
+/// * **total_share** is the total supply of the LP token, the protocol fee share included.
+pub fn compute_share(
+    deposits: &[Uint128; 2],
+    reserves: &[Uint128; 2],
+    total_share: Uint128,
+) -> Result<Uint128, ContractError> {
+    let share = if total_share.is_zero() {
+        // Initial share = collateral amount
+        let share = Uint128::from((deposits[0].u128() * deposits[1].u128()).integer_sqrt());
+        if share <= Uint128::from(MINIMUM_LIQUIDITY_AMOUNT) {
+            return Err(ContractError::MinimumLiquidityAmountError {});
+        }
+
+        share.checked_sub(Uint128::from(MINIMUM_LIQUIDITY_AMOUNT))?
+    } else {
+        // min(1, 2)
+        // 1. sqrt(deposit_0 * exchange_rate_0_to_1 * deposit_0) * (total_share / sqrt(pool_0 * pool_1))
+        // == deposit_0 * total_share / pool_0
+        // 2. sqrt(deposit_1 * exchange_rate_1_to_0 * deposit_1) * (total_share / sqrt(pool_1 * pool_1))
+        // == deposit_1 * total_share / pool_1
+        std::cmp::min(
+            deposits[0].multiply_ratio(total_share, reserves[0]),
+            deposits[1].multiply_ratio(total_share, reserves[1]),
+        )
+    };
+
+    // prevent providing free token
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(share)
+}
+
+/// ## Description
+/// Returns the deposited amounts beyond the amounts backing `share`, rounded up in favor of
+/// the pool.
+/// ## Params
+/// * **deposits** are the deposited amounts, in the order of the pair asset infos.
+///
+/// * **reserves** are the pool reserves before the deposit.
+///
+/// * **share** is the amount of LP tokens minted for the deposits.
+///
+/// * **total_share** is the total supply of the LP token before the deposit.
+pub fn compute_excess_amounts(
+    deposits: &[Uint128; 2],
+    reserves: &[Uint128; 2],
+    share: Uint128,
+    total_share: Uint128,
+) -> [Uint128; 2] {
+    let mut excess_amounts = [Uint128::zero(); 2];
+    for i in 0..2 {
+        let required: Uint256 = div_ceil(
+            Uint256::from(share) * Uint256::from(reserves[i]),
+            total_share.into(),
+        );
+        let deposit: Uint256 = deposits[i].into();
+        if deposit > required {
+            excess_amounts[i] = (deposit - required).into();
+        }
+    }
+
+    excess_amounts
+}
+
+/// ## Description
+/// Returns the integer square root of `value` with Newton's method.
+fn sqrt(value: Uint256) -> Uint256 {
+    if value.is_zero() {
+        return Uint256::zero();
+    }
+
+    let two = Uint256::from(2u64);
+    let mut x = value;
+    let mut y = (x + Uint256::one()).multiply_ratio(Uint256::one(), two);
+    while y < x {
+        x = y;
+        y = (x + value.multiply_ratio(Uint256::one(), x)).multiply_ratio(Uint256::one(), two);
+    }
+
+    x
+}
+
+/// ## Description
+/// Returns the square root of the reserves product, which only grows with the swap commission
+/// between liquidity events.
+pub fn compute_root_k(reserves: &[Uint128; 2]) -> Uint128 {
+    sqrt(Uint256::from(reserves[0]) * Uint256::from(reserves[1])).into()
+}
+
+/// ## Description
+/// Returns the amount of LP tokens that gives the protocol the share `p` of the growth of the
+/// reserves product root since the last liquidity event, as in Uniswap v2:
+/// `total_share * (root_k - root_k_last) * p / ((1 - p) * root_k + p * root_k_last)`.
+/// ## Params
+/// * **reserves** are the current reserves of the pool.
+///
+/// * **root_k_last** is the reserves product root after the last liquidity event.
+///
+/// * **total_share** is the total supply of the LP token.
+///
+/// * **protocol_fee** is the protocol share `p` of the commission.
+pub fn compute_protocol_fee_share(
+    reserves: &[Uint128; 2],
+    root_k_last: Uint128,
+    total_share: Uint128,
+    protocol_fee: Decimal,
+) -> Uint128 {
+    if root_k_last.is_zero() || protocol_fee.is_zero() {
+        return Uint128::zero();
+    }
+
+    let root_k: Uint128 = compute_root_k(reserves);
+    if root_k <= root_k_last {
+        return Uint128::zero();
+    }
+
+    let protocol_fee: Decimal256 = protocol_fee.into();
+    let numerator: Uint256 =
+        Uint256::from(total_share) * Uint256::from(root_k - root_k_last) * protocol_fee;
+    let denominator: Uint256 = Uint256::from(root_k) * (Decimal256::one() - protocol_fee)
+        + Uint256::from(root_k_last) * protocol_fee;
+
+    numerator.multiply_ratio(Uint256::one(), denominator).into()
+}
+
+/// ## Description
+/// Returns the amount of LP tokens owed to the collector for the current reserves.
+fn load_protocol_fee_share(
+    storage: &dyn Storage,
+    reserves: &[Uint128; 2],
+    total_share: Uint128,
+) -> StdResult<Uint128> {
+    let fee_info: FeeInfo = FEE_INFO.load(storage)?;
+    let root_k_last: Uint128 = ROOT_K_LAST.may_load(storage)?.unwrap_or_default();
+
+    Ok(compute_protocol_fee_share(
+        reserves,
+        root_k_last,
+        total_share,
+        fee_info.fee_config.protocol_fee,
+    ))
+}
+
+/// ## Description
+/// Returns the message minting the LP tokens owed to the collector, if any, along with the
+/// minted amount that the LP token supply does not reflect until the message is executed.
+fn mint_protocol_fee(
+    storage: &dyn Storage,
+    config: &Config,
+    reserves: &[Uint128; 2],
+    total_share: Uint128,
+) -> StdResult<(Option<CosmosMsg>, Uint128)> {
+    let share = load_protocol_fee_share(storage, reserves, total_share)?;
+    if share.is_zero() {
+        return Ok((None, share));
+    }
+
+    let fee_info: FeeInfo = FEE_INFO.load(storage)?;
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: fee_info.collector.to_string(),
+            amount: share,
+        })?,
+        funds: vec![],
+    });
+
+    Ok((Some(msg), share))
+}
+
+/// ## Description
+/// Stores the reserves product root after a liquidity event, zero while the protocol fee is off.
+pub fn update_root_k_last(storage: &mut dyn Storage, reserves: &[Uint128; 2]) -> StdResult<()> {
+    let fee_info: FeeInfo = FEE_INFO.load(storage)?;
+    let root_k_last: Uint128 = if fee_info.fee_config.protocol_fee.is_zero() {
+        Uint128::zero()
+    } else {
+        compute_root_k(reserves)
+    };
+
+    ROOT_K_LAST.save(storage, &root_k_last)
+}
+
+pub fn compute_offer_amount(
     offer_pool: Uint128,
     ask_pool: Uint128,
     ask_amount: Uint128,
@@ -624,17 +2598,25 @@ fn compute_offer_amount(
 ) -> Result<(Uint128, Uint128, Uint128), ContractError> {
     // ask => offer
     // offer_amount = cp / (ask_pool - ask_amount / (1 - commission_rate)) - offer_pool
+    // both divisions round up, so that swapping the offer amount returns at least the ask amount
     let cp = Uint256::from(offer_pool) * Uint256::from(ask_pool);
-    let one_minus_commission = Decimal256::one() - Decimal256::from(commission_rate);
-    let inv_one_minus_commission: Decimal = (Decimal256::one() / one_minus_commission).into();
+    let fractional = Uint256::from(DECIMAL_FRACTIONAL);
+    let one_minus_commission: Uint256 =
+        fractional * (Decimal256::one() - Decimal256::from(commission_rate));
+
+    let before_commission_deduction: Uint256 =
+        div_ceil(Uint256::from(ask_amount) * fractional, one_minus_commission);
+    if before_commission_deduction >= Uint256::from(ask_pool) {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
 
-    let offer_amount: Uint128 = Uint128::from(cp.multiply_ratio(
-        Uint256::one(),
-        Uint256::from(ask_pool.checked_sub(ask_amount * inv_one_minus_commission)?),
+    let offer_amount: Uint128 = Uint128::from(div_ceil(
+        cp,
+        Uint256::from(ask_pool) - before_commission_deduction,
     ))
     .checked_sub(offer_pool)?;
 
-    let before_commission_deduction = ask_amount * inv_one_minus_commission;
+    let before_commission_deduction: Uint128 = before_commission_deduction.into();
     let spread_amount = (offer_amount * Decimal::from_ratio(ask_pool, offer_pool))
         .checked_sub(before_commission_deduction)
         .unwrap_or_else(|_| Uint128::zero());
@@ -642,6 +2624,12 @@ fn compute_offer_amount(
     Ok((offer_amount, spread_amount, commission_amount))
 }
 
+/// ## Description
+/// Returns `numerator / denominator` rounded up.
+fn div_ceil(numerator: Uint256, denominator: Uint256) -> Uint256 {
+    (numerator + denominator - Uint256::one()).multiply_ratio(Uint256::one(), denominator)
+}
+
 /// If `belief_price` and `max_spread` both are given,
 /// we compute new spread else we just use swap
 /// spread to check `max_spread`
@@ -710,22 +2698,45 @@ fn assert_slippage_tolerance(
     Ok(())
 }
 
-// we need 6 for xPRISM
-const TOKEN_SYMBOL_MAX_LENGTH: usize = 6;
-fn format_lp_token_name(
-    asset_infos: &[AssetInfo; 2],
-    querier: &QuerierWrapper,
-) -> Result<String, ContractError> {
-    let mut short_symbols: Vec<String> = vec![];
-    for asset_info in asset_infos {
-        let short_symbol: String = match asset_info {
-            AssetInfo::Native(denom) => denom.chars().take(TOKEN_SYMBOL_MAX_LENGTH).collect(),
-            AssetInfo::Cw20(contract_addr) => {
-                let token_symbol = query_token_symbol(querier, contract_addr)?;
-                token_symbol.chars().take(TOKEN_SYMBOL_MAX_LENGTH).collect()
-            }
-        };
-        short_symbols.push(short_symbol);
-    }
-    Ok(format!("{}-{}-LP", short_symbols[0], short_symbols[1]).to_uppercase())
+/// ## Description
+/// Migrates the pair to this version of the code. Pairs deployed before the cw2 versioning
+/// have their config upgraded, versioned pairs must not be downgraded. The fee settings are
+/// cached from the factory if the pair does not store them yet.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version = match CONTRACT.may_load(deps.storage)? {
+        Some(stored) => {
+            assert_migration_version(&stored, CONTRACT_NAME, CONTRACT_VERSION)?;
+            stored.version
+        }
+        None => {
+            migrate_config(deps.storage, &env)?;
+            "unversioned".to_string()
+        }
+    };
+    migrate_reserves(deps.storage, &deps.querier, &env)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    migrate_fee_info(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+        &env.contract.address,
+    )?;
+    migrate_pair_status(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+        &env.contract.address,
+    )?;
+    migrate_root_k_last(deps.storage)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "migrate"),
+        ("previous_version", previous_version.as_str()),
+        ("new_version", CONTRACT_VERSION),
+    ]))
 }
@@ -1,35 +0,0 @@
-use cosmwasm_std::{OverflowError, StdError};
-use thiserror::Error;
-
-#[derive(Error, Debug, PartialEq)]
-pub enum ContractError {
-    #[error("{0}")]
-    Std(#[from] StdError),
-
-    #[error("{0}")]
-    OverflowError(#[from] OverflowError),
-
-    #[error("Unauthorized")]
-    Unauthorized {},
-
-    #[error("Invalid zero amount")]
-    InvalidZeroAmount {},
-
-    #[error("Max spread assertion")]
-    MaxSpreadAssertion {},
-
-    #[error("Max slippage assertion")]
-    MaxSlippageAssertion {},
-
-    #[error("Asset mismatch")]
-    AssetMismatch {},
-
-    #[error("Too small offer amount")]
-    TooSmallOfferAmount {},
-
-    #[error("Doubling assets in asset infos")]
-    DoublingAssets {},
-
-    #[error("ParseReplyError")]
-    ParseReplyError {},
-}
@@ -1,11 +1,6 @@
 pub mod contract;
+pub mod migration;
 pub mod state;
 
-mod error;
-mod parse_reply;
-
 #[cfg(test)]
 mod testing;
-
-#[cfg(test)]
-mod mock_querier;
@@ -0,0 +1,184 @@
+use cosmwasm_std::{Addr, Env, QuerierWrapper, StdResult, Storage, Uint128};
+use cw_storage_plus::Item;
+use prismswap::asset::PairInfo;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::update_root_k_last;
+use crate::state::{Config, CONFIG, RESERVES, ROOT_K_LAST};
+
+pub const LEGACY: Item<LegacyConfig> = Item::new("config");
+
+/// the config of pairs deployed before the cw2 versioning
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub pair_info: PairInfo,
+    pub factory: Addr,
+}
+
+/// ## Description
+/// Adds the price accumulators to the config of a pair deployed before the cw2 versioning.
+/// The accumulation starts at the migration block.
+pub fn migrate_config(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let legacy_config: LegacyConfig = LEGACY.load(storage)?;
+    let config = Config {
+        pair_info: legacy_config.pair_info,
+        factory: legacy_config.factory,
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+        block_time_last: env.block.time.seconds(),
+    };
+
+    CONFIG.save(storage, &config)?;
+    Ok(())
+}
+
+/// ## Description
+/// Initializes the stored reserves of a pair deployed before the reserve accounting
+/// to the pair balances.
+pub fn migrate_reserves(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+) -> StdResult<()> {
+    if RESERVES.may_load(storage)?.is_some() {
+        return Ok(());
+    }
+
+    let config: Config = CONFIG.load(storage)?;
+    let pools = config
+        .pair_info
+        .query_pools(querier, &env.contract.address)?;
+    RESERVES.save(storage, &[pools[0].amount, pools[1].amount])
+}
+
+/// ## Description
+/// Starts the protocol fee accrual of a pair deployed before the protocol fee was minted as LP
+/// tokens. The fees accrue from the migration block.
+pub fn migrate_root_k_last(storage: &mut dyn Storage) -> StdResult<()> {
+    if ROOT_K_LAST.may_load(storage)?.is_some() {
+        return Ok(());
+    }
+
+    let reserves: [Uint128; 2] = RESERVES.load(storage)?;
+    update_root_k_last(storage, &reserves)
+}
+
+#[cfg(test)]
+mod migrate_tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::{Addr, Uint128};
+    use cw2::ContractVersion;
+    use prismswap::asset::{AssetInfo, PairInfo};
+    use prismswap::factory::PairType;
+
+    use crate::contract::{CONTRACT_NAME, CONTRACT_VERSION};
+    use crate::migration::{migrate_config, LegacyConfig, LEGACY};
+    use crate::state::{Config, CONFIG};
+    use prismswap::assert::assert_migration_version;
+    use prismswap::error::ContractError;
+
+    #[test]
+    fn test_config_migration() {
+        let mut deps = mock_dependencies(&[]);
+        let env = mock_env();
+
+        LEGACY
+            .save(
+                &mut deps.storage,
+                &LegacyConfig {
+                    pair_info: PairInfo {
+                        asset_infos: [
+                            AssetInfo::Native("uusd".to_string()),
+                            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                        ],
+                        contract_addr: Addr::unchecked("pair0000"),
+                        liquidity_token: Addr::unchecked("liquidity0000"),
+                        pair_type: PairType::Xyk {},
+                    },
+                    factory: Addr::unchecked("factory0000"),
+                },
+            )
+            .unwrap();
+
+        migrate_config(&mut deps.storage, &env).unwrap();
+
+        let config: Config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                pair_info: PairInfo {
+                    asset_infos: [
+                        AssetInfo::Native("uusd".to_string()),
+                        AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    ],
+                    contract_addr: Addr::unchecked("pair0000"),
+                    liquidity_token: Addr::unchecked("liquidity0000"),
+                    pair_type: PairType::Xyk {},
+                },
+                factory: Addr::unchecked("factory0000"),
+                price0_cumulative_last: Uint128::zero(),
+                price1_cumulative_last: Uint128::zero(),
+                block_time_last: env.block.time.seconds(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_migration_version() {
+        assert_migration_version(
+            &ContractVersion {
+                contract: CONTRACT_NAME.to_string(),
+                version: "1.0.0".to_string(),
+            },
+            CONTRACT_NAME,
+            CONTRACT_VERSION,
+        )
+        .map_err(ContractError::from)
+        .unwrap();
+        assert_migration_version(
+            &ContractVersion {
+                contract: CONTRACT_NAME.to_string(),
+                version: CONTRACT_VERSION.to_string(),
+            },
+            CONTRACT_NAME,
+            CONTRACT_VERSION,
+        )
+        .map_err(ContractError::from)
+        .unwrap();
+
+        let res = assert_migration_version(
+            &ContractVersion {
+                contract: "crates.io:prismswap-pair-stable".to_string(),
+                version: "1.0.0".to_string(),
+            },
+            CONTRACT_NAME,
+            CONTRACT_VERSION,
+        )
+        .map_err(ContractError::from);
+        assert_eq!(
+            res,
+            Err(ContractError::ContractNameMismatch {
+                name: "crates.io:prismswap-pair-stable".to_string(),
+                expected: CONTRACT_NAME.to_string(),
+            })
+        );
+
+        let res = assert_migration_version(
+            &ContractVersion {
+                contract: CONTRACT_NAME.to_string(),
+                version: "99.0.0".to_string(),
+            },
+            CONTRACT_NAME,
+            CONTRACT_VERSION,
+        )
+        .map_err(ContractError::from);
+        assert_eq!(
+            res,
+            Err(ContractError::MigrationDowngrade {
+                current: "99.0.0".to_string(),
+                new: CONTRACT_VERSION.to_string(),
+            })
+        );
+    }
+}
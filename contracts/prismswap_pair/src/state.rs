@@ -1,10 +1,27 @@
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
-use prismswap::asset::PairInfo;
+use cosmwasm_std::{Addr, Decimal, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map, PrimaryKey, U128Key, U64Key, U8Key};
+use prismswap::asset::{Asset, PairInfo};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub const CONFIG: Item<Config> = Item::new("config");
+/// the pool reserves, stored in the order of the pair asset infos
+pub const RESERVES: Item<[Uint128; 2]> = Item::new("reserves");
+/// the square root of the reserves product after the last liquidity event, zero while the
+/// protocol fee is off
+pub const ROOT_K_LAST: Item<Uint128> = Item::new("root_k_last");
+/// the limit orders, keyed by the order id
+pub const ORDERS: Map<U64Key, LimitOrder> = Map::new("orders");
+/// the id of the next limit order
+pub const NEXT_ORDER_ID: Item<u64> = Item::new("next_order_id");
+/// the order ids keyed by the owner and the order id
+pub const ORDERS_BY_OWNER: Map<(&Addr, U64Key), u64> = Map::new("orders_by_owner");
+/// the order ids keyed by the offer asset index, the price atomics and the order id
+pub const ORDERS_BY_PRICE: Map<(U8Key, U128Key, U64Key), u64> = Map::new("orders_by_price");
+/// the amounts escrowed by the open limit orders, held by the pair outside of the reserves
+pub const ORDER_ESCROW: Item<[Uint128; 2]> = Item::new("order_escrow");
+/// the asset lent by the flash swap in progress, other messages are rejected while it is set
+pub const FLASH_SWAP: Item<Asset> = Item::new("flash_swap");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -12,4 +29,117 @@ pub struct Config {
     pub pair_info: PairInfo,
     /// the factory contract address
     pub factory: Addr,
+    /// the cumulative price of the first asset denominated in the second asset
+    pub price0_cumulative_last: Uint128,
+    /// the cumulative price of the second asset denominated in the first asset
+    pub price1_cumulative_last: Uint128,
+    /// the block time in seconds of the last price accumulation
+    pub block_time_last: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitOrder {
+    pub owner: Addr,
+    /// the index of the offered asset in the pair asset infos
+    pub offer_index: u8,
+    /// the unfilled amount of the offered asset
+    pub offer_amount: Uint128,
+    /// the minimum amount of the other asset received per unit of the offered asset
+    pub price: Decimal,
+    /// the amount of the offered asset sold so far
+    pub filled_amount: Uint128,
+    /// the amount of the other asset sent to the owner so far
+    pub received_amount: Uint128,
+}
+
+/// the atomics of one [`Decimal`]
+const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn price_key(price: Decimal) -> U128Key {
+    U128Key::new((Uint128::new(DECIMAL_FRACTIONAL) * price).u128())
+}
+
+/// ## Description
+/// Saves a limit order along with its owner and price index entries.
+pub fn store_order(storage: &mut dyn Storage, order_id: u64, order: &LimitOrder) -> StdResult<()> {
+    ORDERS.save(storage, U64Key::new(order_id), order)?;
+    ORDERS_BY_OWNER.save(storage, (&order.owner, U64Key::new(order_id)), &order_id)?;
+    ORDERS_BY_PRICE.save(
+        storage,
+        (
+            U8Key::new(order.offer_index),
+            price_key(order.price),
+            U64Key::new(order_id),
+        ),
+        &order_id,
+    )
+}
+
+/// ## Description
+/// Removes a limit order along with its owner and price index entries.
+pub fn remove_order(storage: &mut dyn Storage, order_id: u64, order: &LimitOrder) {
+    ORDERS.remove(storage, U64Key::new(order_id));
+    ORDERS_BY_OWNER.remove(storage, (&order.owner, U64Key::new(order_id)));
+    ORDERS_BY_PRICE.remove(
+        storage,
+        (
+            U8Key::new(order.offer_index),
+            price_key(order.price),
+            U64Key::new(order_id),
+        ),
+    );
+}
+
+/// ## Description
+/// Returns a page of the open orders of an owner ordered by id.
+pub fn read_orders_by_owner(
+    storage: &dyn Storage,
+    owner: &Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, LimitOrder)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+
+    ORDERS_BY_OWNER
+        .prefix(owner)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, order_id) = item?;
+            Ok((order_id, ORDERS.load(storage, U64Key::new(order_id))?))
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns a page of the open orders offering an asset ordered by ascending price, then by id.
+/// ## Params
+/// * **offer_index** is the index of the offered asset in the pair asset infos.
+///
+/// * **start_after** is the id of the last order of the previous page.
+pub fn read_orders_by_price(
+    storage: &dyn Storage,
+    offer_index: u8,
+    start_after: Option<(Decimal, u64)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, LimitOrder)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|(price, order_id)| {
+        Bound::exclusive((price_key(price), U64Key::new(order_id)).joined_key())
+    });
+
+    ORDERS_BY_PRICE
+        .sub_prefix(U8Key::new(offer_index))
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, order_id) = item?;
+            Ok((order_id, ORDERS.load(storage, U64Key::new(order_id))?))
+        })
+        .collect()
 }
@@ -1,20 +1,42 @@
-use crate::contract::{assert_max_spread, execute, instantiate, query, reply};
-use crate::error::ContractError;
-use crate::mock_querier::mock_dependencies;
+use crate::contract::{
+    accumulate_prices, assert_max_spread, compute_single_asset_swap_amount, compute_swap, execute,
+    instantiate, migrate, query, reply, CONTRACT_NAME, CONTRACT_VERSION,
+};
+use crate::migration::{LegacyConfig, LEGACY};
+use crate::state::{
+    store_order, Config, LimitOrder, CONFIG, NEXT_ORDER_ID, ORDER_ESCROW, RESERVES, ROOT_K_LAST,
+};
+use prismswap::error::ContractError;
+use prismswap::mock_querier::mock_dependencies;
 
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Reply,
-    ReplyOn, Response, StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+    ReplyOn, Response, StdError, SubMsg, SubMsgExecutionResponse, Timestamp, Uint128, WasmMsg,
 };
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use prismswap::asset::{Asset, AssetInfo, PairInfo};
+use prismswap::factory::{FeeConfig, PairType};
+use prismswap::fee_info::{FeeInfo, FEE_INFO};
 use prismswap::pair::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashSwapCallbackMsg, Hook,
+    HooksResponse, InstantiateMsg, MigrateMsg, OrderResponse, OrdersResponse, PairHookMsg,
+    PendingProtocolFeeResponse, PoolResponse, PriceImpactResponse, QueryMsg,
+    ReverseSimulationResponse, SimulateProvideResponse, SimulateWithdrawResponse,
+    SimulationResponse, SpotPriceResponse, StatsResponse, MAX_HOOK_GAS, MAX_PAIR_HOOKS,
+    MINIMUM_LIQUIDITY_AMOUNT,
 };
+use prismswap::pair_status::{PairStatus, PAIR_STATUS};
 use prismswap::token::InstantiateMsg as TokenInstantiateMsg;
 
+fn mock_fee_config() -> FeeConfig {
+    FeeConfig {
+        total_fee: Decimal::permille(3),
+        protocol_fee: Decimal::percent(50),
+    }
+}
+
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
@@ -32,6 +54,9 @@ fn proper_initialization() {
         ],
         token_code_id: 10u64,
         factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
     };
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -51,6 +76,9 @@ fn proper_initialization() {
         ],
         token_code_id: 10u64,
         factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
     };
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -65,6 +93,9 @@ fn proper_initialization() {
         ],
         token_code_id: 10u64,
         factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -134,6 +165,9 @@ fn proper_initialization() {
         ConfigResponse {
             pair_info,
             factory: Addr::unchecked("factory0000"),
+            fee_config: mock_fee_config(),
+            collector: Addr::unchecked("collector"),
+            params: None,
         }
     )
 }
@@ -161,6 +195,9 @@ fn provide_liquidity() {
         ],
         token_code_id: 10u64,
         factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
     };
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -179,6 +216,9 @@ fn provide_liquidity() {
         ],
         token_code_id: 10u64,
         factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
     };
 
     let env = mock_env();
@@ -202,20 +242,47 @@ fn provide_liquidity() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // failure - the initial share must exceed the locked minimum liquidity
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(1000u128),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(1000u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::MinimumLiquidityAmountError {});
+
     // successfully provide liquidity for the exist pool
     let msg = ExecuteMsg::ProvideLiquidity {
         assets: [
             Asset {
                 info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(2000u128),
             },
             Asset {
                 info: AssetInfo::Native("uusd".to_string()),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(2000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -223,12 +290,13 @@ fn provide_liquidity() {
         "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(2000u128),
         }],
     );
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    let transfer_from_msg = res.messages.first().expect("no message");
+    let lock_msg = res.messages.get(1).expect("no message");
+    let mint_msg = res.messages.get(2).expect("no message");
     assert_eq!(
         transfer_from_msg,
         &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -236,7 +304,19 @@ fn provide_liquidity() {
             msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
                 owner: "addr0000".to_string(),
                 recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(2000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert_eq!(
+        lock_msg,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(MINIMUM_LIQUIDITY_AMOUNT),
             })
             .unwrap(),
             funds: vec![],
@@ -248,24 +328,26 @@ fn provide_liquidity() {
             contract_addr: "liquidity0000".to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Mint {
                 recipient: "addr0000".to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(1000u128),
             })
             .unwrap(),
             funds: vec![],
         }))
     );
 
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(2000u128), Uint128::from(2000u128)]
+    );
+
     // provide more liquidity 1:2, which is not proportional to 1:1,
-    // then it must accept 1:1 and treat left amount as donation
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                200u128 + 200u128, /* user deposit must be pre-applied */
-            ),
-        }],
-    )]);
+    // then it must accept 1:1 and refund the left amount
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(200u128), Uint128::from(200u128)],
+        )
+        .unwrap();
 
     deps.querier.with_token_balances(&[
         (
@@ -291,6 +373,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: Some("staking0000".to_string()), // try changing receiver
+        deadline: None,
     };
 
     let env = mock_env();
@@ -304,7 +387,7 @@ fn provide_liquidity() {
 
     // only accept 100, then 50 share will be generated with 100 * (100 / 200)
     let res: Response = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
+    let transfer_from_msg = res.messages.first().expect("no message");
     let mint_msg = res.messages.get(1).expect("no message");
     assert_eq!(
         transfer_from_msg,
@@ -331,6 +414,92 @@ fn provide_liquidity() {
             funds: vec![],
         }))
     );
+    assert_eq!(
+        res.messages.get(2).expect("no message"),
+        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))
+    );
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr("refund_assets", "native:uusd:100, cw20:asset0000:0")
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(300u128), Uint128::from(300u128)]
+    );
+
+    // the excess of the token is not pulled from the sender
+    // 75 = min(150 * 150 / 300, 300 * 150 / 300), 150 of the token is not needed
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(150u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(300u128))],
+        ),
+    ]);
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(300u128),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(150u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(150u128),
+        }],
+    );
+    let res: Response = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "addr0000".to_string(),
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(150u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(75u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr("refund_assets", "native:uusd:0, cw20:asset0000:150")
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(450u128), Uint128::from(450u128)]
+    );
 
     // check wrong argument
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -346,6 +515,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -365,16 +535,13 @@ fn provide_liquidity() {
         _ => panic!("Must return generic error"),
     }
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                100u128 + 100u128, /* user deposit must be pre-applied */
-            ),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
     deps.querier.with_token_balances(&[
         (
@@ -401,6 +568,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -417,14 +585,13 @@ fn provide_liquidity() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128 + 98u128 /* user deposit must be pre-applied */),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
     // failed because the price is under slippage_tolerance
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -440,6 +607,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -456,16 +624,13 @@ fn provide_liquidity() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                100u128 + 100u128, /* user deposit must be pre-applied */
-            ),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
     // successfully provides
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -481,6 +646,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -493,14 +659,13 @@ fn provide_liquidity() {
     );
     let _res = execute(deps.as_mut(), env, info, msg).unwrap();
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128 + 99u128 /* user deposit must be pre-applied */),
-        }],
-    )]);
+    // initialize reserves to 1:1
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
     // successfully provides
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -516,6 +681,7 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -530,20 +696,14 @@ fn provide_liquidity() {
 }
 
 #[test]
-fn withdraw_liquidity() {
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(100u128),
-    }]);
+fn minimum_liquidity_attack() {
+    let mut deps = mock_dependencies(&[]);
     deps.querier.with_token_balances(&[
         (
             &"liquidity0000".to_string(),
-            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
         ),
+        (&"asset0000".to_string(), &[]),
     ]);
 
     let msg = InstantiateMsg {
@@ -553,12 +713,11 @@ fn withdraw_liquidity() {
         ],
         token_code_id: 10u64,
         factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
     };
-
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
 
     // store liquidity token
     let reply_msg = Reply {
@@ -573,89 +732,106 @@ fn withdraw_liquidity() {
             ),
         }),
     };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
-
-    // withdraw liquidity
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
-        amount: Uint128::from(100u128),
-    });
-
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let log_withdrawn_share = res.attributes.get(2).expect("no log");
-    let log_refund_assets = res.attributes.get(3).expect("no log");
-    let msg_refund_0 = res.messages.get(0).expect("no message");
-    let msg_refund_1 = res.messages.get(1).expect("no message");
-    let msg_burn_liquidity = res.messages.get(2).expect("no message");
-    assert_eq!(
-        msg_refund_0,
-        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![Coin {
+    let provide_msg = |amount: u128| ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(amount),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(amount),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let provide_info = |amount: u128| {
+        mock_info(
+            "attacker0000",
+            &[Coin {
                 denom: "uusd".to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(amount),
             }],
-        }))
-    );
-    assert_eq!(
-        msg_refund_1,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
-    );
-    assert_eq!(
-        msg_burn_liquidity,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Burn {
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
-    );
+        )
+    };
 
+    // the attacker cannot open the pool with a dust deposit
+    let err = execute(deps.as_mut(), mock_env(), provide_info(1), provide_msg(1)).unwrap_err();
+    assert_eq!(err, ContractError::MinimumLiquidityAmountError {});
+
+    // the smallest possible deposit only gets the attacker a single share
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        provide_info(1001),
+        provide_msg(1001),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[4], attr("share", "1"));
+
+    // the attacker inflates the reserves by 1_000_000 of each asset,
+    // the donation goes to the locked shares and the protocol
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_001_001u128), Uint128::from(1_001_001u128)],
+        )
+        .unwrap();
+    deps.querier.with_token_balances(&[(
+        &"liquidity0000".to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(MINIMUM_LIQUIDITY_AMOUNT),
+            ),
+            (&"attacker0000".to_string(), &Uint128::from(1u128)),
+        ],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "attacker0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(1u128),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
     assert_eq!(
-        log_withdrawn_share,
-        &attr("withdrawn_share", 100u128.to_string())
-    );
-    assert_eq!(
-        log_refund_assets,
-        &attr("refund_assets", "native:uusd:100, cw20:asset0000:100")
+        res.attributes.last().unwrap(),
+        &attr("refund_assets", "native:uusd:500, cw20:asset0000:500")
     );
 }
 
 #[test]
-fn try_native_to_token() {
-    let total_share = Uint128::from(30000000000u128);
-    let asset_pool_amount = Uint128::from(20000000000u128);
-    let collateral_pool_amount = Uint128::from(30000000000u128);
-    let exchange_rate: Decimal = Decimal::from_ratio(asset_pool_amount, collateral_pool_amount);
-    let offer_amount = Uint128::from(1500000000u128);
-
+fn provide_single_asset() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
+        amount: Uint128::from(1_100_000u128),
     }]);
-
     deps.querier.with_token_balances(&[
         (
             &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000u128))],
         ),
         (
             &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
         ),
     ]);
 
@@ -666,12 +842,11 @@ fn try_native_to_token() {
         ],
         token_code_id: 10u64,
         factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
     };
-
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
 
     // store liquidity token
     let reply_msg = Reply {
@@ -686,170 +861,138 @@ fn try_native_to_token() {
             ),
         }),
     };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
-
-    // normal swap
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::Native("uusd".to_string()),
-            amount: offer_amount,
-        },
-        belief_price: None,
-        max_spread: None,
-        to: None,
-    };
-    let env = mock_env();
+    let provide_msg =
+        |max_spread: Option<Decimal>, min_share: Option<Uint128>| ExecuteMsg::ProvideSingleAsset {
+            asset: Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(100_000u128),
+            },
+            max_spread,
+            min_share,
+            receiver: None,
+            deadline: None,
+        };
     let info = mock_info(
         "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: offer_amount,
+            amount: Uint128::from(100_000u128),
         }],
     );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(res.messages.len(), 2);
-    let msg_transfer = res.messages.get(0).expect("no message");
-    let msg_collector = res.messages.get(1).expect("no message");
-
-    // current price is 1.5, so expected return without spread is 1000
-    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::from(952_380_952u128);
-    let expected_spread_amount = (offer_amount * exchange_rate)
-        .checked_sub(expected_ret_amount)
-        .unwrap();
-    let expected_commission_amount = expected_ret_amount.multiply_ratio(3u128, 1000u128); // 0.3%
-    let expected_return_amount = expected_ret_amount
-        .checked_sub(expected_commission_amount)
-        .unwrap();
-    let expected_protocol_fee = expected_commission_amount.multiply_ratio(1u128, 2u128); // 50%
-
-    // check simulation res
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: collateral_pool_amount, /* user deposit must be pre-applied */
-        }],
-    )]);
-
-    let simulation_res: SimulationResponse = from_binary(
-        &query(
-            deps.as_ref(),
-            mock_env(),
-            QueryMsg::Simulation {
-                offer_asset: Asset {
-                    info: AssetInfo::Native("uusd".to_string()),
-                    amount: offer_amount,
-                },
-            },
-        )
-        .unwrap(),
-    )
-    .unwrap();
 
-    assert_eq!(expected_return_amount, simulation_res.return_amount);
-    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
-    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
-
-    // check reverse simulation res
-    let reverse_simulation_res: ReverseSimulationResponse = from_binary(
-        &query(
-            deps.as_ref(),
-            mock_env(),
-            QueryMsg::ReverseSimulation {
-                ask_asset: Asset {
-                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
-                    amount: expected_return_amount,
-                },
-            },
-        )
-        .unwrap(),
-    )
-    .unwrap();
+    // failure - the internal swap exceeds the max spread
+    let msg = provide_msg(Some(Decimal::permille(1)), None);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxSpreadAssertion {});
 
-    assert!(
-        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
-            < 3i128
-    );
-    assert!(
-        (expected_commission_amount.u128() as i128
-            - reverse_simulation_res.commission_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
-    assert!(
-        (expected_spread_amount.u128() as i128
-            - reverse_simulation_res.spread_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
+    // failure - less than the minimum share would be minted
+    let msg = provide_msg(None, Some(Uint128::from(50_000u128)));
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
 
+    // 48885 = positive root of s^2 + (1000000 * 1.997 - 100000 * 0.003) * s - 100000 * 1000000
+    // 46467 = 1000000 * 48885 / 1048885 * 0.997
+    // 48731 = 46467 * 1000000 / 953533, the smaller of the two share ratios
+    let msg = provide_msg(Some(Decimal::percent(5)), Some(Uint128::from(46_000u128)));
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "swap"),
+            attr("action", "provide_single_asset"),
             attr("sender", "addr0000"),
             attr("receiver", "addr0000"),
-            attr("offer_asset", "native:uusd"),
-            attr("ask_asset", "cw20:asset0000"),
-            attr("offer_amount", offer_amount.to_string()),
-            attr("return_amount", expected_return_amount.to_string()),
-            attr("spread_amount", expected_spread_amount.to_string()),
-            attr("commission_amount", expected_commission_amount.to_string()),
-            attr("protocol_fee_amount", expected_protocol_fee.to_string()),
+            attr("asset", "native:uusd:100000"),
+            attr("swap_amount", "48885"),
+            attr("return_amount", "46467"),
+            attr("share", "48731"),
         ]
     );
-
     assert_eq!(
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+        res.messages.last().unwrap().msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
                 recipient: "addr0000".to_string(),
-                amount: expected_return_amount,
+                amount: Uint128::from(48731u128),
             })
             .unwrap(),
             funds: vec![],
-        })),
-        msg_transfer,
+        })
     );
+    let reserves = RESERVES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(reserves[0], Uint128::from(1_100_000u128));
+
+    // the commission of the internal swap accrues to the protocol on the next liquidity event
+    // 1048731 = 1000000 * 1048731 / 1000000, the reserves root after a balanced deposit
     assert_eq!(
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "collector".to_string(),
-                amount: expected_protocol_fee,
-            })
-            .unwrap(),
-            funds: vec![],
-        })),
-        msg_collector,
+        ROOT_K_LAST.load(deps.as_ref().storage).unwrap(),
+        Uint128::from(1_048_731u128)
     );
+    let res: PendingProtocolFeeResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingProtocolFee {}).unwrap())
+            .unwrap();
+    assert!(!res.share.is_zero());
 }
 
 #[test]
-fn try_token_to_native() {
-    let total_share = Uint128::from(20000000000u128);
-    let asset_pool_amount = Uint128::from(30000000000u128);
-    let collateral_pool_amount = Uint128::from(20000000000u128);
-    let exchange_rate = Decimal::from_ratio(collateral_pool_amount, asset_pool_amount);
-    let offer_amount = Uint128::from(1500000000u128);
+fn test_compute_single_asset_swap_amount() {
+    let commission_rate = Decimal::permille(3);
+    for (amount, offer_pool, ask_pool) in [
+        (100_000u128, 1_000_000u128, 1_000_000u128),
+        (1_000u128, 1_000_000_000_000u128, 5_000_000_000u128),
+        (10_000_000u128, 1_000_000u128, 2_000_000u128),
+        (
+            1_000_000_000_000_000_000u128,
+            3_000_000_000_000_000_000_000u128,
+            7_000_000_000_000_000_000_000u128,
+        ),
+    ] {
+        let amount = Uint128::from(amount);
+        let offer_pool = Uint128::from(offer_pool);
+        let ask_pool = Uint128::from(ask_pool);
+
+        let swap_amount = compute_single_asset_swap_amount(amount, offer_pool, commission_rate);
+        let (return_amount, _, _) =
+            compute_swap(offer_pool, ask_pool, swap_amount, commission_rate);
+
+        // the remainder and the return match the pool ratio after the swap
+        let share_0 = Uint128::from(1_000_000_000u128).multiply_ratio(
+            amount.checked_sub(swap_amount).unwrap(),
+            offer_pool + swap_amount,
+        );
+        let share_1 = Uint128::from(1_000_000_000u128)
+            .multiply_ratio(return_amount, ask_pool - return_amount);
+        let diff = if share_0 > share_1 {
+            share_0 - share_1
+        } else {
+            share_1 - share_0
+        };
+        assert!(diff <= share_0.multiply_ratio(1u128, 1000u128));
+    }
+}
 
+#[test]
+fn withdraw_liquidity() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: collateral_pool_amount,
+        amount: Uint128::from(100u128),
     }]);
     deps.querier.with_token_balances(&[
         (
             &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
         ),
         (
             &"asset0000".to_string(),
-            &[(
-                &MOCK_CONTRACT_ADDR.to_string(),
-                &(asset_pool_amount + offer_amount),
-            )],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
         ),
     ]);
 
@@ -860,6 +1003,9 @@ fn try_token_to_native() {
         ],
         token_code_id: 10u64,
         factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
     };
 
     let env = mock_env();
@@ -882,78 +1028,312 @@ fn try_token_to_native() {
     };
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
 
-    // unauthorized access; can not execute swap directly for token swap
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
-            amount: offer_amount,
-        },
-        belief_price: None,
-        max_spread: None,
-        to: None,
+    let withdraw_msg =
+        |min_assets: Option<[Asset; 2]>, receiver: Option<String>, deadline: Option<u64>| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "addr0000".to_string(),
+                msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                    min_assets,
+                    receiver,
+                    deadline,
+                })
+                .unwrap(),
+                amount: Uint128::from(100u128),
+            })
+        };
+    let min_assets = |uusd_amount: u128, asset_amount: u128| {
+        Some([
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(uusd_amount),
+            },
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(asset_amount),
+            },
+        ])
     };
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Unauthorized {} => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
 
-    // failure - invalid token addr
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::Cw20(Addr::unchecked("te")),
-            amount: offer_amount,
-        },
-        belief_price: None,
-        max_spread: None,
-        to: None,
-    };
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    assert_eq!(
-        err,
-        ContractError::Std(StdError::generic_err(
-            "Invalid input: human address too short"
-        ))
-    );
+    // failure - the deadline has passed
+    let deadline = mock_env().block.time.seconds() - 1;
+    let msg = withdraw_msg(None, None, Some(deadline));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ExpiredDeadline {});
 
-    // normal sell
+    // failure - less than the minimum assets would be withdrawn
+    let msg = withdraw_msg(min_assets(101, 100), None, None);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MinWithdrawAssertion {});
+
+    // withdraw liquidity
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
-        amount: offer_amount,
-        msg: to_binary(&Cw20HookMsg::Swap {
-            belief_price: None,
-            max_spread: None,
-            to: None,
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
         })
         .unwrap(),
+        amount: Uint128::from(100u128),
     });
-    let env = mock_env();
-    let info = mock_info("asset0000", &[]);
 
+    let env = mock_env();
+    let info = mock_info("liquidity0000", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(res.messages.len(), 2);
-    let msg_transfer = res.messages.get(0).expect("no message");
-    let msg_collector = res.messages.get(1).expect("no message");
+    let log_withdrawn_share = res.attributes.get(3).expect("no log");
+    let log_refund_assets = res.attributes.get(4).expect("no log");
+    let msg_refund_0 = res.messages.first().expect("no message");
+    let msg_refund_1 = res.messages.get(1).expect("no message");
+    let msg_burn_liquidity = res.messages.get(2).expect("no message");
+    assert_eq!(
+        msg_refund_0,
+        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))
+    );
+    assert_eq!(
+        msg_refund_1,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert_eq!(
+        msg_burn_liquidity,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
 
-    // current price is 1.5, so expected return without spread is 1000
-    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::from(952_380_952u128);
-    let expected_spread_amount = (offer_amount * exchange_rate)
-        .checked_sub(expected_ret_amount)
+    assert_eq!(
+        log_withdrawn_share,
+        &attr("withdrawn_share", 100u128.to_string())
+    );
+    assert_eq!(
+        log_refund_assets,
+        &attr("refund_assets", "native:uusd:100, cw20:asset0000:100")
+    );
+
+    // withdraw to another receiver with the minimum assets
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
         .unwrap();
-    let expected_commission_amount = expected_ret_amount.multiply_ratio(3u128, 1000u128); // 0.3%
-    let expected_return_amount = expected_ret_amount
-        .checked_sub(expected_commission_amount)
+    let deadline = mock_env().block.time.seconds();
+    let msg = withdraw_msg(
+        min_assets(100, 100),
+        Some("addr0001".to_string()),
+        Some(deadline),
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))
+    );
+    assert_eq!(res.attributes[2], attr("receiver", "addr0001"));
+}
+
+#[test]
+fn withdraw_single_asset() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1_000_000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
         .unwrap();
-    let expected_protocol_fee_amount = expected_commission_amount.multiply_ratio(1u128, 2u128); // 50%
 
-    // check simulation res
-    // return asset token balance as normal
+    let withdraw_msg = |ask_asset: AssetInfo, min_receive: Option<Uint128>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            msg: to_binary(&Cw20HookMsg::WithdrawSingleAsset {
+                ask_asset,
+                min_receive,
+                receiver: Some("addr0001".to_string()),
+                deadline: None,
+            })
+            .unwrap(),
+            amount: Uint128::from(100_000u128),
+        })
+    };
+
+    // failure - only the liquidity token can execute the hook
+    let msg = withdraw_msg(AssetInfo::Native("uusd".to_string()), None);
+    let info = mock_info("asset0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // failure - the ask asset is not in the pool
+    let msg = withdraw_msg(AssetInfo::Native("uluna".to_string()), None);
+    let info = mock_info("liquidity0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::AssetMismatch {});
+
+    // 100000 of each asset is withdrawn and 100000 asset0000 is swapped into the 900000:900000 pool
+    // 90000 = 900000 - 900000 * 900000 / 1000000
+    // 89730 = 90000 - 90000 * 0.003, the commission stays in the pool
+    // 189730 = 100000 + 89730
+    // 187851 = 189730 / 1.01 is received after the stability tax
+    let msg = withdraw_msg(
+        AssetInfo::Native("uusd".to_string()),
+        Some(Uint128::from(187_852u128)),
+    );
+    let info = mock_info("liquidity0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::MinWithdrawAssertion {});
+
+    let msg = withdraw_msg(
+        AssetInfo::Native("uusd".to_string()),
+        Some(Uint128::from(187_851u128)),
+    );
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(187_851u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(100_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw_single_asset"),
+            attr("sender", "addr0000"),
+            attr("receiver", "addr0001"),
+            attr("withdrawn_share", "100000"),
+            attr("swap_amount", "100000"),
+            attr("return_amount", "89730"),
+            attr("refund_asset", "native:uusd:189730"),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(810_270u128), Uint128::from(1_000_000u128)]
+    );
+}
+
+#[test]
+fn try_native_to_token() {
+    let total_share = Uint128::from(30000000000u128);
+    let asset_pool_amount = Uint128::from(20000000000u128);
+    let collateral_pool_amount = Uint128::from(30000000000u128);
+    let exchange_rate: Decimal = Decimal::from_ratio(asset_pool_amount, collateral_pool_amount);
+    let offer_amount = Uint128::from(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
+    }]);
+
     deps.querier.with_token_balances(&[
         (
             &"liquidity0000".to_string(),
@@ -961,24 +1341,114 @@ fn try_token_to_native() {
         ),
         (
             &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &(asset_pool_amount))],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
         ),
     ]);
 
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    // normal swap
+    let swap_msg = |deadline: Option<u64>| ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+
+    // failure - the deadline has passed
+    let env = mock_env();
+    let deadline = env.block.time.seconds() - 1;
+    let err = execute(deps.as_mut(), env, info.clone(), swap_msg(Some(deadline))).unwrap_err();
+    assert_eq!(err, ContractError::ExpiredDeadline {});
+
+    let env = mock_env();
+    let deadline = env.block.time.seconds();
+    let res = execute(deps.as_mut(), env, info, swap_msg(Some(deadline))).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let msg_transfer = res.messages.first().expect("no message");
+
+    // current price is 1.5, so expected return without spread is 1000
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
+    let expected_ret_amount = Uint128::from(952_380_952u128);
+    let expected_spread_amount = (offer_amount * exchange_rate)
+        .checked_sub(expected_ret_amount)
+        .unwrap();
+    let expected_commission_amount = expected_ret_amount.multiply_ratio(3u128, 1000u128); // 0.3%
+    let expected_return_amount = expected_ret_amount
+        .checked_sub(expected_commission_amount)
+        .unwrap();
+
+    // check simulation res against the reserves before the swap
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
     let simulation_res: SimulationResponse = from_binary(
         &query(
             deps.as_ref(),
             mock_env(),
             QueryMsg::Simulation {
                 offer_asset: Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
                     amount: offer_amount,
-                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
                 },
             },
         )
         .unwrap(),
     )
     .unwrap();
+
     assert_eq!(expected_return_amount, simulation_res.return_amount);
     assert_eq!(expected_commission_amount, simulation_res.commission_amount);
     assert_eq!(expected_spread_amount, simulation_res.spread_amount);
@@ -990,14 +1460,15 @@ fn try_token_to_native() {
             mock_env(),
             QueryMsg::ReverseSimulation {
                 ask_asset: Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
                     amount: expected_return_amount,
-                    info: AssetInfo::Native("uusd".to_string()),
                 },
             },
         )
         .unwrap(),
     )
     .unwrap();
+
     assert!(
         (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
             < 3i128
@@ -1021,79 +1492,2174 @@ fn try_token_to_native() {
             attr("action", "swap"),
             attr("sender", "addr0000"),
             attr("receiver", "addr0000"),
-            attr("offer_asset", "cw20:asset0000"),
-            attr("ask_asset", "native:uusd"),
+            attr("offer_asset", "native:uusd"),
+            attr("ask_asset", "cw20:asset0000"),
             attr("offer_amount", offer_amount.to_string()),
             attr("return_amount", expected_return_amount.to_string()),
             attr("spread_amount", expected_spread_amount.to_string()),
             attr("commission_amount", expected_commission_amount.to_string()),
-            attr(
-                "protocol_fee_amount",
-                expected_protocol_fee_amount.to_string()
-            ),
+            attr("tax_amount", "0"),
         ]
     );
 
     assert_eq!(
-        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
                 amount: expected_return_amount,
-            }],
+            })
+            .unwrap(),
+            funds: vec![],
         })),
         msg_transfer,
     );
-    assert_eq!(
-        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "collector".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: expected_protocol_fee_amount,
-            }],
-        })),
-        msg_collector,
-    );
-
-    // failed due to non asset token contract try to execute sell
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: offer_amount,
-        msg: to_binary(&Cw20HookMsg::Swap {
-            belief_price: None,
-            max_spread: None,
-            to: None,
-        })
-        .unwrap(),
-    });
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Unauthorized {} => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
 }
 
 #[test]
-fn test_max_spread() {
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Uint128::from(1200000000u128),
-        Uint128::from(989999u128),
-        Uint128::zero(),
-    )
-    .unwrap_err();
+fn try_token_to_native() {
+    let total_share = Uint128::from(20000000000u128);
+    let asset_pool_amount = Uint128::from(30000000000u128);
+    let collateral_pool_amount = Uint128::from(20000000000u128);
+    let exchange_rate = Decimal::from_ratio(collateral_pool_amount, asset_pool_amount);
+    let offer_amount = Uint128::from(1500000000u128);
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Uint128::from(1200000000u128),
-        Uint128::from(990000u128),
-        Uint128::zero(),
-    )
-    .unwrap();
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &(asset_pool_amount + offer_amount),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    // unauthorized access; can not execute swap directly for token swap
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // failure - invalid token addr
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Cw20(Addr::unchecked("te")),
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(
+            "Invalid input: human address too short"
+        ))
+    );
+
+    // normal sell
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let env = mock_env();
+    let info = mock_info("asset0000", &[]);
+
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let msg_transfer = res.messages.first().expect("no message");
+
+    // current price is 1.5, so expected return without spread is 1000
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
+    let expected_ret_amount = Uint128::from(952_380_952u128);
+    let expected_spread_amount = (offer_amount * exchange_rate)
+        .checked_sub(expected_ret_amount)
+        .unwrap();
+    let expected_commission_amount = expected_ret_amount.multiply_ratio(3u128, 1000u128); // 0.3%
+    let expected_return_amount = expected_ret_amount
+        .checked_sub(expected_commission_amount)
+        .unwrap();
+
+    // check simulation res against the reserves before the swap
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let simulation_res: SimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Simulation {
+                offer_asset: Asset {
+                    amount: offer_amount,
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(expected_return_amount, simulation_res.return_amount);
+    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
+    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
+
+    // check reverse simulation res
+    let reverse_simulation_res: ReverseSimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ReverseSimulation {
+                ask_asset: Asset {
+                    amount: expected_return_amount,
+                    info: AssetInfo::Native("uusd".to_string()),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(
+        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
+            < 3i128
+    );
+    assert!(
+        (expected_commission_amount.u128() as i128
+            - reverse_simulation_res.commission_amount.u128() as i128)
+            .abs()
+            < 3i128
+    );
+    assert!(
+        (expected_spread_amount.u128() as i128
+            - reverse_simulation_res.spread_amount.u128() as i128)
+            .abs()
+            < 3i128
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap"),
+            attr("sender", "addr0000"),
+            attr("receiver", "addr0000"),
+            attr("offer_asset", "cw20:asset0000"),
+            attr("ask_asset", "native:uusd"),
+            attr("offer_amount", offer_amount.to_string()),
+            attr("return_amount", expected_return_amount.to_string()),
+            attr("spread_amount", expected_spread_amount.to_string()),
+            attr("commission_amount", expected_commission_amount.to_string()),
+            attr("tax_amount", "0"),
+        ]
+    );
+
+    assert_eq!(
+        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: expected_return_amount,
+            }],
+        })),
+        msg_transfer,
+    );
+
+    // failed due to non asset token contract try to execute sell
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let env = mock_env();
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn swap_exact_output() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    let swap_msg = |max_offer: Option<u128>| ExecuteMsg::SwapExactOutput {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(110_000u128),
+        },
+        ask_amount: Uint128::from(90_637u128),
+        max_offer: max_offer.map(Uint128::from),
+        to: Some("addr0001".to_string()),
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(110_000u128),
+        }],
+    );
+
+    // 90910 = 90637 / 0.997 rounded up is the return before the commission
+    // 100002 = 1000000 * 1000000 / (1000000 - 90910) - 1000000 rounded up
+    let res: ReverseSimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ReverseSimulation {
+                ask_asset: Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(90_637u128),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.offer_amount, Uint128::from(100_002u128));
+
+    // swapping the offer amount returns at least the ask amount
+    let (return_amount, _, _) = compute_swap(
+        Uint128::from(1_000_000u128),
+        Uint128::from(1_000_000u128),
+        Uint128::from(100_002u128),
+        Decimal::permille(3),
+    );
+    assert!(return_amount >= Uint128::from(90_637u128));
+
+    // failure - the offer exceeds the max offer
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        swap_msg(Some(100_001)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxOfferAssertion {});
+
+    // failure - the offer exceeds the sent funds
+    let msg = ExecuteMsg::SwapExactOutput {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(100_001u128),
+        },
+        ask_amount: Uint128::from(90_637u128),
+        max_offer: None,
+        to: None,
+        deadline: None,
+    };
+    let small_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100_001u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), small_info, msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxOfferAssertion {});
+
+    let res = execute(deps.as_mut(), mock_env(), info, swap_msg(Some(100_002))).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0001".to_string(),
+                    amount: Uint128::from(90_637u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(9_998u128),
+                }],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap_exact_output"),
+            attr("sender", "addr0000"),
+            attr("receiver", "addr0001"),
+            attr("offer_asset", "native:uusd"),
+            attr("ask_asset", "cw20:asset0000"),
+            attr("offer_amount", "100002"),
+            attr("return_amount", "90637"),
+            attr("refund_amount", "9998"),
+            attr("spread_amount", "9092"),
+            attr("commission_amount", "272"),
+            attr("tax_amount", "0"),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_100_002u128), Uint128::from(909_363u128)]
+    );
+
+    // the cw20 hook buys uusd with the sent tokens
+    // 1004 = 1000 / 0.997 rounded up
+    // 831 = 909363 * 1100002 / (1100002 - 1004) - 909363 rounded up
+    let hook_msg = |sender: &str, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::SwapExactOutput {
+                ask_amount: Uint128::from(1_000u128),
+                max_offer: None,
+                to: None,
+                deadline: None,
+            })
+            .unwrap(),
+        })
+    };
+
+    // failure - only the pool tokens can be sent
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        hook_msg("addr0000", 1_000),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        hook_msg("addr0000", 1_000),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(1_000u128),
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(169u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_099_002u128), Uint128::from(910_194u128)]
+    );
+
+    // both swaps are recorded, the commissions are split by the protocol fee
+    let res: StatsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        StatsResponse {
+            volumes: [
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(100_002u128),
+                },
+                Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(831u128),
+                },
+            ],
+            lp_commissions: [
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(2u128),
+                },
+                Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(136u128),
+                },
+            ],
+            protocol_commissions: [
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(1u128),
+                },
+                Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(136u128),
+                },
+            ],
+            swap_count: 2,
+            last_trade_time: mock_env().block.time.seconds(),
+        }
+    );
+}
+
+#[test]
+fn swap_with_tax() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1_000_000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    let reserves = [Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)];
+    RESERVES.save(deps.as_mut().storage, &reserves).unwrap();
+
+    // 90637 = (1000000 - 1000000 * 1000000 / 1100000) * 0.997
+    // 898 = 90637 - 90637 / 1.01 is deducted as the tax
+    let res: SimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(100_000u128),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.return_amount, Uint128::from(89_739u128));
+    assert_eq!(res.tax_amount, Uint128::from(898u128));
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100_000u128),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(89_739u128),
+            }],
+        }))]
+    );
+    // the return amount is reported net of the tax, as in the simulation
+    assert_eq!(res.attributes[6], attr("return_amount", "89739"));
+    assert_eq!(res.attributes[9], attr("tax_amount", "898"));
+
+    // the tax of 990 rounds up to 10, so the pool pays 1000
+    RESERVES.save(deps.as_mut().storage, &reserves).unwrap();
+    let res: ReverseSimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ReverseSimulation {
+                ask_asset: Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(990u128),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.offer_amount, Uint128::from(1_006u128));
+    assert_eq!(res.tax_amount, Uint128::from(10u128));
+
+    // the receiver gets exactly the ask amount after the tax
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(2_000u128),
+        msg: to_binary(&Cw20HookMsg::SwapExactOutput {
+            ask_amount: Uint128::from(990u128),
+            max_offer: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(990u128),
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(994u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(999_000u128), Uint128::from(1_001_006u128)]
+    );
+
+    // the flash swap callback carries the lent amount received net of the tax
+    let msg = ExecuteMsg::FlashSwap {
+        ask_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(100_000u128),
+        },
+        msg: to_binary(&"arbitrage").unwrap(),
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("arber0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[..2].to_vec(),
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "arber0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(99_009u128),
+                }],
+            })),
+            SubMsg::new(
+                FlashSwapCallbackMsg {
+                    ask_asset: Asset {
+                        info: AssetInfo::Native("uusd".to_string()),
+                        amount: Uint128::from(99_009u128),
+                    },
+                    msg: to_binary(&"arbitrage").unwrap(),
+                }
+                .into_cosmos_msg("arber0000")
+                .unwrap()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn pair_hooks() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    let add_hook_msg = |contract_addr: &str| ExecuteMsg::AddHook {
+        contract_addr: contract_addr.to_string(),
+        gas_limit: 100_000u64,
+    };
+
+    // failure - only the factory owner can add hooks
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        add_hook_msg("hook0000"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // failure - the gas limit must be positive and capped
+    for gas_limit in [0u64, MAX_HOOK_GAS + 1] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            ExecuteMsg::AddHook {
+                contract_addr: "hook0000".to_string(),
+                gas_limit,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidHookGasLimit {});
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        add_hook_msg("hook0000"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "add_hook"),
+            attr("contract_addr", "hook0000"),
+            attr("gas_limit", "100000"),
+        ]
+    );
+
+    // failure - the hook is already registered
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        add_hook_msg("hook0000"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::HookAlreadyRegistered {});
+
+    let res: HooksResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap()).unwrap();
+    assert_eq!(
+        res.hooks,
+        vec![Hook {
+            contract_addr: Addr::unchecked("hook0000"),
+            gas_limit: 100_000u64,
+        }]
+    );
+
+    // the hook receives the swap after the transfer
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(1_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1],
+        SubMsg {
+            id: 2,
+            msg: PairHookMsg::Swap {
+                sender: Addr::unchecked("addr0000"),
+                receiver: Addr::unchecked("addr0000"),
+                offer_asset: Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(1_000u128),
+                },
+                return_asset: Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(997u128),
+                },
+                reserves: [
+                    Asset {
+                        info: AssetInfo::Native("uusd".to_string()),
+                        amount: Uint128::from(1_001_000u128),
+                    },
+                    Asset {
+                        info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                        amount: Uint128::from(999_003u128),
+                    },
+                ],
+            }
+            .into_cosmos_msg("hook0000")
+            .unwrap(),
+            gas_limit: Some(100_000u64),
+            reply_on: ReplyOn::Error,
+        }
+    );
+
+    // a failed hook does not revert the swap
+    let reply_msg = Reply {
+        id: 2,
+        result: ContractResult::Err("out of gas".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "pair_hook_failed"),
+            attr("error", "out of gas"),
+        ]
+    );
+
+    // failure - the number of hooks is limited
+    for i in 1..MAX_PAIR_HOOKS {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            add_hook_msg(&format!("hook{:04}", i)),
+        )
+        .unwrap();
+    }
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        add_hook_msg("hook9999"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TooManyHooks {});
+
+    // failure - only registered hooks can be removed
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RemoveHook {
+            contract_addr: "hook9999".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::HookNotFound {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RemoveHook {
+            contract_addr: "hook0000".to_string(),
+        },
+    )
+    .unwrap();
+    let res: HooksResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap()).unwrap();
+    assert_eq!(res.hooks.len(), MAX_PAIR_HOOKS - 1);
+    assert!(!res
+        .hooks
+        .iter()
+        .any(|hook| hook.contract_addr == Addr::unchecked("hook0000")));
+}
+
+#[test]
+fn paused_pair() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
+    // failure - only the factory can push the status
+    let msg = ExecuteMsg::UpdatePairStatus {
+        paused: true,
+        deregistered: false,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_pair_status"),
+            attr("paused", "true"),
+            attr("deregistered", "false"),
+        ]
+    );
+
+    // failure - swaps are halted
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(10u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    // failure - deposits are halted
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(10u128),
+            },
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(10u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    // failure - the single asset withdrawal swaps against the pool
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawSingleAsset {
+            ask_asset: AssetInfo::Native("uusd".to_string()),
+            min_receive: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(50u128),
+    });
+    let info = mock_info("liquidity0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    // liquidity providers can always exit
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(50u128),
+    });
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes[4],
+        attr("refund_assets", "native:uusd:50, cw20:asset0000:50")
+    );
+
+    // failure - the global pause is read from the factory without a push
+    let msg = ExecuteMsg::UpdatePairStatus {
+        paused: false,
+        deregistered: false,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+    deps.querier.with_global_paused(true);
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(10u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+    deps.querier.with_global_paused(false);
+
+    // failure - a deregistered pair only allows withdrawals
+    let status_msg = ExecuteMsg::UpdatePairStatus {
+        paused: false,
+        deregistered: true,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        status_msg,
+    )
+    .unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Deregistered {});
+}
+
+#[test]
+fn flash_swap() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    let flash_swap_msg = |amount: u128| ExecuteMsg::FlashSwap {
+        ask_asset: Asset {
+            info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+            amount: Uint128::from(amount),
+        },
+        msg: to_binary(&"arbitrage").unwrap(),
+        deadline: None,
+    };
+
+    // failure - cannot borrow the whole reserve
+    let info = mock_info("arber0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, flash_swap_msg(1_000_000)).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientLiquidity {});
+
+    let info = mock_info("arber0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, flash_swap_msg(100_000)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "arber0000".to_string(),
+                    amount: Uint128::from(100_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(
+                FlashSwapCallbackMsg {
+                    ask_asset: Asset {
+                        info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                        amount: Uint128::from(100_000u128),
+                    },
+                    msg: to_binary(&"arbitrage").unwrap(),
+                }
+                .into_cosmos_msg("arber0000")
+                .unwrap()
+            ),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::FinalizeFlashSwap {}).unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+
+    // failure - the pair is locked until the flash swap is finalized
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(10u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "arber0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::FlashSwapInProgress {});
+
+    // failure - only the pair can finalize the flash swap
+    let info = mock_info("arber0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FinalizeFlashSwap {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the callback paid uusd back for the 100000 asset0000 borrowed
+    // (1000000 + x - x * 0.003) * 900000 >= 1000000 * 1000000 for x >= 111446
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(900_000u128))],
+    )]);
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_111_445u128),
+        }],
+    )]);
+
+    // failure - the payment is too small
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FinalizeFlashSwap {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FlashSwapInvariant {});
+
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_111_446u128),
+        }],
+    )]);
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FinalizeFlashSwap {},
+    )
+    .unwrap();
+
+    // the whole payment stays in the pool
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "finalize_flash_swap"),
+            attr("offer_assets", "native:uusd:111446, cw20:asset0000:0"),
+            attr("reserves", "1111446, 900000"),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_111_446u128), Uint128::from(900_000u128)]
+    );
+
+    // the pair is unlocked
+    let info = mock_info("arber0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, flash_swap_msg(100_000)).unwrap();
+}
+
+#[test]
+fn protocol_fee_accrual() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+    ROOT_K_LAST
+        .save(deps.as_mut().storage, &Uint128::from(1_000_000u128))
+        .unwrap();
+
+    let res: PendingProtocolFeeResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingProtocolFee {}).unwrap())
+            .unwrap();
+    assert_eq!(res.share, Uint128::zero());
+
+    // the swap does not transfer the protocol fee
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(100_000u128),
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(10)),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // 90637 = 90909 - 272 commission is returned, the reserves are 1100000:909363
+    // 1000149 = sqrt(1100000 * 909363)
+    // 74 = 1000000 * 149 * 0.5 / (1000149 * 0.5 + 1000000 * 0.5)
+    // 81 = 1100000 * 74 / 1000074, 67 = 909363 * 74 / 1000074
+    let res: PendingProtocolFeeResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingProtocolFee {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        PendingProtocolFeeResponse {
+            share: Uint128::from(74u128),
+            assets: [
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(81u128),
+                },
+                Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(67u128),
+                },
+            ],
+        }
+    );
+
+    // the protocol fee share is minted on the withdrawal and dilutes it
+    // 1099 = 1100000 * 1000 / 1000074, 909 = 909363 * 1000 / 1000074
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(1_000u128),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "collector".to_string(),
+                amount: Uint128::from(74u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr("refund_assets", "native:uusd:1099, cw20:asset0000:909")
+    );
+
+    // the accrual restarts from the reserves after the withdrawal
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_098_901u128), Uint128::from(908_454u128)]
+    );
+    assert_eq!(
+        ROOT_K_LAST.load(deps.as_ref().storage).unwrap(),
+        Uint128::from(999_150u128)
+    );
+}
+
+#[test]
+fn sync_and_skim() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
+    // donations do not move the pool
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(150u128),
+        }],
+    )]);
+    let res: PoolResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Pool {}).unwrap()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::from(100u128));
+    assert_eq!(res.assets[1].amount, Uint128::from(100u128));
+
+    // anyone can skim the excess
+    let msg = ExecuteMsg::Skim {
+        to: "addr0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(50u128),
+            }],
+        }))]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(100u128), Uint128::from(100u128)]
+    );
+
+    // failure - only the factory owner can sync
+    let msg = ExecuteMsg::Sync {};
+    let err = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::Sync {};
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr("reserves", "native:uusd:150, cw20:asset0000:100"),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(150u128), Uint128::from(100u128)]
+    );
+
+    // nothing left to skim
+    let msg = ExecuteMsg::Skim {
+        to: "addr0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn limit_orders() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_001_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_101_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    let order_msg = |sender: &str, amount: u128, price: Decimal| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::SubmitOrder { price }).unwrap(),
+        })
+    };
+
+    // failure - only the pool tokens can be sent
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        order_msg("addr0000", 1_000, Decimal::one()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // failure - the price must be positive
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        order_msg("addr0000", 1_000, Decimal::zero()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidOrderPrice {});
+
+    // failure - the order must escrow the minimum amount
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        order_msg("addr0000", 999, Decimal::one()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::OrderTooSmall {});
+
+    // the orders sell above the pool price of 0.997 after the commission
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        order_msg("addr0001", 100_000, Decimal::percent(102)),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "submit_order"),
+            attr("owner", "addr0001"),
+            attr("order_id", "0"),
+            attr("offer_asset", "cw20:asset0000:100000"),
+            attr("price", "1.02"),
+        ]
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        order_msg("addr0000", 1_000, Decimal::one()),
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::SubmitOrder {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(1_000u128),
+        },
+        price: Decimal::percent(110),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the escrowed amounts are not part of the reserves
+    let msg = ExecuteMsg::Sync {};
+    execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)]
+    );
+
+    let res: OrdersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OrdersByOwner {
+                owner: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.orders.iter().map(|o| o.order_id).collect::<Vec<u64>>(),
+        vec![1, 2]
+    );
+    let res: OrdersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OrdersByOwner {
+                owner: "addr0000".to_string(),
+                start_after: Some(1),
+                limit: Some(1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.orders,
+        vec![OrderResponse {
+            order_id: 2,
+            owner: Addr::unchecked("addr0000"),
+            offer_asset: Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(1_000u128),
+            },
+            ask_asset_info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+            price: Decimal::percent(110),
+            filled_amount: Uint128::zero(),
+            received_amount: Uint128::zero(),
+        }]
+    );
+
+    // the orders selling an asset are sorted by price
+    let res: OrdersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OrdersByPrice {
+                offer_asset_info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.orders.iter().map(|o| o.order_id).collect::<Vec<u64>>(),
+        vec![1, 0]
+    );
+    let res: OrdersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OrdersByPrice {
+                offer_asset_info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                start_after: Some((Decimal::one(), 1)),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.orders.iter().map(|o| o.order_id).collect::<Vec<u64>>(),
+        vec![0]
+    );
+
+    // buying asset0000 raises its price past both orders, the cheaper one is filled first
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(100_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0002",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0002".to_string(),
+                    amount: Uint128::from(90_637u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(1_205u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(86_911u128),
+                }],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes[10..].to_vec(),
+        vec![
+            attr("filled_order_id", "1"),
+            attr("filled_amount", "1000"),
+            attr("fill_return_amount", "1205"),
+            attr("filled_order_id", "0"),
+            attr("filled_amount", "78447"),
+            attr("fill_return_amount", "86911"),
+        ]
+    );
+
+    // the partial fill stops once the pool price after the commission reaches 1.02
+    // 1011884 = 1100000 - 1205 - 86911, 988810 = 909363 + 1000 + 78447
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_011_884u128), Uint128::from(988_810u128)]
+    );
+    let res: OrderResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Order { order_id: 0 }).unwrap())
+            .unwrap();
+    assert_eq!(res.offer_asset.amount, Uint128::from(21_553u128));
+    assert_eq!(res.filled_amount, Uint128::from(78_447u128));
+    assert_eq!(res.received_amount, Uint128::from(86_911u128));
+    assert!(res.received_amount >= res.filled_amount * res.price);
+
+    // the filled order is removed
+    query(deps.as_ref(), mock_env(), QueryMsg::Order { order_id: 1 }).unwrap_err();
+
+    let res: StatsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(res.swap_count, 3);
+    assert_eq!(res.volumes[1].amount, Uint128::from(79_447u128));
+
+    // failure - only the owner can cancel the order
+    let msg = ExecuteMsg::CancelOrder { order_id: 0 };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::CancelOrder { order_id: 0 };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(21_553u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    let msg = ExecuteMsg::CancelOrder { order_id: 2 };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1_000u128),
+            }],
+        }))]
+    );
+    assert_eq!(
+        ORDER_ESCROW.load(deps.as_ref().storage).unwrap(),
+        [Uint128::zero(), Uint128::zero()]
+    );
+
+    let res: OrdersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OrdersByOwner {
+                owner: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.orders.is_empty());
+}
+
+#[test]
+fn limit_orders_dust() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_001_001u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    // the remainder of a partial fill, too small to return any amount
+    store_order(
+        deps.as_mut().storage,
+        0,
+        &LimitOrder {
+            owner: Addr::unchecked("addr0001"),
+            offer_index: 1,
+            offer_amount: Uint128::from(1u128),
+            price: Decimal::percent(90),
+            filled_amount: Uint128::from(999u128),
+            received_amount: Uint128::from(996u128),
+        },
+    )
+    .unwrap();
+    NEXT_ORDER_ID.save(deps.as_mut().storage, &1).unwrap();
+    ORDER_ESCROW
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::zero(), Uint128::from(1u128)],
+        )
+        .unwrap();
+
+    // the dust order is refunded and the order behind it is filled
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1_000u128),
+        msg: to_binary(&Cw20HookMsg::SubmitOrder {
+            price: Decimal::percent(95),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0001".to_string(),
+                    amount: Uint128::from(1u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(997u128),
+                }],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes[5..].to_vec(),
+        vec![
+            attr("refunded_order_id", "0"),
+            attr("filled_order_id", "1"),
+            attr("filled_amount", "1000"),
+            attr("fill_return_amount", "997"),
+        ]
+    );
+
+    query(deps.as_ref(), mock_env(), QueryMsg::Order { order_id: 0 }).unwrap_err();
+    query(deps.as_ref(), mock_env(), QueryMsg::Order { order_id: 1 }).unwrap_err();
+    assert_eq!(
+        ORDER_ESCROW.load(deps.as_ref().storage).unwrap(),
+        [Uint128::zero(), Uint128::zero()]
+    );
+}
+
+#[test]
+fn update_fee_config() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1000000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1000000u128), Uint128::from(1000000u128)],
+        )
+        .unwrap();
+    ROOT_K_LAST
+        .save(deps.as_mut().storage, &Uint128::from(990000u128))
+        .unwrap();
+
+    let simulation_msg = QueryMsg::Simulation {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(1000u128),
+        },
+    };
+    let res: SimulationResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), simulation_msg.clone()).unwrap()).unwrap();
+    assert_eq!(res.commission_amount, Uint128::from(2u128));
+
+    let fee_config = FeeConfig {
+        total_fee: Decimal::zero(),
+        protocol_fee: Decimal::zero(),
+    };
+    let msg = ExecuteMsg::UpdateFeeConfig {
+        fee_config: Some(fee_config.clone()),
+        collector: Some(Addr::unchecked("collector0001")),
+    };
+
+    // failure - only the factory can push fee settings
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_fee_config"),
+            attr("total_fee", "0"),
+            attr("protocol_fee", "0"),
+            attr("collector", "collector0001"),
+        ]
+    );
+
+    // the accrued protocol fee is minted to the previous collector with the previous settings
+    // 5025 = 1000000 * 10000 * 0.5 / (1000000 * 0.5 + 990000 * 0.5)
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "collector".to_string(),
+                amount: Uint128::from(5025u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    // the accrual restarts under the new settings, which turn the protocol fee off
+    assert_eq!(
+        ROOT_K_LAST.load(deps.as_ref().storage).unwrap(),
+        Uint128::zero()
+    );
+
+    let res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res.fee_config, fee_config);
+    assert_eq!(res.collector, Addr::unchecked("collector0001"));
+
+    // swaps read the cached fee settings
+    let res: SimulationResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), simulation_msg).unwrap()).unwrap();
+    assert_eq!(res.commission_amount, Uint128::zero());
+}
+
+#[test]
+fn test_max_spread() {
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Uint128::from(1200000000u128),
+        Uint128::from(989999u128),
+        Uint128::zero(),
+    )
+    .unwrap_err();
+
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Uint128::from(1200000000u128),
+        Uint128::from(990000u128),
+        Uint128::zero(),
+    )
+    .unwrap();
 
     assert_max_spread(
         None,
@@ -1104,24 +3670,588 @@ fn test_max_spread() {
     )
     .unwrap_err();
 
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Uint128::zero(),
-        Uint128::from(990000u128),
-        Uint128::from(10000u128),
+    assert_max_spread(
+        None,
+        Some(Decimal::percent(1)),
+        Uint128::zero(),
+        Uint128::from(990000u128),
+        Uint128::from(10000u128),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_query_pool() {
+    let total_share_amount = Uint128::from(111u128);
+    let asset_0_amount = Uint128::from(222u128);
+    let asset_1_amount = Uint128::from(333u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: asset_0_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_1_amount)],
+        ),
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(deps.as_mut().storage, &[asset_0_amount, asset_1_amount])
+        .unwrap();
+
+    let res: PoolResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Pool {}).unwrap()).unwrap();
+    assert_eq!(
+        res.assets,
+        [
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: asset_0_amount
+            },
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: asset_1_amount
+            }
+        ]
+    );
+    assert_eq!(res.total_share, total_share_amount);
+}
+
+#[test]
+fn spot_price_and_price_impact() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(2_000_000u128),
+        )],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // failure - the pool is empty
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SpotPrice {
+            base_asset: AssetInfo::Native("uusd".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientLiquidity {});
+
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(2_000_000u128)],
+        )
+        .unwrap();
+
+    let res: SpotPriceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SpotPrice {
+                base_asset: AssetInfo::Native("uusd".to_string()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.price, Decimal::from_ratio(2u128, 1u128));
+
+    let res: SpotPriceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SpotPrice {
+                base_asset: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.price, Decimal::percent(50));
+
+    // failure - the asset is not in the pair
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SpotPrice {
+            base_asset: AssetInfo::Native("uluna".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AssetMismatch {});
+
+    // 181818 = 2000000 - 1000000 * 2000000 / 1100000, 545 of which is the commission
+    // 0.093635 = 1 - 1.81273 / 2
+    let res: PriceImpactResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PriceImpact {
+                offer_asset: Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(100_000u128),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        PriceImpactResponse {
+            execution_price: Decimal::from_ratio(181_273u128, 100_000u128),
+            mid_price: Decimal::from_ratio(2u128, 1u128),
+            price_impact: Decimal::from_ratio(93_635u128, 1_000_000u128),
+        }
+    );
+
+    // a small swap mostly pays the commission, 997 = 999 - 2 are returned
+    let res: PriceImpactResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PriceImpact {
+                offer_asset: Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(2_000u128),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.mid_price, Decimal::percent(50));
+    assert_eq!(res.execution_price, Decimal::from_ratio(997u128, 2_000u128));
+    assert_eq!(res.price_impact, Decimal::permille(3));
+
+    // failure - zero offer amount
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PriceImpact {
+            offer_asset: Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::zero(),
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidZeroAmount {});
+}
+
+#[test]
+fn simulate_provide_and_withdraw() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (&"liquidity0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let provide_assets = |uusd_amount: u128, asset_amount: u128| {
+        [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(asset_amount),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(uusd_amount),
+            },
+        ]
+    };
+
+    // the first deposit mints the geometric mean less the locked minimum liquidity
+    // 3000 = sqrt(2000 * 8000) - 1000
+    let res: SimulateProvideResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateProvide {
+                assets: provide_assets(2_000, 8_000),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.share, Uint128::from(3_000u128));
+    assert_eq!(res.excess_assets[0].amount, Uint128::zero());
+    assert_eq!(res.excess_assets[1].amount, Uint128::zero());
+    assert_eq!(res.slippage, Decimal::zero());
+
+    // failure - the deposit does not cover the minimum liquidity
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SimulateProvide {
+            assets: provide_assets(1_000, 1_000),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MinimumLiquidityAmountError {});
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(100_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200_000u128))],
+        ),
+    ]);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100_000u128), Uint128::from(200_000u128)],
+        )
+        .unwrap();
+
+    // 10000 = min(10000 * 100000 / 100000, 30000 * 100000 / 200000)
+    // 10000 = 30000 - 10000 * 200000 / 100000 of asset0000 is not needed
+    // 1/3 = 1 - 100000 * 30000 / (200000 * 10000)
+    let res: SimulateProvideResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateProvide {
+                assets: provide_assets(10_000, 30_000),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        SimulateProvideResponse {
+            share: Uint128::from(10_000u128),
+            excess_assets: [
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::zero(),
+                },
+                Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(10_000u128),
+                },
+            ],
+            slippage: Decimal::from_ratio(1u128, 3u128),
+        }
+    );
+
+    // failure - the asset is not in the pair
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SimulateProvide {
+            assets: [
+                Asset {
+                    info: AssetInfo::Native("uluna".to_string()),
+                    amount: Uint128::from(10_000u128),
+                },
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(10_000u128),
+                },
+            ],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AssetMismatch {});
+
+    // a tenth of the supply
+    let res: SimulateWithdrawResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateWithdraw {
+                lp_amount: Uint128::from(10_000u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.refund_assets,
+        [
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(10_000u128),
+            },
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(20_000u128),
+            },
+        ]
+    );
+
+    // failure - more than the total supply
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SimulateWithdraw {
+            lp_amount: Uint128::from(100_001u128),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientLiquidity {});
+
+    // the deposit matches the simulation and the slippage bound
+    let provide_msg = |slippage_tolerance: Decimal| ExecuteMsg::ProvideLiquidity {
+        assets: provide_assets(10_000, 30_000),
+        slippage_tolerance: Some(slippage_tolerance),
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10_000u128),
+        }],
+    );
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        provide_msg(Decimal::percent(33)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        provide_msg(Decimal::percent(34)),
     )
     .unwrap();
+    assert!(res.attributes.contains(&attr("share", "10000")));
 }
 
 #[test]
-fn test_query_pool() {
-    let total_share_amount = Uint128::from(111u128);
-    let asset_0_amount = Uint128::from(222u128);
-    let asset_1_amount = Uint128::from(333u128);
+fn test_accumulate_prices() {
+    struct Case {
+        block_time: u64,
+        block_time_last: u64,
+        last0: u128,
+        last1: u128,
+        x_amount: u128,
+        y_amount: u128,
+    }
+
+    struct Result {
+        block_time_last: u64,
+        price_x: u128,
+        price_y: u128,
+    }
+
+    let test_cases: Vec<(Case, Result)> = vec![
+        (
+            Case {
+                block_time: 1000,
+                block_time_last: 0,
+                last0: 0,
+                last1: 0,
+                x_amount: 250_000000,
+                y_amount: 500_000000,
+            },
+            Result {
+                block_time_last: 1000,
+                price_x: 2000_000000, // 1000 * 2.0
+                price_y: 500_000000,  // 1000 * 0.5
+            },
+        ),
+        // same block, no accumulation
+        (
+            Case {
+                block_time: 1000,
+                block_time_last: 1000,
+                last0: 1,
+                last1: 2,
+                x_amount: 250_000000,
+                y_amount: 500_000000,
+            },
+            Result {
+                block_time_last: 1000,
+                price_x: 1,
+                price_y: 2,
+            },
+        ),
+        // empty pool, only the block time is shifted
+        (
+            Case {
+                block_time: 1500,
+                block_time_last: 1000,
+                last0: 500_000000,
+                last1: 2000_000000,
+                x_amount: 0,
+                y_amount: 0,
+            },
+            Result {
+                block_time_last: 1500,
+                price_x: 500_000000,
+                price_y: 2000_000000,
+            },
+        ),
+        (
+            Case {
+                block_time: 1500,
+                block_time_last: 1000,
+                last0: 500_000000,
+                last1: 2000_000000,
+                x_amount: 250_000000,
+                y_amount: 500_000000,
+            },
+            Result {
+                block_time_last: 1500,
+                price_x: 1500_000000, // 500 + 500 * 2.0
+                price_y: 2250_000000, // 2000 + 500 * 0.5
+            },
+        ),
+        // the accumulators wrap around on overflow
+        (
+            Case {
+                block_time: 1001,
+                block_time_last: 1000,
+                last0: u128::MAX,
+                last1: 0,
+                x_amount: 1_000000,
+                y_amount: 1_000000,
+            },
+            Result {
+                block_time_last: 1001,
+                price_x: 999_999,
+                price_y: 1_000000,
+            },
+        ),
+    ];
+
+    for (case, result) in test_cases {
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(case.block_time);
+
+        let mut config = Config {
+            pair_info: PairInfo {
+                asset_infos: [
+                    AssetInfo::Native("uusd".to_string()),
+                    AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                ],
+                contract_addr: Addr::unchecked(MOCK_CONTRACT_ADDR),
+                liquidity_token: Addr::unchecked("liquidity0000"),
+                pair_type: PairType::Xyk {},
+            },
+            factory: Addr::unchecked("factory0000"),
+            price0_cumulative_last: Uint128::new(case.last0),
+            price1_cumulative_last: Uint128::new(case.last1),
+            block_time_last: case.block_time_last,
+        };
+
+        accumulate_prices(
+            &env,
+            &mut config,
+            Uint128::new(case.x_amount),
+            Uint128::new(case.y_amount),
+        );
+
+        assert_eq!(config.block_time_last, result.block_time_last);
+        assert_eq!(config.price0_cumulative_last, Uint128::new(result.price_x));
+        assert_eq!(config.price1_cumulative_last, Uint128::new(result.price_y));
+    }
+}
+
+#[test]
+fn test_cumulative_prices() {
+    let asset_0_amount = Uint128::from(1000_000000u128);
+    let asset_1_amount = Uint128::from(2000_000000u128);
+    let offer_amount = Uint128::from(1_000000u128);
+
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: asset_0_amount,
+        amount: asset_0_amount + offer_amount, /* user deposit must be pre-applied */
     }]);
 
     deps.querier.with_token_balances(&[
@@ -1131,7 +4261,7 @@ fn test_query_pool() {
         ),
         (
             &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share_amount)],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
         ),
     ]);
 
@@ -1142,11 +4272,14 @@ fn test_query_pool() {
         ],
         token_code_id: 10u64,
         factory: Addr::unchecked("factory0000"),
+        fee_config: mock_fee_config(),
+        collector: Addr::unchecked("collector"),
+        init_params: None,
     };
 
     let env = mock_env();
+    let start_time = env.block.time.seconds();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
 
     // store liquidity token
@@ -1162,23 +4295,168 @@ fn test_query_pool() {
             ),
         }),
     };
-
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    RESERVES
+        .save(deps.as_mut().storage, &[asset_0_amount, asset_1_amount])
+        .unwrap();
 
-    let res: PoolResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Pool {}).unwrap()).unwrap();
+    // swap 100 seconds later, prices are accumulated with the reserves before the swap
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(start_time + 100);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let config: Config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.block_time_last, start_time + 100);
+    assert_eq!(config.price0_cumulative_last, Uint128::from(200_000000u128));
+    assert_eq!(config.price1_cumulative_last, Uint128::from(50_000000u128));
+
+    // the query accumulates up to the current block with the stored reserves
+    RESERVES
+        .save(deps.as_mut().storage, &[asset_0_amount, asset_1_amount])
+        .unwrap();
+
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(start_time + 150);
+    let res: CumulativePricesResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::CumulativePrices {}).unwrap()).unwrap();
     assert_eq!(
-        res.assets,
-        [
-            Asset {
-                info: AssetInfo::Native("uusd".to_string()),
-                amount: asset_0_amount
+        res,
+        CumulativePricesResponse {
+            assets: [
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: asset_0_amount,
+                },
+                Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: asset_1_amount,
+                },
+            ],
+            total_share: Uint128::from(100u128),
+            price0_cumulative_last: Uint128::from(300_000000u128),
+            price1_cumulative_last: Uint128::from(75_000000u128),
+            block_time_last: start_time + 150,
+        }
+    );
+}
+
+#[test]
+fn migration() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200u128))],
+    )]);
+    let env = mock_env();
+
+    let pair_info = PairInfo {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        contract_addr: Addr::unchecked(MOCK_CONTRACT_ADDR),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::Xyk {},
+    };
+    LEGACY
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                pair_info: pair_info.clone(),
+                factory: Addr::unchecked("factory0000"),
             },
-            Asset {
-                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
-                amount: asset_1_amount
-            }
+        )
+        .unwrap();
+
+    deps.querier.with_paused(true);
+    deps.querier.with_deregistered(true);
+
+    // unversioned pairs get the price accumulators
+    let res = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("previous_version", "unversioned"),
+            attr("new_version", CONTRACT_VERSION),
         ]
     );
-    assert_eq!(res.total_share, total_share_amount);
+    assert_eq!(
+        CONFIG.load(deps.as_ref().storage).unwrap(),
+        Config {
+            pair_info,
+            factory: Addr::unchecked("factory0000"),
+            price0_cumulative_last: Uint128::zero(),
+            price1_cumulative_last: Uint128::zero(),
+            block_time_last: env.block.time.seconds(),
+        }
+    );
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.contract, CONTRACT_NAME);
+    assert_eq!(version.version, CONTRACT_VERSION);
+
+    // the reserves are initialized to the balances
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(100u128), Uint128::from(200u128)]
+    );
+
+    // the protocol fee accrues from the migration, 141 = sqrt(100 * 200)
+    assert_eq!(
+        ROOT_K_LAST.load(deps.as_ref().storage).unwrap(),
+        Uint128::from(141u128)
+    );
+
+    // the fee settings are cached from the factory
+    assert_eq!(
+        FEE_INFO.load(deps.as_ref().storage).unwrap(),
+        FeeInfo {
+            fee_config: mock_fee_config(),
+            collector: Addr::unchecked("collector"),
+        }
+    );
+
+    // the pause and deregistration status is cached from the factory
+    assert_eq!(
+        PAIR_STATUS.load(deps.as_ref().storage).unwrap(),
+        PairStatus {
+            paused: true,
+            deregistered: true,
+        }
+    );
+
+    // versioned pairs keep their config
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "1.0.0").unwrap();
+    migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+
+    // refuse downgrades
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+    let res = migrate(deps.as_mut(), env, MigrateMsg {});
+    assert_eq!(
+        res,
+        Err(ContractError::MigrationDowngrade {
+            current: "99.0.0".to_string(),
+            new: CONTRACT_VERSION.to_string(),
+        })
+    );
 }
@@ -0,0 +1,2370 @@
+use crate::math::{compute_d, compute_offer_amount, compute_swap, N_COINS};
+use crate::state::{Config, CONFIG, FLASH_SWAP, RESERVES, RESERVES_LAST};
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Uint128,
+    WasmMsg,
+};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cw2::{set_contract_version, CONTRACT};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use prismswap::assert::{
+    assert_deadline, assert_migration_version, assert_min_assets, assert_pair_active,
+};
+use prismswap::asset::{
+    format_lp_token_name, Asset, AssetInfo, PairInfo, PrismSwapAsset, PrismSwapAssetInfo,
+};
+use prismswap::error::ContractError;
+use prismswap::factory::{FeeConfig, PairType};
+use prismswap::fee_info::{self, FeeInfo, FEE_INFO};
+use prismswap::hooks::{add_hook, hook_messages, remove_hook, HOOKS, HOOK_REPLY_ID};
+use prismswap::pair::{
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashSwapCallbackMsg,
+    HooksResponse, InstantiateMsg, MigrateMsg, PairHookMsg, PendingProtocolFeeResponse,
+    PoolResponse, PriceImpactResponse, QueryMsg, ReverseSimulationResponse,
+    SimulateProvideResponse, SimulateWithdrawResponse, SimulationResponse, SpotPriceResponse,
+    StablePoolConfig, StablePoolParams, StablePoolUpdateParams, StatsResponse, MAX_AMP,
+    MAX_AMP_CHANGE, MINIMUM_LIQUIDITY_AMOUNT, MIN_AMP_CHANGING_TIME, TWAP_PRECISION,
+};
+use prismswap::pair_status::{self, PairStatus, PAIR_STATUS};
+use prismswap::parse_reply::parse_reply_instantiate_data;
+use prismswap::pool::{compute_deposit_reserves, compute_refund_amounts, reserve_assets};
+use prismswap::querier::{query_factory_config, query_supply};
+use prismswap::stats::{record_trade, Stats, STATS};
+use prismswap::token::InstantiateMsg as TokenInstantiateMsg;
+
+/// contract name that is used for migration
+pub const CONTRACT_NAME: &str = "crates.io:prismswap-pair-stable";
+/// contract version that is used for migration
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const INSTANTIATE_REPLY_ID: u64 = 1;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    msg.asset_infos[0].check(deps.api)?;
+    msg.asset_infos[1].check(deps.api)?;
+
+    if msg.asset_infos[0] == msg.asset_infos[1] {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    let params: StablePoolParams = match msg.init_params {
+        Some(params) => from_binary(&params)?,
+        None => return Err(ContractError::InitParamsNotFound {}),
+    };
+
+    if params.amp == 0 || params.amp > MAX_AMP {
+        return Err(ContractError::IncorrectAmp {});
+    }
+
+    let config = Config {
+        pair_info: PairInfo {
+            contract_addr: env.contract.address.clone(),
+            liquidity_token: Addr::unchecked(""),
+            asset_infos: msg.asset_infos.clone(),
+            pair_type: PairType::Stable {},
+        },
+        factory: msg.factory,
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+        block_time_last: env.block.time.seconds(),
+        init_amp: params.amp,
+        init_amp_time: env.block.time.seconds(),
+        next_amp: params.amp,
+        next_amp_time: env.block.time.seconds(),
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    FEE_INFO.save(
+        deps.storage,
+        &FeeInfo {
+            fee_config: msg.fee_config,
+            collector: msg.collector,
+        },
+    )?;
+    PAIR_STATUS.save(deps.storage, &PairStatus::default())?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
+
+    Ok(Response::new().add_submessage(SubMsg {
+        msg: WasmMsg::Instantiate {
+            code_id: msg.token_code_id,
+            msg: to_binary(&TokenInstantiateMsg {
+                name: token_name,
+                symbol: "uLP".to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+            funds: vec![],
+            admin: None,
+            label: "".to_string(),
+        }
+        .into(),
+        id: INSTANTIATE_REPLY_ID,
+        gas_limit: None,
+        reply_on: ReplyOn::Success,
+    }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // the pool is unbalanced until the flash swap is finalized
+    if FLASH_SWAP.may_load(deps.storage)?.is_some()
+        && !matches!(msg, ExecuteMsg::FinalizeFlashSwap {})
+    {
+        return Err(ContractError::FlashSwapInProgress {});
+    }
+
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ProvideLiquidity {
+            assets,
+            slippage_tolerance,
+            receiver,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assets[0].info.check(deps.api)?;
+            assets[1].info.check(deps.api)?;
+            provide_liquidity(deps, env, info, assets, slippage_tolerance, receiver)
+        }
+        ExecuteMsg::ProvideSingleAsset {
+            asset,
+            max_spread,
+            min_share,
+            receiver,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            asset.info.check(deps.api)?;
+            provide_single_asset(deps, env, info, asset, max_spread, min_share, receiver)
+        }
+        ExecuteMsg::Swap {
+            offer_asset,
+            belief_price,
+            max_spread,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            offer_asset.info.check(deps.api)?;
+            if !offer_asset.info.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(&to_addr)?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                belief_price,
+                max_spread,
+                to_addr,
+            )
+        }
+        ExecuteMsg::SwapExactOutput {
+            offer_asset,
+            ask_amount,
+            max_offer,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            offer_asset.info.check(deps.api)?;
+            if !offer_asset.info.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(&to_addr)?)
+            } else {
+                None
+            };
+
+            swap_exact_output(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                ask_amount,
+                max_offer,
+                to_addr,
+            )
+        }
+        ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
+        ExecuteMsg::UpdateFeeConfig {
+            fee_config,
+            collector,
+        } => update_fee_config(deps, env, info, fee_config, collector),
+        ExecuteMsg::UpdatePairStatus {
+            paused,
+            deregistered,
+        } => {
+            let config: Config = CONFIG.load(deps.storage)?;
+            pair_status::update_pair_status(
+                deps.storage,
+                &info,
+                &config.factory,
+                PairStatus {
+                    paused,
+                    deregistered,
+                },
+            )
+        }
+        ExecuteMsg::Sync {} => sync(deps, env, info),
+        ExecuteMsg::Skim { to } => {
+            let to_addr = deps.api.addr_validate(&to)?;
+            skim(deps.as_ref(), env, to_addr)
+        }
+        ExecuteMsg::AddHook {
+            contract_addr,
+            gas_limit,
+        } => {
+            let config: Config = CONFIG.load(deps.storage)?;
+            add_hook(deps, info, &config.factory, contract_addr, gas_limit)
+        }
+        ExecuteMsg::RemoveHook { contract_addr } => {
+            let config: Config = CONFIG.load(deps.storage)?;
+            remove_hook(deps, info, &config.factory, contract_addr)
+        }
+        ExecuteMsg::FlashSwap {
+            ask_asset,
+            msg,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            ask_asset.info.check(deps.api)?;
+            flash_swap(deps, env, info, ask_asset, msg)
+        }
+        ExecuteMsg::FinalizeFlashSwap {} => finalize_flash_swap(deps, env, info),
+        // the order fills solve the constant product curve for the order price in closed form,
+        // the stableswap curve has none, so limit orders stay on the constant product pair
+        ExecuteMsg::SubmitOrder { .. } | ExecuteMsg::CancelOrder { .. } => {
+            Err(ContractError::NonSupported {})
+        }
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let contract_addr = info.sender.clone();
+
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Swap {
+            belief_price,
+            max_spread,
+            to,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            // only asset contract can execute this message
+            let mut authorized: bool = false;
+            let config: Config = CONFIG.load(deps.storage)?;
+
+            for pool in config.pair_info.asset_infos {
+                if let AssetInfo::Cw20(contract_addr) = &pool {
+                    if contract_addr.eq(&info.sender) {
+                        authorized = true;
+                    }
+                }
+            }
+
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Cw20(contract_addr),
+                    amount: cw20_msg.amount,
+                },
+                belief_price,
+                max_spread,
+                to_addr,
+            )
+        }
+        Ok(Cw20HookMsg::SwapExactOutput {
+            ask_amount,
+            max_offer,
+            to,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            // only asset contract can execute this message
+            let config: Config = CONFIG.load(deps.storage)?;
+            let offer_info = AssetInfo::Cw20(contract_addr);
+            if !config.pair_info.asset_infos.contains(&offer_info) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap_exact_output(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: offer_info,
+                    amount: cw20_msg.amount,
+                },
+                ask_amount,
+                max_offer,
+                to_addr,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            min_assets,
+            receiver,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            let sender = Addr::unchecked(cw20_msg.sender);
+            let receiver = if let Some(receiver) = receiver {
+                deps.api.addr_validate(&receiver)?
+            } else {
+                sender.clone()
+            };
+
+            withdraw_liquidity(
+                deps,
+                env,
+                info,
+                sender,
+                cw20_msg.amount,
+                min_assets,
+                receiver,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawSingleAsset {
+            ask_asset,
+            min_receive,
+            receiver,
+            deadline,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            let sender = Addr::unchecked(cw20_msg.sender);
+            let receiver = if let Some(receiver) = receiver {
+                deps.api.addr_validate(&receiver)?
+            } else {
+                sender.clone()
+            };
+
+            withdraw_single_asset(
+                deps,
+                env,
+                info,
+                sender,
+                cw20_msg.amount,
+                ask_asset,
+                min_receive,
+                receiver,
+            )
+        }
+        // limit orders are only supported by the constant product pair
+        Ok(Cw20HookMsg::SubmitOrder { .. }) => Err(ContractError::NonSupported {}),
+        Err(err) => Err(ContractError::Std(err)),
+    }
+}
+
+/// This just stores the result for future query
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    // a failed hook is reverted on its own without reverting the pair action
+    if msg.id == HOOK_REPLY_ID {
+        let error = msg.result.into_result().err().unwrap_or_default();
+        return Ok(
+            Response::new().add_attributes(vec![("action", "pair_hook_failed"), ("error", &error)])
+        );
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if config.pair_info.liquidity_token != Addr::unchecked("") {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let res = parse_reply_instantiate_data(msg).map_err(|_| ContractError::ParseReplyError {})?;
+    let liquidity_token = res.contract_address;
+
+    config.pair_info.liquidity_token = deps.api.addr_validate(&liquidity_token)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("liquidity_token_addr", liquidity_token))
+}
+
+/// ## Description
+/// Updates the amplification coefficient ramp of the pool. Only the factory owner can execute it.
+/// ## Params
+/// * **params** is the binary serialised [`StablePoolUpdateParams`].
+pub fn update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    params: Binary,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    let factory_config = query_factory_config(&deps.querier, &config.factory)?;
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match from_binary::<StablePoolUpdateParams>(&params)? {
+        StablePoolUpdateParams::StartChangingAmp {
+            next_amp,
+            next_amp_time,
+        } => start_changing_amp(&env, &mut config, next_amp, next_amp_time)?,
+        StablePoolUpdateParams::StopChangingAmp {} => stop_changing_amp(&env, &mut config),
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_config"),
+        ("next_amp", &config.next_amp.to_string()),
+        ("next_amp_time", &config.next_amp_time.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Updates the fee settings cached by the pair. Only the factory can execute it.
+/// The protocol fee accrued under the previous settings is minted to the previous collector
+/// first, and the accrual restarts from the current reserves.
+/// ## Params
+/// * **fee_config** is an [`Option`] field of type [`FeeConfig`] with the new fee settings.
+///
+/// * **collector** is an [`Option`] field of type [`Addr`] with the new protocol fee collector.
+pub fn update_fee_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    fee_config: Option<FeeConfig>,
+    collector: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.factory {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // settle the protocol fee with the previous settings
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let (protocol_fee_msg, _) =
+        mint_protocol_fee(deps.storage, &config, amp, &reserves, total_share)?;
+
+    let res = fee_info::update_fee_config(deps.storage, fee_config, collector)?;
+    update_reserves_last(deps.storage, &reserves)?;
+
+    Ok(res.add_messages(protocol_fee_msg))
+}
+
+/// ## Description
+/// Sets the stored reserves to the pair balances, accumulating prices against the previous reserves.
+/// Only the factory owner can execute it.
+pub fn sync(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    let factory_config = query_factory_config(&deps.querier, &config.factory)?;
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let reserves: [Uint128; 2] = RESERVES.load(deps.storage)?;
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    accumulate_prices(&env, &mut config, amp, reserves[0], reserves[1]);
+    CONFIG.save(deps.storage, &config)?;
+
+    let balances: [Asset; 2] = load_balances(&deps.querier, &env, &config)?;
+    RESERVES.save(deps.storage, &[balances[0].amount, balances[1].amount])?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "sync"),
+        ("reserves", &format!("{}, {}", balances[0], balances[1])),
+    ]))
+}
+
+/// ## Description
+/// Transfers the pair balances exceeding the stored reserves, such as direct donations, to `to`.
+/// ## Params
+/// * **to** is the object of type [`Addr`] receiving the excess balances.
+pub fn skim(deps: Deps, env: Env, to: Addr) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let reserves: [Uint128; 2] = RESERVES.load(deps.storage)?;
+
+    let balances: [Asset; 2] = load_balances(&deps.querier, &env, &config)?;
+    let excess_assets: Vec<Asset> = balances
+        .iter()
+        .zip(reserves.iter())
+        .map(|(balance, reserve)| Asset {
+            info: balance.info.clone(),
+            amount: balance
+                .amount
+                .checked_sub(*reserve)
+                .unwrap_or_else(|_| Uint128::zero()),
+        })
+        .collect();
+
+    let messages: Vec<CosmosMsg> = excess_assets
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| asset.transfer_msg_with_tax(&deps.querier, &to))
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "skim"),
+        ("to", to.as_str()),
+        (
+            "skimmed_assets",
+            &format!("{}, {}", excess_assets[0], excess_assets[1]),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Lends `ask_asset` to the caller and executes the flash swap callback on it. The callback
+/// carries the amount received net of the stability tax, while the pool accounts for the full
+/// lent amount. The pair is locked until the final self call checks the invariant D after fees
+/// is restored.
+/// ## Params
+/// * **ask_asset** is the object of type [`Asset`] lent to the caller.
+///
+/// * **msg** is the binary serialised message passed back to the caller.
+pub fn flash_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    ask_asset: Asset,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let ask_pool: &Asset = pools
+        .iter()
+        .find(|pool| pool.info == ask_asset.info)
+        .ok_or(ContractError::AssetMismatch {})?;
+
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if ask_asset.amount >= ask_pool.amount {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    FLASH_SWAP.save(deps.storage, &ask_asset)?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            ask_asset.transfer_msg_with_tax(&deps.querier, &info.sender)?,
+            FlashSwapCallbackMsg {
+                ask_asset: ask_asset.deduct_tax(&deps.querier)?,
+                msg,
+            }
+            .into_cosmos_msg(&info.sender)?,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::FinalizeFlashSwap {})?,
+                funds: vec![],
+            }),
+        ])
+        .add_attributes(vec![
+            ("action", "flash_swap"),
+            ("sender", info.sender.as_str()),
+            ("ask_asset", &ask_asset.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Checks the pair balances after the flash swap callback keep the invariant D at the current
+/// amp, with the total fee charged on the amounts paid in, and stores them as the new reserves.
+/// Only the pair itself can execute it.
+pub fn finalize_flash_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let ask_asset: Asset = FLASH_SWAP.load(deps.storage)?;
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let reserves: [Uint128; 2] = RESERVES.load(deps.storage)?;
+    let balances: [Asset; 2] = load_balances(&deps.querier, &env, &config)?;
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    let mut offer_assets: Vec<Asset> = vec![];
+    let mut commissions = [Uint128::zero(); 2];
+    let mut adjusted_balances = [Uint128::zero(); 2];
+    for (i, (balance, reserve)) in balances.iter().zip(reserves.iter()).enumerate() {
+        let lent_amount = if balance.info == ask_asset.info {
+            ask_asset.amount
+        } else {
+            Uint128::zero()
+        };
+
+        // anything above the reserve left after the loan has been paid in
+        let offer_amount = balance
+            .amount
+            .checked_sub(reserve.checked_sub(lent_amount)?)
+            .unwrap_or_else(|_| Uint128::zero());
+        let commission_amount = offer_amount * fee_info.fee_config.total_fee;
+        commissions[i] = commission_amount;
+
+        adjusted_balances[i] = balance.amount.checked_sub(commission_amount)?;
+        offer_assets.push(Asset {
+            info: balance.info.clone(),
+            amount: offer_amount,
+        });
+    }
+
+    // an emptied reserve has no invariant, the loan was not repaid
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    let d_after = compute_d(amp, adjusted_balances[0], adjusted_balances[1])
+        .map_err(|_| ContractError::FlashSwapInvariant {})?;
+    if d_after < compute_d(amp, reserves[0], reserves[1])? {
+        return Err(ContractError::FlashSwapInvariant {});
+    }
+
+    FLASH_SWAP.remove(deps.storage);
+
+    // accumulate prices against the reserves prior to the flash swap
+    accumulate_prices(&env, &mut config, amp, reserves[0], reserves[1]);
+    CONFIG.save(deps.storage, &config)?;
+
+    // the commission stays in the pool, the protocol share is minted on the next liquidity event
+    let new_reserves: [Uint128; 2] = [balances[0].amount, balances[1].amount];
+    RESERVES.save(deps.storage, &new_reserves)?;
+
+    let volumes = [offer_assets[0].amount, offer_assets[1].amount];
+    record_trade(deps.storage, &env, &fee_info, volumes, commissions)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "finalize_flash_swap"),
+        (
+            "offer_assets",
+            &format!("{}, {}", offer_assets[0], offer_assets[1]),
+        ),
+        (
+            "reserves",
+            &format!("{}, {}", new_reserves[0], new_reserves[1]),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Starts ramping the amplification coefficient linearly from its current value to `next_amp`.
+fn start_changing_amp(
+    env: &Env,
+    config: &mut Config,
+    next_amp: u64,
+    next_amp_time: u64,
+) -> Result<(), ContractError> {
+    if next_amp == 0 || next_amp > MAX_AMP {
+        return Err(ContractError::IncorrectAmp {});
+    }
+
+    let block_time = env.block.time.seconds();
+    let current_amp = compute_current_amp(config, block_time);
+
+    let next_amp_with_precision = next_amp * MAX_AMP_CHANGE;
+    if next_amp_with_precision < current_amp || next_amp > current_amp * MAX_AMP_CHANGE {
+        return Err(ContractError::MaxAmpChangeAssertion {});
+    }
+
+    if block_time < config.init_amp_time + MIN_AMP_CHANGING_TIME
+        || next_amp_time < block_time + MIN_AMP_CHANGING_TIME
+    {
+        return Err(ContractError::MinAmpChangingTimeAssertion {});
+    }
+
+    config.init_amp = current_amp;
+    config.init_amp_time = block_time;
+    config.next_amp = next_amp;
+    config.next_amp_time = next_amp_time;
+
+    Ok(())
+}
+
+/// ## Description
+/// Stops the current ramp, freezing the amplification coefficient at its current value.
+fn stop_changing_amp(env: &Env, config: &mut Config) {
+    let block_time = env.block.time.seconds();
+    let current_amp = compute_current_amp(config, block_time);
+
+    config.init_amp = current_amp;
+    config.init_amp_time = block_time;
+    config.next_amp = current_amp;
+    config.next_amp_time = block_time;
+}
+
+/// ## Description
+/// Returns the amplification coefficient at `block_time`, interpolating linearly while a ramp is active.
+pub fn compute_current_amp(config: &Config, block_time: u64) -> u64 {
+    if block_time >= config.next_amp_time {
+        return config.next_amp;
+    }
+
+    let elapsed_time = (block_time - config.init_amp_time) as u128;
+    let total_time = (config.next_amp_time - config.init_amp_time) as u128;
+    let init_amp = config.init_amp as u128;
+    let next_amp = config.next_amp as u128;
+
+    let current_amp = if next_amp > init_amp {
+        init_amp + (next_amp - init_amp) * elapsed_time / total_time
+    } else {
+        init_amp - (init_amp - next_amp) * elapsed_time / total_time
+    };
+
+    current_amp as u64
+}
+
+/// CONTRACT - should approve contract to use the amount of token
+pub fn provide_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    for asset in assets.iter() {
+        asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let deposits: [Uint128; 2] = [
+        assets
+            .iter()
+            .find(|a| a.info.eq(&pools[0].info))
+            .map(|a| a.amount)
+            .expect("Wrong asset info is given"),
+        assets
+            .iter()
+            .find(|a| a.info.eq(&pools[1].info))
+            .map(|a| a.amount)
+            .expect("Wrong asset info is given"),
+    ];
+
+    if deposits[0].is_zero() && deposits[1].is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (i, pool) in pools.iter().enumerate() {
+        // If the pool is token contract, then we need to execute TransferFrom msg to receive funds
+        if let AssetInfo::Cw20(contract_addr) = &pool.info {
+            if !deposits[i].is_zero() {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: info.sender.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: deposits[i],
+                    })?,
+                    funds: vec![],
+                }));
+            }
+        }
+    }
+
+    // accumulate prices against the reserves prior to the deposit
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    accumulate_prices(&env, &mut config, amp, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+
+    // mint the protocol fee share before the deposit dilutes it
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let (protocol_fee_msg, protocol_fee_share) = mint_protocol_fee(
+        deps.storage,
+        &config,
+        amp,
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+    messages.extend(protocol_fee_msg);
+    let total_share = total_share.checked_add(protocol_fee_share)?;
+
+    let share = if total_share.is_zero() {
+        // the initial deposit must be balanced enough to compute the invariant
+        if deposits[0].is_zero() || deposits[1].is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        // Initial share = invariant of the deposits
+        let share = Uint128::from(compute_d(amp, deposits[0], deposits[1])?);
+        if share <= Uint128::from(MINIMUM_LIQUIDITY_AMOUNT) {
+            return Err(ContractError::MinimumLiquidityAmountError {});
+        }
+
+        // lock the minimum liquidity in the pair forever
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.pair_info.liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: env.contract.address.to_string(),
+                amount: Uint128::from(MINIMUM_LIQUIDITY_AMOUNT),
+            })?,
+            funds: vec![],
+        }));
+
+        share.checked_sub(Uint128::from(MINIMUM_LIQUIDITY_AMOUNT))?
+    } else {
+        let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+        let share = compute_provide_share(
+            amp,
+            [pools[0].amount, pools[1].amount],
+            deposits,
+            total_share,
+            fee_info.fee_config.total_fee,
+        )?;
+
+        assert_slippage_tolerance(
+            &slippage_tolerance,
+            &deposits,
+            &pools,
+            share,
+            total_share,
+            amp,
+        )?;
+
+        share
+    };
+
+    // prevent providing free token
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let reserves: [Uint128; 2] = [
+        pools[0].amount.checked_add(deposits[0])?,
+        pools[1].amount.checked_add(deposits[1])?,
+    ];
+    RESERVES.save(deps.storage, &reserves)?;
+    update_reserves_last(deps.storage, &reserves)?;
+
+    // mint LP token to sender
+    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: receiver.to_string(),
+            amount: share,
+        })?,
+        funds: vec![],
+    }));
+
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::ProvideLiquidity {
+            sender: info.sender.clone(),
+            receiver: deps.api.addr_validate(&receiver)?,
+            assets: reserve_assets(&config.pair_info.asset_infos, &deposits),
+            share,
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "provide_liquidity"),
+            ("sender", info.sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("assets", &format!("{}, {}", assets[0], assets[1])),
+            ("share", &share.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Provides a single asset as an imbalanced deposit. Unlike the constant product pair, no part
+/// of the asset is swapped: the stableswap share computation charges the imbalance fee on the
+/// part of the deposit that moves the pool away from its current balance.
+/// ## Params
+/// * **asset** is the object of type [`Asset`] to provide.
+///
+/// * **max_spread** is the optional maximum loss of the share against its value at the peg.
+///
+/// * **min_share** is the optional minimum amount of LP tokens to receive.
+///
+/// * **receiver** is the optional receiver of the LP tokens, the sender if empty.
+pub fn provide_single_asset(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    max_spread: Option<Decimal>,
+    min_share: Option<Uint128>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    asset.assert_sent_native_token_balance(&info)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_index: usize = if asset.info.eq(&pools[0].info) {
+        0
+    } else if asset.info.eq(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    if total_share.is_zero() || pools[0].amount.is_zero() || pools[1].amount.is_zero() {
+        return Err(ContractError::EmptyPool {});
+    }
+
+    // accumulate prices against the reserves prior to the deposit
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    accumulate_prices(&env, &mut config, amp, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+
+    // mint the protocol fee share before the deposit dilutes it
+    let (protocol_fee_msg, protocol_fee_share) = mint_protocol_fee(
+        deps.storage,
+        &config,
+        amp,
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+    let total_share = total_share.checked_add(protocol_fee_share)?;
+
+    let mut deposits = [Uint128::zero(); 2];
+    deposits[offer_index] = asset.amount;
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+    let share = compute_provide_share(
+        amp,
+        [pools[0].amount, pools[1].amount],
+        deposits,
+        total_share,
+        fee_info.fee_config.total_fee,
+    )?;
+
+    // prevent providing free token
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let expected_share: Uint128 =
+        compute_expected_share(amp, &deposits, &pools, total_share)?.into();
+    assert_max_spread(
+        None,
+        max_spread,
+        expected_share,
+        share,
+        expected_share.saturating_sub(share),
+    )?;
+
+    if let Some(min_share) = min_share {
+        if share < min_share {
+            return Err(ContractError::MaxSlippageAssertion {});
+        }
+    }
+
+    let reserves: [Uint128; 2] = [
+        pools[0].amount.checked_add(deposits[0])?,
+        pools[1].amount.checked_add(deposits[1])?,
+    ];
+    RESERVES.save(deps.storage, &reserves)?;
+    // the imbalance fee of the deposit accrues to the protocol on the next liquidity event
+    update_reserves_last(
+        deps.storage,
+        &compute_deposit_reserves(&[pools[0].amount, pools[1].amount], share, total_share),
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let AssetInfo::Cw20(contract_addr) = &asset.info {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: asset.amount,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    messages.extend(protocol_fee_msg);
+
+    // mint LP token to receiver
+    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: receiver.to_string(),
+            amount: share,
+        })?,
+        funds: vec![],
+    }));
+
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::ProvideLiquidity {
+            sender: info.sender.clone(),
+            receiver: deps.api.addr_validate(&receiver)?,
+            assets: reserve_assets(&config.pair_info.asset_infos, &deposits),
+            share,
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "provide_single_asset"),
+            ("sender", info.sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("asset", &asset.to_string()),
+            ("share", &share.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for `deposits`. Deposits that move the pool away from
+/// its current balance are charged a fee on the imbalanced part, the fee stays in the pool.
+fn compute_provide_share(
+    amp: u64,
+    pools: [Uint128; 2],
+    deposits: [Uint128; 2],
+    total_share: Uint128,
+    total_fee: Decimal,
+) -> Result<Uint128, ContractError> {
+    let d_before = compute_d(amp, pools[0], pools[1])?;
+    let new_pools = [
+        pools[0].checked_add(deposits[0])?,
+        pools[1].checked_add(deposits[1])?,
+    ];
+    let d_after = compute_d(amp, new_pools[0], new_pools[1])?;
+    if d_after <= d_before {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // fee = total_fee * n / (4 * (n - 1))
+    let n_coins = N_COINS as u64;
+    let fee_rate = Decimal256::from(total_fee) * Decimal256::from_ratio(n_coins, 4 * (n_coins - 1));
+
+    let mut adjusted_pools = [Uint128::zero(); 2];
+    for i in 0..2 {
+        let ideal_balance: Uint128 = d_after
+            .multiply_ratio(Uint256::from(pools[i]), d_before)
+            .into();
+        let difference = if ideal_balance > new_pools[i] {
+            ideal_balance - new_pools[i]
+        } else {
+            new_pools[i] - ideal_balance
+        };
+        let fee: Uint128 = (Uint256::from(difference) * fee_rate).into();
+        adjusted_pools[i] = new_pools[i].checked_sub(fee)?;
+    }
+
+    let d_adjusted = compute_d(amp, adjusted_pools[0], adjusted_pools[1])?;
+    if d_adjusted <= d_before {
+        return Ok(Uint128::zero());
+    }
+
+    Ok(Uint256::from(total_share)
+        .multiply_ratio(d_adjusted - d_before, d_before)
+        .into())
+}
+
+pub fn withdraw_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    min_assets: Option<[Asset; 2]>,
+    receiver: Addr,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage).unwrap();
+
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    // accumulate prices against the reserves prior to the withdrawal
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    accumulate_prices(&env, &mut config, amp, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+    // mint the protocol fee share before the withdrawal
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let (protocol_fee_msg, protocol_fee_share) = mint_protocol_fee(
+        deps.storage,
+        &config,
+        amp,
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+    let total_share = total_share.checked_add(protocol_fee_share)?;
+
+    let refund_amounts =
+        compute_refund_amounts(&[pools[0].amount, pools[1].amount], amount, total_share);
+    let refund_assets: Vec<Asset> = pools
+        .iter()
+        .zip(refund_amounts.iter())
+        .map(|(a, refund_amount)| Asset {
+            info: a.info.clone(),
+            amount: *refund_amount,
+        })
+        .collect();
+
+    assert_min_assets(&refund_assets, &min_assets)?;
+
+    // update pool info
+    let reserves: [Uint128; 2] = [
+        pools[0].amount.checked_sub(refund_assets[0].amount)?,
+        pools[1].amount.checked_sub(refund_assets[1].amount)?,
+    ];
+    RESERVES.save(deps.storage, &reserves)?;
+    update_reserves_last(deps.storage, &reserves)?;
+
+    Ok(Response::new()
+        .add_messages(protocol_fee_msg)
+        .add_messages(vec![
+            refund_assets[0].transfer_msg_with_tax(&deps.querier, &receiver)?,
+            refund_assets[1].transfer_msg_with_tax(&deps.querier, &receiver)?,
+            // burn liquidity token
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: config.pair_info.liquidity_token.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+                funds: vec![],
+            }),
+        ])
+        .add_submessages(hook_messages(
+            deps.storage,
+            PairHookMsg::WithdrawLiquidity {
+                sender: sender.clone(),
+                receiver: receiver.clone(),
+                share: amount,
+                refund_assets: reserve_assets(
+                    &config.pair_info.asset_infos,
+                    &[refund_assets[0].amount, refund_assets[1].amount],
+                ),
+                reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+            },
+        )?)
+        .add_attributes(vec![
+            ("action", "withdraw_liquidity"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("withdrawn_share", &amount.to_string()),
+            (
+                "refund_assets",
+                &format!("{}, {}", refund_assets[0], refund_assets[1]),
+            ),
+        ]))
+}
+
+/// ## Description
+/// Withdraws liquidity in a single asset: the share is withdrawn pro rata, then the other leg
+/// is swapped on the curve against the reserves after the withdrawal.
+/// ## Params
+/// * **sender** is the object of type [`Addr`] that sent the LP tokens.
+///
+/// * **amount** is the amount of LP tokens burnt.
+///
+/// * **ask_asset** is the object of type [`AssetInfo`] to receive.
+///
+/// * **min_receive** is the optional minimum amount of the ask asset to receive.
+///
+/// * **receiver** is the object of type [`Addr`] receiving the ask asset.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_single_asset(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    ask_asset: AssetInfo,
+    min_receive: Option<Uint128>,
+    receiver: Addr,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let ask_index: usize = if ask_asset.eq(&pools[0].info) {
+        0
+    } else if ask_asset.eq(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let offer_index: usize = 1 - ask_index;
+
+    // accumulate prices against the reserves prior to the withdrawal
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    accumulate_prices(&env, &mut config, amp, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+    // mint the protocol fee share before the withdrawal
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let (protocol_fee_msg, protocol_fee_share) = mint_protocol_fee(
+        deps.storage,
+        &config,
+        amp,
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+    let total_share = total_share.checked_add(protocol_fee_share)?;
+
+    let refund_amounts: [Uint128; 2] =
+        compute_refund_amounts(&[pools[0].amount, pools[1].amount], amount, total_share);
+    let withdrawn_reserves: [Uint128; 2] = [
+        pools[0].amount.checked_sub(refund_amounts[0])?,
+        pools[1].amount.checked_sub(refund_amounts[1])?,
+    ];
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    // swap the other leg against the reserves after the withdrawal
+    let (return_amount, _, commission_amount) = compute_swap(
+        withdrawn_reserves[offer_index],
+        withdrawn_reserves[ask_index],
+        refund_amounts[offer_index],
+        amp,
+        fee_info.fee_config.total_fee,
+    )?;
+
+    let refund_asset = Asset {
+        info: ask_asset,
+        amount: refund_amounts[ask_index].checked_add(return_amount)?,
+    };
+    // the stability tax of a native ask asset is deducted from the sent amount, so the minimum
+    // applies to the amount received
+    let tax_amount: Uint128 = refund_asset.compute_tax(&deps.querier)?;
+    if let Some(min_receive) = min_receive {
+        if refund_asset.amount.checked_sub(tax_amount)? < min_receive {
+            return Err(ContractError::MinWithdrawAssertion {});
+        }
+    }
+
+    // the commission of the swap accrues to the protocol on the next liquidity event
+    update_reserves_last(deps.storage, &withdrawn_reserves)?;
+
+    let mut volumes = [Uint128::zero(); 2];
+    volumes[offer_index] = refund_amounts[offer_index];
+    let mut commissions = [Uint128::zero(); 2];
+    commissions[ask_index] = commission_amount;
+    record_trade(deps.storage, &env, &fee_info, volumes, commissions)?;
+
+    // the swapped leg goes back into the pool, so only the ask reserve decreases
+    let mut reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    reserves[ask_index] = reserves[ask_index].checked_sub(refund_asset.amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    messages.extend(protocol_fee_msg);
+    if !refund_asset.amount.is_zero() {
+        messages.push(refund_asset.transfer_msg_with_tax(&deps.querier, &receiver)?);
+    }
+
+    // burn liquidity token
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    }));
+
+    let mut refunds = [Uint128::zero(); 2];
+    refunds[ask_index] = refund_asset.amount;
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::WithdrawLiquidity {
+            sender: sender.clone(),
+            receiver: receiver.clone(),
+            share: amount,
+            refund_assets: reserve_assets(&config.pair_info.asset_infos, &refunds),
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "withdraw_single_asset"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("withdrawn_share", &amount.to_string()),
+            ("swap_amount", &refund_amounts[offer_index].to_string()),
+            ("return_amount", &return_amount.to_string()),
+            ("refund_asset", &refund_asset.to_string()),
+        ]))
+}
+
+// CONTRACT - a user must do token approval
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_index: usize = if offer_asset.info.eq(&pools[0].info) {
+        0
+    } else if offer_asset.info.eq(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let offer_pool: Asset = pools[offer_index].clone();
+    let ask_pool: Asset = pools[1 - offer_index].clone();
+
+    // accumulate prices against the reserves prior to the swap
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    accumulate_prices(&env, &mut config, amp, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    let offer_amount = offer_asset.amount;
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_amount,
+        amp,
+        fee_info.fee_config.total_fee,
+    )?;
+
+    // check max spread limit if exist
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+
+    let return_asset = Asset {
+        info: ask_pool.info.clone(),
+        amount: return_amount,
+    };
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    // the stability tax of a native return asset is deducted from the sent amount
+    let tax_amount: Uint128 = return_asset.compute_tax(&deps.querier)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !return_amount.is_zero() {
+        messages.push(return_asset.transfer_msg_with_tax(&deps.querier, &receiver)?);
+    }
+
+    // the commission stays in the pool, the protocol share is minted on the next liquidity event
+    let mut reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = reserves[offer_index].checked_add(offer_amount)?;
+    reserves[1 - offer_index] = reserves[1 - offer_index].checked_sub(return_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let mut volumes = [Uint128::zero(); 2];
+    volumes[offer_index] = offer_amount;
+    let mut commissions = [Uint128::zero(); 2];
+    commissions[1 - offer_index] = commission_amount;
+    record_trade(deps.storage, &env, &fee_info, volumes, commissions)?;
+
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::Swap {
+            sender: sender.clone(),
+            receiver: receiver.clone(),
+            offer_asset: offer_asset.clone(),
+            return_asset: Asset {
+                info: return_asset.info,
+                amount: return_amount.checked_sub(tax_amount)?,
+            },
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    // send collateral token from the contract to a user
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "swap"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("offer_asset", &offer_asset.info.to_string()),
+            ("ask_asset", &ask_pool.info.to_string()),
+            ("offer_amount", &offer_amount.to_string()),
+            ("return_amount", &return_amount.to_string()),
+            ("spread_amount", &spread_amount.to_string()),
+            ("commission_amount", &commission_amount.to_string()),
+            ("tax_amount", &tax_amount.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Swaps the offer asset for exactly `ask_amount` of the other asset. The offer is computed
+/// on the curve against the reserves, rounded in favor of the pool, and the unused offer asset
+/// is refunded to the sender. The pool pays the stability tax of a native ask asset on top of
+/// `ask_amount`, so the receiver gets the full amount.
+/// ## Params
+/// * **offer_asset** is the object of type [`Asset`] sent to pay for the swap.
+///
+/// * **ask_amount** is the amount of the other asset to receive.
+///
+/// * **max_offer** is the optional maximum amount of the offer asset to spend.
+///
+/// * **to** is the optional receiver of the ask asset, the sender if empty.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_exact_output(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_amount: Uint128,
+    max_offer: Option<Uint128>,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    if ask_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    assert_pair_active(
+        deps.storage,
+        &deps.querier,
+        &config.factory,
+        &config.pair_info.asset_infos,
+    )?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_index: usize = if offer_asset.info.eq(&pools[0].info) {
+        0
+    } else if offer_asset.info.eq(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let offer_pool: Asset = pools[offer_index].clone();
+    let ask_pool: Asset = pools[1 - offer_index].clone();
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    let tax_amount: Uint128 = Asset {
+        info: ask_pool.info.clone(),
+        amount: ask_amount,
+    }
+    .compute_reverse_tax(&deps.querier)?;
+    let return_amount: Uint128 = ask_amount.checked_add(tax_amount)?;
+
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        offer_pool.amount,
+        ask_pool.amount,
+        return_amount,
+        amp,
+        fee_info.fee_config.total_fee,
+    )?;
+
+    let max_offer: Uint128 = max_offer.map_or(offer_asset.amount, |max_offer| {
+        std::cmp::min(max_offer, offer_asset.amount)
+    });
+    if offer_amount > max_offer {
+        return Err(ContractError::MaxOfferAssertion {});
+    }
+
+    // accumulate prices against the reserves prior to the swap
+    accumulate_prices(&env, &mut config, amp, pools[0].amount, pools[1].amount);
+    CONFIG.save(deps.storage, &config)?;
+
+    // the commission stays in the pool, the protocol share is minted on the next liquidity event
+    let mut reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = reserves[offer_index].checked_add(offer_amount)?;
+    reserves[1 - offer_index] = reserves[1 - offer_index].checked_sub(return_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let mut volumes = [Uint128::zero(); 2];
+    volumes[offer_index] = offer_amount;
+    let mut commissions = [Uint128::zero(); 2];
+    commissions[1 - offer_index] = commission_amount;
+    record_trade(deps.storage, &env, &fee_info, volumes, commissions)?;
+
+    let return_asset = Asset {
+        info: ask_pool.info.clone(),
+        amount: return_amount,
+    };
+    let refund_asset = Asset {
+        info: offer_asset.info.clone(),
+        amount: offer_asset.amount.checked_sub(offer_amount)?,
+    };
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    let mut messages: Vec<CosmosMsg> =
+        vec![return_asset.transfer_msg_with_tax(&deps.querier, &receiver)?];
+    if !refund_asset.amount.is_zero() {
+        messages.push(refund_asset.transfer_msg_with_tax(&deps.querier, &sender)?);
+    }
+
+    let hook_msgs = hook_messages(
+        deps.storage,
+        PairHookMsg::Swap {
+            sender: sender.clone(),
+            receiver: receiver.clone(),
+            offer_asset: Asset {
+                info: offer_asset.info.clone(),
+                amount: offer_amount,
+            },
+            return_asset: Asset {
+                info: return_asset.info,
+                amount: ask_amount,
+            },
+            reserves: reserve_assets(&config.pair_info.asset_infos, &reserves),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "swap_exact_output"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("offer_asset", &offer_asset.info.to_string()),
+            ("ask_asset", &ask_pool.info.to_string()),
+            ("offer_amount", &offer_amount.to_string()),
+            ("return_amount", &ask_amount.to_string()),
+            ("refund_amount", &refund_asset.amount.to_string()),
+            ("spread_amount", &spread_amount.to_string()),
+            ("commission_amount", &commission_amount.to_string()),
+            ("tax_amount", &tax_amount.to_string()),
+        ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps, env)?)?),
+        QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps)?)?),
+        QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
+        QueryMsg::Simulation { offer_asset } => {
+            Ok(to_binary(&query_simulation(deps, env, offer_asset)?)?)
+        }
+        QueryMsg::ReverseSimulation { ask_asset } => {
+            Ok(to_binary(&query_reverse_simulation(deps, env, ask_asset)?)?)
+        }
+        QueryMsg::CumulativePrices {} => Ok(to_binary(&query_cumulative_prices(deps, env)?)?),
+        QueryMsg::PendingProtocolFee {} => Ok(to_binary(&query_pending_protocol_fee(deps, env)?)?),
+        QueryMsg::SimulateProvide { assets } => {
+            Ok(to_binary(&query_simulate_provide(deps, env, assets)?)?)
+        }
+        QueryMsg::SimulateWithdraw { lp_amount } => {
+            Ok(to_binary(&query_simulate_withdraw(deps, env, lp_amount)?)?)
+        }
+        QueryMsg::SpotPrice { base_asset } => {
+            Ok(to_binary(&query_spot_price(deps, env, base_asset)?)?)
+        }
+        QueryMsg::PriceImpact { offer_asset } => {
+            Ok(to_binary(&query_price_impact(deps, env, offer_asset)?)?)
+        }
+        QueryMsg::Stats {} => Ok(to_binary(&query_stats(deps)?)?),
+        QueryMsg::Hooks {} => Ok(to_binary(&query_hooks(deps)?)?),
+        // limit orders are only supported by the constant product pair
+        QueryMsg::Order { .. }
+        | QueryMsg::OrdersByOwner { .. }
+        | QueryMsg::OrdersByPrice { .. } => Err(ContractError::NonSupported {}),
+    }
+}
+
+pub fn query_config(deps: Deps, env: Env) -> Result<ConfigResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+
+    Ok(ConfigResponse {
+        pair_info: config.pair_info,
+        factory: config.factory,
+        fee_config: fee_info.fee_config,
+        collector: fee_info.collector,
+        params: Some(to_binary(&StablePoolConfig {
+            amp: Decimal::from_ratio(amp, 1u64),
+        })?),
+    })
+}
+
+pub fn query_pair_info(deps: Deps) -> Result<PairInfo, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    Ok(config.pair_info)
+}
+
+pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let assets: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+
+    let resp = PoolResponse {
+        assets,
+        total_share,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_simulation(
+    deps: Deps,
+    env: Env,
+    offer_asset: Asset,
+) -> Result<SimulationResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_pool: Asset;
+    let ask_pool: Asset;
+    if offer_asset.info.eq(&pools[0].info) {
+        offer_pool = pools[0].clone();
+        ask_pool = pools[1].clone();
+    } else if offer_asset.info.eq(&pools[1].info) {
+        offer_pool = pools[1].clone();
+        ask_pool = pools[0].clone();
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        compute_current_amp(&config, env.block.time.seconds()),
+        fee_info.fee_config.total_fee,
+    )?;
+
+    let tax_amount: Uint128 = Asset {
+        info: ask_pool.info,
+        amount: return_amount,
+    }
+    .compute_tax(&deps.querier)?;
+
+    Ok(SimulationResponse {
+        return_amount: return_amount.checked_sub(tax_amount)?,
+        spread_amount,
+        commission_amount,
+        tax_amount,
+    })
+}
+
+pub fn query_reverse_simulation(
+    deps: Deps,
+    env: Env,
+    ask_asset: Asset,
+) -> Result<ReverseSimulationResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_pool: Asset;
+    let ask_pool: Asset;
+    if ask_asset.info.eq(&pools[0].info) {
+        ask_pool = pools[0].clone();
+        offer_pool = pools[1].clone();
+    } else if ask_asset.info.eq(&pools[1].info) {
+        ask_pool = pools[1].clone();
+        offer_pool = pools[0].clone();
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+
+    // the pool pays the stability tax on top of the asked amount
+    let tax_amount: Uint128 = ask_asset.compute_reverse_tax(&deps.querier)?;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        offer_pool.amount,
+        ask_pool.amount,
+        ask_asset.amount.checked_add(tax_amount)?,
+        compute_current_amp(&config, env.block.time.seconds()),
+        fee_info.fee_config.total_fee,
+    )?;
+
+    Ok(ReverseSimulationResponse {
+        offer_amount,
+        spread_amount,
+        commission_amount,
+        tax_amount,
+    })
+}
+
+/// ## Description
+/// Returns the LP tokens minted for the deposit and the slippage of the share against the share
+/// the deposit is worth at the peg in a [`SimulateProvideResponse`] object. The whole deposit is
+/// kept by the pool, so there are no excess assets. The protocol fee share minted before the
+/// deposit is accounted for.
+/// ## Params
+/// * **assets** are the objects of type [`Asset`] deposited.
+pub fn query_simulate_provide(
+    deps: Deps,
+    env: Env,
+    assets: [Asset; 2],
+) -> Result<SimulateProvideResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let mut deposits = [Uint128::zero(); 2];
+    for (i, pool) in pools.iter().enumerate() {
+        deposits[i] = assets
+            .iter()
+            .find(|a| a.info == pool.info)
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})?;
+    }
+
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    let reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let total_share = total_share.checked_add(load_protocol_fee_share(
+        deps.storage,
+        amp,
+        &reserves,
+        total_share,
+    )?)?;
+
+    let (share, slippage) = if total_share.is_zero() {
+        // the initial deposit must be balanced enough to compute the invariant
+        if deposits[0].is_zero() || deposits[1].is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let share = Uint128::from(compute_d(amp, deposits[0], deposits[1])?);
+        if share <= Uint128::from(MINIMUM_LIQUIDITY_AMOUNT) {
+            return Err(ContractError::MinimumLiquidityAmountError {});
+        }
+
+        (
+            share.checked_sub(Uint128::from(MINIMUM_LIQUIDITY_AMOUNT))?,
+            Decimal::zero(),
+        )
+    } else {
+        let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+        let share = compute_provide_share(
+            amp,
+            reserves,
+            deposits,
+            total_share,
+            fee_info.fee_config.total_fee,
+        )?;
+
+        // the deposit passes the slippage tolerance check down to 1 - share / expected_share
+        let expected_share = compute_expected_share(amp, &deposits, &pools, total_share)?;
+        let slippage = if expected_share > Uint256::from(share) {
+            Decimal256::from_ratio(expected_share - Uint256::from(share), expected_share)
+        } else {
+            Decimal256::zero()
+        };
+        (share, slippage.into())
+    };
+
+    Ok(SimulateProvideResponse {
+        share,
+        excess_assets: reserve_assets(&config.pair_info.asset_infos, &[Uint128::zero(); 2]),
+        slippage,
+    })
+}
+
+/// ## Description
+/// Returns the assets refunded for burning `lp_amount` LP tokens in a
+/// [`SimulateWithdrawResponse`] object. The protocol fee share minted before the withdrawal is
+/// accounted for.
+/// ## Params
+/// * **lp_amount** is the amount of LP tokens burnt.
+pub fn query_simulate_withdraw(
+    deps: Deps,
+    env: Env,
+    lp_amount: Uint128,
+) -> Result<SimulateWithdrawResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let reserves: [Uint128; 2] = [pools[0].amount, pools[1].amount];
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let total_share = total_share.checked_add(load_protocol_fee_share(
+        deps.storage,
+        compute_current_amp(&config, env.block.time.seconds()),
+        &reserves,
+        total_share,
+    )?)?;
+    if lp_amount > total_share {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    Ok(SimulateWithdrawResponse {
+        refund_assets: reserve_assets(
+            &config.pair_info.asset_infos,
+            &compute_refund_amounts(&reserves, lp_amount, total_share),
+        ),
+    })
+}
+
+/// ## Description
+/// Returns the mid-price of the base asset in the other asset in a [`SpotPriceResponse`]
+/// object. The price is the fee-less return of swapping one scaled unit of the base asset on
+/// the curve, as accumulated by the price oracle.
+/// ## Params
+/// * **base_asset** is the object of type [`AssetInfo`] priced in the other asset.
+pub fn query_spot_price(
+    deps: Deps,
+    env: Env,
+    base_asset: AssetInfo,
+) -> Result<SpotPriceResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let base_index: usize = pools
+        .iter()
+        .position(|pool| pool.info == base_asset)
+        .ok_or(ContractError::AssetMismatch {})?;
+
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    Ok(SpotPriceResponse {
+        price: mid_price(amp, pools[base_index].amount, pools[1 - base_index].amount)?,
+    })
+}
+
+/// ## Description
+/// Returns the execution price of the swap computed on the curve with the pair fee settings,
+/// the mid-price and the price impact `1 - execution_price / mid_price` in a
+/// [`PriceImpactResponse`] object. The impact includes the commission.
+/// ## Params
+/// * **offer_asset** is the object of type [`Asset`] offered to the pool.
+pub fn query_price_impact(
+    deps: Deps,
+    env: Env,
+    offer_asset: Asset,
+) -> Result<PriceImpactResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+
+    let offer_index: usize = pools
+        .iter()
+        .position(|pool| pool.info == offer_asset.info)
+        .ok_or(ContractError::AssetMismatch {})?;
+    let offer_pool: &Asset = &pools[offer_index];
+    let ask_pool: &Asset = &pools[1 - offer_index];
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    let mid_price: Decimal = mid_price(amp, offer_pool.amount, ask_pool.amount)?;
+
+    let fee_info: FeeInfo = FEE_INFO.load(deps.storage)?;
+    let (return_amount, _, _) = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        amp,
+        fee_info.fee_config.total_fee,
+    )?;
+    let execution_price = Decimal::from_ratio(return_amount, offer_asset.amount);
+
+    // the curve is convex, the execution price only exceeds the mid-price by rounding
+    let mid_price_256: Decimal256 = mid_price.into();
+    let execution_price_256: Decimal256 = execution_price.into();
+    let price_impact: Decimal256 = if mid_price_256 > execution_price_256 {
+        Decimal256::one() - execution_price_256 / mid_price_256
+    } else {
+        Decimal256::zero()
+    };
+
+    Ok(PriceImpactResponse {
+        execution_price,
+        mid_price,
+        price_impact: price_impact.into(),
+    })
+}
+
+/// ## Description
+/// Returns the price accumulators of the pool as of the current block in a
+/// [`CumulativePricesResponse`] object.
+pub fn query_cumulative_prices(
+    deps: Deps,
+    env: Env,
+) -> Result<CumulativePricesResponse, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    let assets: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+
+    let amp = compute_current_amp(&config, env.block.time.seconds());
+    accumulate_prices(&env, &mut config, amp, assets[0].amount, assets[1].amount);
+
+    Ok(CumulativePricesResponse {
+        assets,
+        total_share,
+        price0_cumulative_last: config.price0_cumulative_last,
+        price1_cumulative_last: config.price1_cumulative_last,
+        block_time_last: config.block_time_last,
+    })
+}
+
+/// ## Description
+/// Returns the hook contracts registered on the pair in a [`HooksResponse`] object.
+pub fn query_hooks(deps: Deps) -> Result<HooksResponse, ContractError> {
+    Ok(HooksResponse {
+        hooks: HOOKS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+/// ## Description
+/// Returns the cumulative trading statistics of the pair in a [`StatsResponse`] object.
+pub fn query_stats(deps: Deps) -> Result<StatsResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let stats: Stats = STATS.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(StatsResponse {
+        volumes: reserve_assets(&config.pair_info.asset_infos, &stats.volumes),
+        lp_commissions: reserve_assets(&config.pair_info.asset_infos, &stats.lp_commissions),
+        protocol_commissions: reserve_assets(
+            &config.pair_info.asset_infos,
+            &stats.protocol_commissions,
+        ),
+        swap_count: stats.swap_count,
+        last_trade_time: stats.last_trade_time,
+    })
+}
+
+/// ## Description
+/// Returns the LP tokens minted to the collector on the next liquidity event along with the
+/// pool assets backing them.
+pub fn query_pending_protocol_fee(
+    deps: Deps,
+    env: Env,
+) -> Result<PendingProtocolFeeResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let pools: [Asset; 2] = load_pools(deps.storage, &config)?;
+    let total_share: Uint128 = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let share = load_protocol_fee_share(
+        deps.storage,
+        compute_current_amp(&config, env.block.time.seconds()),
+        &[pools[0].amount, pools[1].amount],
+        total_share,
+    )?;
+
+    let total_share = total_share.checked_add(share)?;
+    let assets: Vec<Asset> = pools
+        .iter()
+        .map(|pool| Asset {
+            info: pool.info.clone(),
+            amount: if share.is_zero() {
+                Uint128::zero()
+            } else {
+                pool.amount.multiply_ratio(share, total_share)
+            },
+        })
+        .collect();
+
+    Ok(PendingProtocolFeeResponse {
+        share,
+        assets: [assets[0].clone(), assets[1].clone()],
+    })
+}
+
+/// ## Description
+/// Returns the pair balances of the pool assets.
+fn load_balances(querier: &QuerierWrapper, env: &Env, config: &Config) -> StdResult<[Asset; 2]> {
+    config.pair_info.query_pools(querier, &env.contract.address)
+}
+
+/// ## Description
+/// Returns the stored reserves of the pool in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **config** is the object of type [`Config`].
+pub fn load_pools(storage: &dyn Storage, config: &Config) -> StdResult<[Asset; 2]> {
+    let reserves: [Uint128; 2] = RESERVES.load(storage)?;
+
+    Ok([
+        Asset {
+            info: config.pair_info.asset_infos[0].clone(),
+            amount: reserves[0],
+        },
+        Asset {
+            info: config.pair_info.asset_infos[1].clone(),
+            amount: reserves[1],
+        },
+    ])
+}
+
+/// ## Description
+/// Accumulates the pool prices weighted by the seconds elapsed since the last accumulation.
+/// The price of an asset is the fee-less return of swapping one scaled unit of it on the curve.
+/// Must be called with the reserves as they were before the current state change.
+/// ## Params
+/// * **env** is the object of type [`Env`].
+///
+/// * **config** is the object of type [`Config`] holding the accumulators.
+///
+/// * **amp** is the current amplification coefficient.
+///
+/// * **x** is the reserve of the first asset.
+///
+/// * **y** is the reserve of the second asset.
+pub fn accumulate_prices(env: &Env, config: &mut Config, amp: u64, x: Uint128, y: Uint128) {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return;
+    }
+
+    // we have to shift the block time when any of the reserves is zero
+    // or when the pool is too shallow to price one unit
+    if let (Ok(price0), Ok(price1)) = (spot_price(amp, x, y), spot_price(amp, y, x)) {
+        let time_elapsed = (block_time - config.block_time_last) as u128;
+
+        config.price0_cumulative_last = config
+            .price0_cumulative_last
+            .wrapping_add(Uint128::from(time_elapsed * price0.u128()));
+        config.price1_cumulative_last = config
+            .price1_cumulative_last
+            .wrapping_add(Uint128::from(time_elapsed * price1.u128()));
+    }
+
+    config.block_time_last = block_time;
+}
+
+/// Returns the amount of ask asset received for one unit of offer asset as a [`Decimal`].
+fn mid_price(amp: u64, offer_pool: Uint128, ask_pool: Uint128) -> Result<Decimal, ContractError> {
+    let price = spot_price(amp, offer_pool, ask_pool)
+        .map_err(|_| ContractError::InsufficientLiquidity {})?;
+
+    Ok(Decimal::from_ratio(
+        price,
+        10u128.pow(TWAP_PRECISION.into()),
+    ))
+}
+
+/// Returns the amount of ask asset received for one unit of offer asset, scaled by [`TWAP_PRECISION`].
+fn spot_price(amp: u64, offer_pool: Uint128, ask_pool: Uint128) -> StdResult<Uint128> {
+    let one_unit = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
+    if offer_pool.is_zero() || ask_pool.is_zero() {
+        return Err(StdError::generic_err("Cannot price an empty reserve"));
+    }
+
+    let (return_amount, _, _) = compute_swap(offer_pool, ask_pool, one_unit, amp, Decimal::zero())?;
+    Ok(return_amount)
+}
+
+/// ## Description
+/// Returns the amount of LP tokens that gives the protocol the share `p` of the growth of the
+/// invariant D since the last liquidity event, as in Uniswap v2 with D in place of the reserves
+/// product root: `total_share * (d - d_last) * p / ((1 - p) * d + p * d_last)`.
+/// Both invariants are computed at the current amp, so an amp ramp is not charged as a fee.
+/// ## Params
+/// * **amp** is the current amplification coefficient.
+///
+/// * **reserves** are the current reserves of the pool.
+///
+/// * **reserves_last** are the reserves after the last liquidity event.
+///
+/// * **total_share** is the total supply of the LP token.
+///
+/// * **protocol_fee** is the protocol share `p` of the commission.
+pub fn compute_protocol_fee_share(
+    amp: u64,
+    reserves: &[Uint128; 2],
+    reserves_last: &[Uint128; 2],
+    total_share: Uint128,
+    protocol_fee: Decimal,
+) -> StdResult<Uint128> {
+    if reserves_last[0].is_zero() || reserves_last[1].is_zero() || protocol_fee.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let d: Uint256 = compute_d(amp, reserves[0], reserves[1])?;
+    let d_last: Uint256 = compute_d(amp, reserves_last[0], reserves_last[1])?;
+    if d <= d_last {
+        return Ok(Uint128::zero());
+    }
+
+    let protocol_fee: Decimal256 = protocol_fee.into();
+    let numerator: Uint256 = Uint256::from(total_share) * (d - d_last) * protocol_fee;
+    let denominator: Uint256 = d * (Decimal256::one() - protocol_fee) + d_last * protocol_fee;
+
+    Ok(numerator.multiply_ratio(Uint256::one(), denominator).into())
+}
+
+/// ## Description
+/// Returns the amount of LP tokens owed to the collector for the current reserves.
+fn load_protocol_fee_share(
+    storage: &dyn Storage,
+    amp: u64,
+    reserves: &[Uint128; 2],
+    total_share: Uint128,
+) -> StdResult<Uint128> {
+    let fee_info: FeeInfo = FEE_INFO.load(storage)?;
+    let reserves_last: [Uint128; 2] = RESERVES_LAST.may_load(storage)?.unwrap_or_default();
+
+    compute_protocol_fee_share(
+        amp,
+        reserves,
+        &reserves_last,
+        total_share,
+        fee_info.fee_config.protocol_fee,
+    )
+}
+
+/// ## Description
+/// Returns the message minting the LP tokens owed to the collector, if any, along with the
+/// minted amount that the LP token supply does not reflect until the message is executed.
+fn mint_protocol_fee(
+    storage: &dyn Storage,
+    config: &Config,
+    amp: u64,
+    reserves: &[Uint128; 2],
+    total_share: Uint128,
+) -> StdResult<(Option<CosmosMsg>, Uint128)> {
+    let share = load_protocol_fee_share(storage, amp, reserves, total_share)?;
+    if share.is_zero() {
+        return Ok((None, share));
+    }
+
+    let fee_info: FeeInfo = FEE_INFO.load(storage)?;
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: fee_info.collector.to_string(),
+            amount: share,
+        })?,
+        funds: vec![],
+    });
+
+    Ok((Some(msg), share))
+}
+
+/// ## Description
+/// Stores the reserves after a liquidity event, zero while the protocol fee is off.
+pub fn update_reserves_last(storage: &mut dyn Storage, reserves: &[Uint128; 2]) -> StdResult<()> {
+    let fee_info: FeeInfo = FEE_INFO.load(storage)?;
+    let reserves_last: [Uint128; 2] = if fee_info.fee_config.protocol_fee.is_zero() {
+        [Uint128::zero(); 2]
+    } else {
+        *reserves
+    };
+
+    RESERVES_LAST.save(storage, &reserves_last)
+}
+
+/// If `belief_price` and `max_spread` both are given,
+/// we compute new spread else we just use swap
+/// spread to check `max_spread`
+pub fn assert_max_spread(
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+) -> Result<(), ContractError> {
+    let offer_amount: Uint256 = offer_amount.into();
+    let return_amount: Uint256 = return_amount.into();
+    let spread_amount: Uint256 = spread_amount.into();
+
+    if let (Some(max_spread), Some(belief_price)) = (max_spread, belief_price) {
+        let belief_price: Decimal256 = belief_price.into();
+        let max_spread: Decimal256 = max_spread.into();
+
+        let expected_return = offer_amount / belief_price;
+        let spread_amount = if expected_return > return_amount {
+            expected_return - return_amount
+        } else {
+            Uint256::zero()
+        };
+
+        if return_amount < expected_return
+            && Decimal256::from_ratio(spread_amount, expected_return) > max_spread
+        {
+            return Err(ContractError::MaxSpreadAssertion {});
+        }
+    } else if let Some(max_spread) = max_spread {
+        let max_spread: Decimal256 = max_spread.into();
+        if Decimal256::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
+            return Err(ContractError::MaxSpreadAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the share the deposits would be worth at the peg.
+fn compute_expected_share(
+    amp: u64,
+    deposits: &[Uint128; 2],
+    pools: &[Asset; 2],
+    total_share: Uint128,
+) -> StdResult<Uint256> {
+    let d = compute_d(amp, pools[0].amount, pools[1].amount)?;
+    Ok(Uint256::from(total_share).multiply_ratio(Uint256::from(deposits[0] + deposits[1]), d))
+}
+
+/// The assets of a stableswap pool are pegged, so the minted share is checked against
+/// the share the deposits would be worth at the peg.
+fn assert_slippage_tolerance(
+    slippage_tolerance: &Option<Decimal>,
+    deposits: &[Uint128; 2],
+    pools: &[Asset; 2],
+    share: Uint128,
+    total_share: Uint128,
+    amp: u64,
+) -> Result<(), ContractError> {
+    if let Some(slippage_tolerance) = *slippage_tolerance {
+        let slippage_tolerance: Decimal256 = slippage_tolerance.into();
+        if slippage_tolerance > Decimal256::one() {
+            return Err(StdError::generic_err("slippage_tolerance cannot bigger than 1").into());
+        }
+
+        let one_minus_slippage_tolerance = Decimal256::one() - slippage_tolerance;
+        let expected_share = compute_expected_share(amp, deposits, pools, total_share)?;
+
+        if Uint256::from(share) < expected_share * one_minus_slippage_tolerance {
+            return Err(ContractError::MaxSlippageAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Migrates the pair to this version of the code, refusing downgrades. The reserves are
+/// initialized to the pair balances and the fee settings are cached from the factory if the
+/// pair does not store them yet.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = CONTRACT.load(deps.storage)?;
+    assert_migration_version(&stored, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // the stable pair has no legacy deployments, its state is always written on instantiation
+    if RESERVES.may_load(deps.storage)?.is_none()
+        || FEE_INFO.may_load(deps.storage)?.is_none()
+        || PAIR_STATUS.may_load(deps.storage)?.is_none()
+    {
+        return Err(ContractError::LegacyStateMigration {});
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "migrate"),
+        ("previous_version", stored.version.as_str()),
+        ("new_version", CONTRACT_VERSION),
+    ]))
+}
@@ -0,0 +1,188 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Decimal, StdError, StdResult, Uint128};
+
+/// the number of assets in the pool
+pub const N_COINS: u8 = 2;
+/// the maximum number of iterations used to converge the invariant
+const ITERATIONS: u8 = 32;
+/// the precision used to scale the commission rate
+const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
+
+/// ## Description
+/// Returns the stableswap invariant `D` of the pool by solving
+/// `A * n^n * sum(x_i) + D = A * D * n^n + D^(n+1) / (n^n * prod(x_i))` with Newton's method.
+/// ## Params
+/// * **amp** is the amplification coefficient of the pool.
+///
+/// * **amount_a** is the reserve of the first asset.
+///
+/// * **amount_b** is the reserve of the second asset.
+pub fn compute_d(amp: u64, amount_a: Uint128, amount_b: Uint128) -> StdResult<Uint256> {
+    let amount_a: Uint256 = amount_a.into();
+    let amount_b: Uint256 = amount_b.into();
+    let n_coins = Uint256::from(N_COINS as u64);
+
+    let sum_x = amount_a + amount_b;
+    if sum_x.is_zero() {
+        return Ok(Uint256::zero());
+    }
+    if amount_a.is_zero() || amount_b.is_zero() {
+        return Err(StdError::generic_err(
+            "Cannot compute the invariant of an empty reserve",
+        ));
+    }
+
+    let leverage = compute_leverage(amp);
+    let mut d = sum_x;
+    for _ in 0..ITERATIONS {
+        let mut d_product = d;
+        d_product = d_product.multiply_ratio(d, amount_a * n_coins);
+        d_product = d_product.multiply_ratio(d, amount_b * n_coins);
+
+        let d_prev = d;
+        // d = (leverage * sum_x + d_p * n) * d / ((leverage - 1) * d + (n + 1) * d_p)
+        let numerator = (leverage * sum_x + d_product * n_coins) * d;
+        let denominator = (leverage - Uint256::one()) * d + (n_coins + Uint256::one()) * d_product;
+        d = numerator.multiply_ratio(Uint256::one(), denominator);
+
+        if abs_diff(d, d_prev) <= Uint256::one() {
+            return Ok(d);
+        }
+    }
+
+    Err(StdError::generic_err(
+        "The invariant calculation did not converge",
+    ))
+}
+
+/// ## Description
+/// Returns the new reserve of the ask asset which keeps the invariant `d` unchanged
+/// when the reserve of the offer asset becomes `new_offer_pool`.
+/// ## Params
+/// * **amp** is the amplification coefficient of the pool.
+///
+/// * **new_offer_pool** is the reserve of the offer asset after the swap.
+///
+/// * **d** is the invariant of the pool.
+pub fn compute_new_balance(amp: u64, new_offer_pool: Uint128, d: Uint256) -> StdResult<Uint128> {
+    if new_offer_pool.is_zero() {
+        return Err(StdError::generic_err(
+            "Cannot compute the balance of an empty reserve",
+        ));
+    }
+
+    let n_coins = Uint256::from(N_COINS as u64);
+    let leverage = compute_leverage(amp);
+    let new_offer_pool: Uint256 = new_offer_pool.into();
+
+    // y^2 + (b - d) * y = c
+    // c = d^(n+1) / (n^n * new_offer_pool * leverage)
+    // b = new_offer_pool + d / leverage
+    let c = d
+        .multiply_ratio(d, new_offer_pool * n_coins)
+        .multiply_ratio(d, leverage * n_coins);
+    let b = new_offer_pool + d.multiply_ratio(Uint256::one(), leverage);
+
+    let mut y = d;
+    for _ in 0..ITERATIONS {
+        let y_prev = y;
+        // y = (y^2 + c) / (2y + b - d)
+        let denominator = y * n_coins + b;
+        if denominator <= d {
+            return Err(StdError::generic_err(
+                "The balance calculation did not converge",
+            ));
+        }
+        y = (y * y + c).multiply_ratio(Uint256::one(), denominator - d);
+
+        if abs_diff(y, y_prev) <= Uint256::one() {
+            return Ok(y.into());
+        }
+    }
+
+    Err(StdError::generic_err(
+        "The balance calculation did not converge",
+    ))
+}
+
+/// ## Description
+/// Returns the return, spread and commission amounts of swapping `offer_amount` into the pool.
+/// The spread is measured against the 1:1 peg of the assets.
+pub fn compute_swap(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    amp: u64,
+    commission_rate: Decimal,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    let d = compute_d(amp, offer_pool, ask_pool)?;
+    let new_ask_pool = compute_new_balance(amp, offer_pool.checked_add(offer_amount)?, d)?;
+
+    // subtract one unit to round in favor of the pool
+    let return_amount = ask_pool
+        .checked_sub(new_ask_pool)
+        .unwrap_or_else(|_| Uint128::zero())
+        .checked_sub(Uint128::from(1u128))
+        .unwrap_or_else(|_| Uint128::zero());
+
+    let spread_amount = offer_amount
+        .checked_sub(return_amount)
+        .unwrap_or_else(|_| Uint128::zero());
+    let commission_amount = return_amount * commission_rate;
+
+    // commission will be absorbed to pool
+    let return_amount = return_amount.checked_sub(commission_amount)?;
+    Ok((return_amount, spread_amount, commission_amount))
+}
+
+/// ## Description
+/// Returns the offer, spread and commission amounts required to receive `ask_amount` from the pool.
+pub fn compute_offer_amount(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    amp: u64,
+    commission_rate: Decimal,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    // round up, so that swapping the offer amount returns at least the ask amount
+    let fractional = Uint256::from(DECIMAL_FRACTIONAL);
+    let one_minus_commission: Uint256 =
+        fractional * (Decimal256::one() - Decimal256::from(commission_rate));
+    let before_commission_deduction: Uint128 =
+        div_ceil(Uint256::from(ask_amount) * fractional, one_minus_commission).into();
+
+    let d = compute_d(amp, offer_pool, ask_pool)?;
+    let new_offer_pool =
+        compute_new_balance(amp, ask_pool.checked_sub(before_commission_deduction)?, d)?;
+
+    // add one unit to round in favor of the pool
+    let offer_amount = new_offer_pool
+        .checked_sub(offer_pool)?
+        .checked_add(Uint128::from(1u128))?;
+
+    let spread_amount = offer_amount
+        .checked_sub(before_commission_deduction)
+        .unwrap_or_else(|_| Uint128::zero());
+    let commission_amount = before_commission_deduction * commission_rate;
+    Ok((offer_amount, spread_amount, commission_amount))
+}
+
+/// ## Description
+/// Returns the leverage `A * n^n` of the amplification coefficient.
+fn compute_leverage(amp: u64) -> Uint256 {
+    Uint256::from(amp * (N_COINS as u64).pow(N_COINS as u32))
+}
+
+/// ## Description
+/// Returns `numerator / denominator` rounded up.
+fn div_ceil(numerator: Uint256, denominator: Uint256) -> Uint256 {
+    (numerator + denominator - Uint256::one()).multiply_ratio(Uint256::one(), denominator)
+}
+
+fn abs_diff(a: Uint256, b: Uint256) -> Uint256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
@@ -0,0 +1,36 @@
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::Item;
+use prismswap::asset::{Asset, PairInfo};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const CONFIG: Item<Config> = Item::new("config");
+/// the pool reserves, stored in the order of the pair asset infos
+pub const RESERVES: Item<[Uint128; 2]> = Item::new("reserves");
+/// the reserves after the last liquidity event, zero while the protocol fee is off. The protocol
+/// fee is the growth of the invariant D since then, both sides computed at the current amp
+pub const RESERVES_LAST: Item<[Uint128; 2]> = Item::new("reserves_last");
+/// the asset lent by the flash swap in progress, other messages are rejected while it is set
+pub const FLASH_SWAP: Item<Asset> = Item::new("flash_swap");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// the type of pair info available in [`PairInfo`]
+    pub pair_info: PairInfo,
+    /// the factory contract address
+    pub factory: Addr,
+    /// the cumulative price of the first asset denominated in the second asset
+    pub price0_cumulative_last: Uint128,
+    /// the cumulative price of the second asset denominated in the first asset
+    pub price1_cumulative_last: Uint128,
+    /// the block time in seconds of the last price accumulation
+    pub block_time_last: u64,
+    /// the amplification coefficient at the start of the current ramp
+    pub init_amp: u64,
+    /// the block time in seconds when the current ramp started
+    pub init_amp_time: u64,
+    /// the amplification coefficient targeted by the current ramp
+    pub next_amp: u64,
+    /// the block time in seconds when the current ramp ends
+    pub next_amp_time: u64,
+}
@@ -0,0 +1,2719 @@
+use crate::contract::{
+    compute_current_amp, compute_protocol_fee_share, execute, instantiate, migrate, query, reply,
+    CONTRACT_NAME, CONTRACT_VERSION,
+};
+use crate::math::{compute_d, compute_offer_amount, compute_swap};
+use crate::state::{CONFIG, RESERVES, RESERVES_LAST};
+use prismswap::error::ContractError;
+use prismswap::mock_querier::{mock_dependencies, WasmMockQuerier};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Env,
+    OwnedDeps, Reply, ReplyOn, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use prismswap::asset::{Asset, AssetInfo};
+use prismswap::factory::FeeConfig;
+use prismswap::pair::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, FlashSwapCallbackMsg, Hook, HooksResponse,
+    InstantiateMsg, MigrateMsg, PairHookMsg, PendingProtocolFeeResponse, PoolResponse,
+    PriceImpactResponse, QueryMsg, ReverseSimulationResponse, SimulateProvideResponse,
+    SimulateWithdrawResponse, SimulationResponse, SpotPriceResponse, StablePoolConfig,
+    StablePoolParams, StablePoolUpdateParams, StatsResponse, MAX_HOOK_GAS, MAX_PAIR_HOOKS,
+    MINIMUM_LIQUIDITY_AMOUNT,
+};
+use prismswap::pair_status::{PairStatus, PAIR_STATUS};
+use prismswap::token::InstantiateMsg as TokenInstantiateMsg;
+
+fn instantiate_msg(init_params: Option<StablePoolParams>) -> InstantiateMsg {
+    InstantiateMsg {
+        asset_infos: [
+            AssetInfo::Native("uusd".to_string()),
+            AssetInfo::Cw20(Addr::unchecked("asset0000")),
+        ],
+        token_code_id: 10u64,
+        factory: Addr::unchecked("factory0000"),
+        fee_config: FeeConfig {
+            total_fee: Decimal::permille(3),
+            protocol_fee: Decimal::percent(50),
+        },
+        collector: Addr::unchecked("collector"),
+        init_params: init_params.map(|params| to_binary(&params).unwrap()),
+    }
+}
+
+fn init_pair(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>, amp: u64) {
+    let msg = instantiate_msg(Some(StablePoolParams { amp }));
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+}
+
+fn env_at(seconds: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(seconds);
+    env
+}
+
+fn query_amp(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>, env: Env) -> Decimal {
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::Config {}).unwrap()).unwrap();
+    let params: StablePoolConfig = from_binary(&config.params.unwrap()).unwrap();
+    params.amp
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(123u128))],
+    )]);
+
+    // failure - missing init params
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), env, info, instantiate_msg(None)).unwrap_err();
+    assert_eq!(err, ContractError::InitParamsNotFound {});
+
+    // failure - invalid amp
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(
+        deps.as_mut(),
+        env,
+        info,
+        instantiate_msg(Some(StablePoolParams { amp: 0 })),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IncorrectAmp {});
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(
+        deps.as_mut(),
+        env,
+        info,
+        instantiate_msg(Some(StablePoolParams { amp: 1_000_001 })),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IncorrectAmp {});
+
+    // success
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(
+        deps.as_mut(),
+        env,
+        info,
+        instantiate_msg(Some(StablePoolParams { amp: 100 })),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            msg: WasmMsg::Instantiate {
+                code_id: 10u64,
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: "UUSD-PRISM-LP".to_string(),
+                    symbol: "uLP".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: MOCK_CONTRACT_ADDR.to_string(),
+                        cap: None,
+                    }),
+                })
+                .unwrap(),
+                funds: vec![],
+                label: "".to_string(),
+                admin: None,
+            }
+            .into(),
+            gas_limit: None,
+            id: 1,
+            reply_on: ReplyOn::Success,
+        }]
+    );
+
+    assert_eq!(
+        query_amp(&deps, mock_env()),
+        Decimal::from_ratio(100u128, 1u128)
+    );
+}
+
+#[test]
+fn provide_liquidity() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    init_pair(&mut deps, 100);
+
+    // failure - the initial deposit must contain both assets
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::zero(),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000_000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidZeroAmount {});
+
+    // failure - the deadline has passed
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: Some(mock_env().block.time.seconds() - 1),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000_000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::ExpiredDeadline {});
+
+    // initial share = invariant of the deposits
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "addr0000".to_string(),
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(1_000_000_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(MINIMUM_LIQUIDITY_AMOUNT),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(2_000_000_000u128 - MINIMUM_LIQUIDITY_AMOUNT),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+
+    // a balanced deposit mints shares proportionally without fees
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [
+            Uint128::from(1_000_000_000u128),
+            Uint128::from(1_000_000_000u128)
+        ]
+    );
+    deps.querier.with_token_balances(&[(
+        &"liquidity0000".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(2_000_000_000u128))],
+    )]);
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+        ],
+        slippage_tolerance: Some(Decimal::percent(1)),
+        receiver: Some("staking0000".to_string()),
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages.get(1).expect("no message"),
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "staking0000".to_string(),
+                amount: Uint128::from(2_000_000_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+
+    // a single sided deposit is charged the imbalance fee
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [
+            Uint128::from(2_000_000_000u128),
+            Uint128::from(2_000_000_000u128)
+        ]
+    );
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[
+                Uint128::from(1_000_000_000u128),
+                Uint128::from(1_000_000_000u128),
+            ],
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::zero(),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    let share = match res.messages.get(1).expect("no message").msg.clone() {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_binary(&msg).unwrap() {
+            Cw20ExecuteMsg::Mint { amount, .. } => amount,
+            _ => panic!("DO NOT ENTER HERE"),
+        },
+        _ => panic!("DO NOT ENTER HERE"),
+    };
+    assert!(share < Uint128::from(1_000_000_000u128));
+    assert!(share > Uint128::from(990_000_000u128));
+
+    // failure - the share is below the slippage tolerance
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::zero(),
+            },
+        ],
+        slippage_tolerance: Some(Decimal::permille(1)),
+        receiver: None,
+        deadline: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
+}
+
+#[test]
+fn withdraw_liquidity() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(200u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    init_pair(&mut deps, 100);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
+    // failure - only the liquidity token can withdraw
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(100u128),
+    });
+    let err = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // withdraw half of the liquidity
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(100u128),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(cosmwasm_std::BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(50u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(50u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(100u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(50u128), Uint128::from(50u128)]
+    );
+}
+
+#[test]
+fn provide_single_asset() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000_000u128))],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    init_pair(&mut deps, 100);
+
+    let msg =
+        |max_spread: Option<Decimal>, min_share: Option<Uint128>| ExecuteMsg::ProvideSingleAsset {
+            asset: Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(1_000_000_000u128),
+            },
+            max_spread,
+            min_share,
+            receiver: None,
+            deadline: None,
+        };
+
+    // failure - the pool is empty
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg(None, None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EmptyPool {});
+
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[
+                Uint128::from(1_000_000_000u128),
+                Uint128::from(1_000_000_000u128),
+            ],
+        )
+        .unwrap();
+
+    // the share is the one of the imbalanced deposit
+    let simulation: SimulateProvideResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateProvide {
+                assets: [
+                    Asset {
+                        info: AssetInfo::Native("uusd".to_string()),
+                        amount: Uint128::zero(),
+                    },
+                    Asset {
+                        info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                        amount: Uint128::from(1_000_000_000u128),
+                    },
+                ],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    // failure - the share is below the minimum share
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg(None, Some(simulation.share + Uint128::from(1u128))),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
+
+    // failure - the loss against the peg exceeds the max spread
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg(
+            Some((Decimal256::from(simulation.slippage) * Decimal256::percent(99)).into()),
+            None,
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSpreadAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg(
+            Some(simulation.slippage + Decimal::permille(1)),
+            Some(simulation.share),
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "addr0000".to_string(),
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(1_000_000_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0000".to_string(),
+                    amount: simulation.share,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [
+            Uint128::from(1_000_000_000u128),
+            Uint128::from(2_000_000_000u128)
+        ]
+    );
+
+    // the imbalance fee of the deposit accrues to the protocol on the next liquidity event
+    let res: PendingProtocolFeeResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingProtocolFee {}).unwrap())
+            .unwrap();
+    assert!(!res.share.is_zero());
+}
+
+#[test]
+fn withdraw_single_asset() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000_000u128))],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1_000_000u128))],
+    );
+
+    init_pair(&mut deps, 100);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[
+                Uint128::from(1_000_000_000u128),
+                Uint128::from(1_000_000_000u128),
+            ],
+        )
+        .unwrap();
+
+    let msg = |min_receive: Option<Uint128>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            msg: to_binary(&Cw20HookMsg::WithdrawSingleAsset {
+                ask_asset: AssetInfo::Native("uusd".to_string()),
+                min_receive,
+                receiver: None,
+                deadline: None,
+            })
+            .unwrap(),
+            amount: Uint128::from(200_000_000u128),
+        })
+    };
+
+    // failure - only the liquidity token can withdraw
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg(None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the token leg is swapped against the reserves after the withdrawal
+    let (return_amount, _, commission_amount) = compute_swap(
+        Uint128::from(900_000_000u128),
+        Uint128::from(900_000_000u128),
+        Uint128::from(100_000_000u128),
+        100,
+        Decimal::permille(3),
+    )
+    .unwrap();
+    let refund_amount = Uint128::from(100_000_000u128) + return_amount;
+    // the capped stability tax is deducted from the sent amount
+    let received_amount = refund_amount - Uint128::from(1_000_000u128);
+
+    // failure - the received amount is below the minimum
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg(Some(received_amount + Uint128::from(1u128))),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MinWithdrawAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg(Some(received_amount)),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: received_amount,
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(200_000_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [
+            Uint128::from(1_000_000_000u128) - refund_amount,
+            Uint128::from(1_000_000_000u128)
+        ]
+    );
+    assert_eq!(
+        RESERVES_LAST.load(deps.as_ref().storage).unwrap(),
+        [
+            Uint128::from(900_000_000u128),
+            Uint128::from(900_000_000u128)
+        ]
+    );
+
+    // the internal swap is recorded in the statistics
+    let stats: StatsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(stats.volumes[1].amount, Uint128::from(100_000_000u128));
+    assert_eq!(
+        stats.protocol_commissions[0].amount + stats.lp_commissions[0].amount,
+        commission_amount
+    );
+    assert_eq!(stats.swap_count, 1);
+}
+
+#[test]
+fn swap() {
+    let total_share = Uint128::from(60_000_000_000u128);
+    let asset_pool_amount = Uint128::from(30_000_000_000u128);
+    let collateral_pool_amount = Uint128::from(30_000_000_000u128);
+    let offer_amount = Uint128::from(1_500_000_000u128);
+
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    init_pair(&mut deps, 100);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let (expected_return, expected_spread, expected_commission) = compute_swap(
+        collateral_pool_amount,
+        asset_pool_amount,
+        offer_amount,
+        100,
+        Decimal::permille(3),
+    )
+    .unwrap();
+
+    // the stableswap curve returns almost the whole offer amount
+    assert!(expected_return + expected_commission > Uint128::from(1_495_000_000u128));
+
+    // the simulation matches the executed swap
+    let simulation_res: SimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: offer_amount,
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(simulation_res.return_amount, expected_return);
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(1)),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+
+    let update_pair_status = |deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+                              paused: bool,
+                              deregistered: bool| {
+        let msg = ExecuteMsg::UpdatePairStatus {
+            paused,
+            deregistered,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("factory0000", &[]),
+            msg,
+        )
+    };
+
+    // failure - only the factory can push the status
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdatePairStatus {
+            paused: true,
+            deregistered: false,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // failure - the factory paused the pair
+    update_pair_status(&mut deps, true, false).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    // failure - the factory paused every pair
+    update_pair_status(&mut deps, false, false).unwrap();
+    deps.querier.with_global_paused(true);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+    deps.querier.with_global_paused(false);
+
+    // failure - the factory deregistered the pair
+    update_pair_status(&mut deps, false, true).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Deregistered {});
+    update_pair_status(&mut deps, false, false).unwrap();
+
+    // the commission stays in the pool
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: expected_return,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes
+            .iter()
+            .find(|attr| attr.key == "spread_amount")
+            .unwrap()
+            .value,
+        expected_spread.to_string()
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [
+            collateral_pool_amount + offer_amount,
+            asset_pool_amount - expected_return
+        ]
+    );
+
+    // failure - max spread exceeded
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: offer_amount,
+        },
+        belief_price: Some(Decimal::percent(90)),
+        max_spread: Some(Decimal::permille(1)),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxSpreadAssertion {});
+
+    // the reverse simulation requires roughly the same offer amount
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+    let reverse_simulation_res: ReverseSimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ReverseSimulation {
+                ask_asset: Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: expected_return,
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(
+        (reverse_simulation_res.offer_amount.u128() as i128 - offer_amount.u128() as i128).abs()
+            < 5
+    );
+}
+
+#[test]
+fn swap_with_tax() {
+    let total_share = Uint128::from(60_000_000_000u128);
+    let asset_pool_amount = Uint128::from(30_000_000_000u128);
+    let collateral_pool_amount = Uint128::from(30_000_000_000u128);
+    let offer_amount = Uint128::from(1_000_000u128);
+
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1_000u128))],
+    );
+
+    init_pair(&mut deps, 100);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let (expected_return, _, _) = compute_swap(
+        asset_pool_amount,
+        collateral_pool_amount,
+        offer_amount,
+        100,
+        Decimal::permille(3),
+    )
+    .unwrap();
+
+    // the tax on the return is capped
+    let simulation_res: SimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: offer_amount,
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(simulation_res.tax_amount, Uint128::from(1_000u128));
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+
+    // the pool bears the tax of the return, the receiver gets the simulated amount
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: simulation_res.return_amount,
+            }],
+        }))]
+    );
+    assert_eq!(
+        simulation_res.return_amount + simulation_res.tax_amount,
+        expected_return
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [
+            collateral_pool_amount - expected_return,
+            asset_pool_amount + offer_amount
+        ]
+    );
+}
+
+#[test]
+fn swap_exact_output() {
+    let total_share = Uint128::from(60_000_000_000u128);
+    let asset_pool_amount = Uint128::from(30_000_000_000u128);
+    let collateral_pool_amount = Uint128::from(30_000_000_000u128);
+    let ask_amount = Uint128::from(1_000_000_000u128);
+    let sent_amount = Uint128::from(1_100_000_000u128);
+
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    init_pair(&mut deps, 100);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+
+    let (offer_amount, _, commission_amount) = compute_offer_amount(
+        collateral_pool_amount,
+        asset_pool_amount,
+        ask_amount,
+        100,
+        Decimal::permille(3),
+    )
+    .unwrap();
+
+    // the reverse simulation matches the offer spent
+    let reverse_simulation_res: ReverseSimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ReverseSimulation {
+                ask_asset: Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: ask_amount,
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reverse_simulation_res.offer_amount, offer_amount);
+
+    let swap_msg = |max_offer: Option<Uint128>| ExecuteMsg::SwapExactOutput {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: sent_amount,
+        },
+        ask_amount,
+        max_offer,
+        to: Some("addr0001".to_string()),
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: sent_amount,
+        }],
+    );
+
+    // failure - the offer exceeds the max offer
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        swap_msg(Some(offer_amount - Uint128::from(1u128))),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxOfferAssertion {});
+
+    // failure - the offer exceeds the sent funds
+    let msg = ExecuteMsg::SwapExactOutput {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: ask_amount,
+        },
+        ask_amount,
+        max_offer: None,
+        to: None,
+        deadline: None,
+    };
+    let small_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: ask_amount,
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), small_info, msg).unwrap_err();
+    assert_eq!(err, ContractError::MaxOfferAssertion {});
+
+    // the unused offer is refunded to the sender
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        swap_msg(Some(offer_amount)),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0001".to_string(),
+                    amount: ask_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: sent_amount - offer_amount,
+                }],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes
+            .iter()
+            .find(|attr| attr.key == "offer_amount")
+            .unwrap()
+            .value,
+        offer_amount.to_string()
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [
+            collateral_pool_amount + offer_amount,
+            asset_pool_amount - ask_amount
+        ]
+    );
+
+    // the trade is recorded in the statistics
+    let stats: StatsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()).unwrap();
+    let protocol_commission = commission_amount * Decimal::percent(50);
+    assert_eq!(stats.volumes[0].amount, offer_amount);
+    assert_eq!(stats.volumes[1].amount, Uint128::zero());
+    assert_eq!(stats.protocol_commissions[1].amount, protocol_commission);
+    assert_eq!(
+        stats.lp_commissions[1].amount,
+        commission_amount - protocol_commission
+    );
+    assert_eq!(stats.swap_count, 1);
+    assert_eq!(stats.last_trade_time, mock_env().block.time.seconds());
+
+    // the token side is swapped through the cw20 hook
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[collateral_pool_amount, asset_pool_amount],
+        )
+        .unwrap();
+    let (offer_amount, _, _) = compute_offer_amount(
+        asset_pool_amount,
+        collateral_pool_amount,
+        ask_amount,
+        100,
+        Decimal::permille(3),
+    )
+    .unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: sent_amount,
+        msg: to_binary(&Cw20HookMsg::SwapExactOutput {
+            ask_amount,
+            max_offer: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: ask_amount,
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: sent_amount - offer_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [
+            collateral_pool_amount - ask_amount,
+            asset_pool_amount + offer_amount
+        ]
+    );
+}
+
+#[test]
+fn pair_hooks() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000u128))],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    init_pair(&mut deps, 100);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    let add_hook_msg = |contract_addr: &str| ExecuteMsg::AddHook {
+        contract_addr: contract_addr.to_string(),
+        gas_limit: 100_000u64,
+    };
+
+    // failure - only the factory owner can add hooks
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        add_hook_msg("hook0000"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // failure - the gas limit must be positive and capped
+    for gas_limit in [0u64, MAX_HOOK_GAS + 1] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            ExecuteMsg::AddHook {
+                contract_addr: "hook0000".to_string(),
+                gas_limit,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidHookGasLimit {});
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        add_hook_msg("hook0000"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "add_hook"),
+            attr("contract_addr", "hook0000"),
+            attr("gas_limit", "100000"),
+        ]
+    );
+
+    // failure - the hook is already registered
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        add_hook_msg("hook0000"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::HookAlreadyRegistered {});
+
+    let res: HooksResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap()).unwrap();
+    assert_eq!(
+        res.hooks,
+        vec![Hook {
+            contract_addr: Addr::unchecked("hook0000"),
+            gas_limit: 100_000u64,
+        }]
+    );
+
+    // the hook receives the swap after the transfer
+    let (return_amount, _, _) = compute_swap(
+        Uint128::from(1_000_000u128),
+        Uint128::from(1_000_000u128),
+        Uint128::from(1_000u128),
+        100,
+        Decimal::permille(3),
+    )
+    .unwrap();
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(1_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1],
+        SubMsg {
+            id: 2,
+            msg: PairHookMsg::Swap {
+                sender: Addr::unchecked("addr0000"),
+                receiver: Addr::unchecked("addr0000"),
+                offer_asset: Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(1_000u128),
+                },
+                return_asset: Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: return_amount,
+                },
+                reserves: [
+                    Asset {
+                        info: AssetInfo::Native("uusd".to_string()),
+                        amount: Uint128::from(1_001_000u128),
+                    },
+                    Asset {
+                        info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                        amount: Uint128::from(1_000_000u128) - return_amount,
+                    },
+                ],
+            }
+            .into_cosmos_msg("hook0000")
+            .unwrap(),
+            gas_limit: Some(100_000u64),
+            reply_on: ReplyOn::Error,
+        }
+    );
+
+    // the hook receives the withdrawal after the burn
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(200_000u128),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 4);
+    assert_eq!(
+        res.messages[3].msg,
+        PairHookMsg::WithdrawLiquidity {
+            sender: Addr::unchecked("addr0000"),
+            receiver: Addr::unchecked("addr0000"),
+            share: Uint128::from(200_000u128),
+            refund_assets: [
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(100_000u128),
+                },
+                Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(100_000u128),
+                },
+            ],
+            reserves: [
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(900_000u128),
+                },
+                Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: Uint128::from(900_000u128),
+                },
+            ],
+        }
+        .into_cosmos_msg("hook0000")
+        .unwrap()
+    );
+
+    // a failed hook does not revert the pair action
+    let reply_msg = Reply {
+        id: 2,
+        result: ContractResult::Err("out of gas".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "pair_hook_failed"),
+            attr("error", "out of gas"),
+        ]
+    );
+
+    // failure - the number of hooks is limited
+    for i in 1..MAX_PAIR_HOOKS {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            add_hook_msg(&format!("hook{:04}", i)),
+        )
+        .unwrap();
+    }
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        add_hook_msg("hook9999"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TooManyHooks {});
+
+    // failure - only registered hooks can be removed
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RemoveHook {
+            contract_addr: "hook9999".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::HookNotFound {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RemoveHook {
+            contract_addr: "hook0000".to_string(),
+        },
+    )
+    .unwrap();
+    let res: HooksResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap()).unwrap();
+    assert_eq!(res.hooks.len(), MAX_PAIR_HOOKS - 1);
+    assert!(!res
+        .hooks
+        .iter()
+        .any(|hook| hook.contract_addr == Addr::unchecked("hook0000")));
+}
+
+#[test]
+fn protocol_fee_accrual() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000u128))],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    init_pair(&mut deps, 100);
+    let reserves_last = [Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)];
+    RESERVES
+        .save(deps.as_mut().storage, &reserves_last)
+        .unwrap();
+    RESERVES_LAST
+        .save(deps.as_mut().storage, &reserves_last)
+        .unwrap();
+
+    let res: PendingProtocolFeeResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingProtocolFee {}).unwrap())
+            .unwrap();
+    assert_eq!(res.share, Uint128::zero());
+
+    // the swap does not transfer the protocol fee
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(100_000u128),
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(10)),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // the commission grew the invariant, the protocol is owed
+    // total_share * (d - d_last) * 0.5 / (d * 0.5 + d_last * 0.5)
+    let reserves = RESERVES.load(deps.as_ref().storage).unwrap();
+    let d = compute_d(100, reserves[0], reserves[1]).unwrap();
+    let d_last = compute_d(100, reserves_last[0], reserves_last[1]).unwrap();
+    assert!(d > d_last);
+    let expected_share: Uint128 = (Uint256::from(2_000_000u128) * (d - d_last))
+        .multiply_ratio(Uint256::one(), d + d_last)
+        .into();
+    assert!(!expected_share.is_zero());
+
+    let res: PendingProtocolFeeResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingProtocolFee {}).unwrap())
+            .unwrap();
+    let total_share = Uint128::from(2_000_000u128) + expected_share;
+    assert_eq!(
+        res,
+        PendingProtocolFeeResponse {
+            share: expected_share,
+            assets: [
+                Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: reserves[0].multiply_ratio(expected_share, total_share),
+                },
+                Asset {
+                    info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                    amount: reserves[1].multiply_ratio(expected_share, total_share),
+                },
+            ],
+        }
+    );
+
+    // the protocol fee share is minted on the withdrawal and dilutes it
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(1_000u128),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "collector".to_string(),
+                amount: expected_share,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    let refund_amounts = [
+        reserves[0] * Decimal::from_ratio(1_000u128, total_share),
+        reserves[1] * Decimal::from_ratio(1_000u128, total_share),
+    ];
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr(
+            "refund_assets",
+            format!(
+                "native:uusd:{}, cw20:asset0000:{}",
+                refund_amounts[0], refund_amounts[1]
+            )
+        )
+    );
+
+    // the accrual restarts from the reserves after the withdrawal
+    let reserves = [
+        reserves[0] - refund_amounts[0],
+        reserves[1] - refund_amounts[1],
+    ];
+    assert_eq!(RESERVES.load(deps.as_ref().storage).unwrap(), reserves);
+    assert_eq!(RESERVES_LAST.load(deps.as_ref().storage).unwrap(), reserves);
+}
+
+#[test]
+fn sync_and_skim() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(200u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    init_pair(&mut deps, 100);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(100u128)],
+        )
+        .unwrap();
+
+    // donations do not move the pool
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(150u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(200u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(130u128))],
+        ),
+    ]);
+    let res: PoolResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Pool {}).unwrap()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::from(100u128));
+    assert_eq!(res.assets[1].amount, Uint128::from(100u128));
+
+    // anyone can skim the excess
+    let msg = ExecuteMsg::Skim {
+        to: "addr0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(50u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0001".to_string(),
+                    amount: Uint128::from(30u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(100u128), Uint128::from(100u128)]
+    );
+
+    // failure - only the factory owner can sync
+    let msg = ExecuteMsg::Sync {};
+    let err = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::Sync {};
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr("reserves", "native:uusd:150, cw20:asset0000:130"),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(150u128), Uint128::from(130u128)]
+    );
+
+    // nothing left to skim
+    let msg = ExecuteMsg::Skim {
+        to: "addr0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn flash_swap() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+
+    init_pair(&mut deps, 100);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    let flash_swap_msg = |amount: u128| ExecuteMsg::FlashSwap {
+        ask_asset: Asset {
+            info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+            amount: Uint128::from(amount),
+        },
+        msg: to_binary(&"arbitrage").unwrap(),
+        deadline: None,
+    };
+
+    // failure - cannot borrow the whole reserve
+    let info = mock_info("arber0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, flash_swap_msg(1_000_000)).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientLiquidity {});
+
+    let info = mock_info("arber0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, flash_swap_msg(100_000)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "arber0000".to_string(),
+                    amount: Uint128::from(100_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(
+                FlashSwapCallbackMsg {
+                    ask_asset: Asset {
+                        info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                        amount: Uint128::from(100_000u128),
+                    },
+                    msg: to_binary(&"arbitrage").unwrap(),
+                }
+                .into_cosmos_msg("arber0000")
+                .unwrap()
+            ),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::FinalizeFlashSwap {}).unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+
+    // failure - the pair is locked until the flash swap is finalized
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(10u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "arber0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::FlashSwapInProgress {});
+
+    // failure - only the pair can finalize the flash swap
+    let info = mock_info("arber0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FinalizeFlashSwap {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the callback paid uusd back for the 100000 asset0000 borrowed, the payment net of the
+    // commission must keep the invariant D
+    let d = compute_d(
+        100,
+        Uint128::from(1_000_000u128),
+        Uint128::from(1_000_000u128),
+    )
+    .unwrap();
+    let payment = |amount: u128| Uint128::from(amount) * Decimal::permille(997);
+    assert!(
+        compute_d(
+            100,
+            Uint128::from(1_000_000u128) + payment(100_000),
+            Uint128::from(900_000u128),
+        )
+        .unwrap()
+            < d
+    );
+    assert!(
+        compute_d(
+            100,
+            Uint128::from(1_000_000u128) + payment(101_000),
+            Uint128::from(900_000u128),
+        )
+        .unwrap()
+            >= d
+    );
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(900_000u128))],
+    )]);
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_100_000u128),
+        }],
+    )]);
+
+    // failure - the payment is too small
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FinalizeFlashSwap {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FlashSwapInvariant {});
+
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_101_000u128),
+        }],
+    )]);
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FinalizeFlashSwap {},
+    )
+    .unwrap();
+
+    // the whole payment stays in the pool
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "finalize_flash_swap"),
+            attr("offer_assets", "native:uusd:101000, cw20:asset0000:0"),
+            attr("reserves", "1101000, 900000"),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_101_000u128), Uint128::from(900_000u128)]
+    );
+
+    // the pair is unlocked
+    let info = mock_info("arber0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, flash_swap_msg(100_000)).unwrap();
+}
+
+#[test]
+fn limit_orders_not_supported() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    init_pair(&mut deps, 100);
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+    let msg = ExecuteMsg::SubmitOrder {
+        offer_asset: Asset {
+            info: AssetInfo::Native("uusd".to_string()),
+            amount: Uint128::from(1_000u128),
+        },
+        price: Decimal::one(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::NonSupported {});
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1_000u128),
+        msg: to_binary(&Cw20HookMsg::SubmitOrder {
+            price: Decimal::one(),
+        })
+        .unwrap(),
+    });
+    let err = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::NonSupported {});
+
+    let err = query(deps.as_ref(), mock_env(), QueryMsg::Order { order_id: 1 }).unwrap_err();
+    assert_eq!(err, ContractError::NonSupported {});
+}
+
+#[test]
+fn update_fee_config() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000_000u128))],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    init_pair(&mut deps, 100);
+    let reserves = [
+        Uint128::from(1_000_000_000u128),
+        Uint128::from(1_000_000_000u128),
+    ];
+    let reserves_last = [
+        Uint128::from(990_000_000u128),
+        Uint128::from(990_000_000u128),
+    ];
+    RESERVES.save(deps.as_mut().storage, &reserves).unwrap();
+    RESERVES_LAST
+        .save(deps.as_mut().storage, &reserves_last)
+        .unwrap();
+
+    let msg = ExecuteMsg::UpdateFeeConfig {
+        fee_config: Some(FeeConfig {
+            total_fee: Decimal::permille(1),
+            protocol_fee: Decimal::zero(),
+        }),
+        collector: Some(Addr::unchecked("collector0001")),
+    };
+
+    // failure - only the factory can push fee settings
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the accrued protocol fee is minted to the previous collector with the previous settings
+    let share = compute_protocol_fee_share(
+        100,
+        &reserves,
+        &reserves_last,
+        Uint128::from(2_000_000_000u128),
+        Decimal::percent(50),
+    )
+    .unwrap();
+    assert!(!share.is_zero());
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "collector".to_string(),
+                amount: share,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // the accrual restarts under the new settings, which turn the protocol fee off
+    assert_eq!(
+        RESERVES_LAST.load(deps.as_ref().storage).unwrap(),
+        [Uint128::zero(); 2]
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(123u128))],
+    )]);
+
+    init_pair(&mut deps, 100);
+
+    let start_changing_amp = |next_amp: u64, next_amp_time: u64| ExecuteMsg::UpdateConfig {
+        params: to_binary(&StablePoolUpdateParams::StartChangingAmp {
+            next_amp,
+            next_amp_time,
+        })
+        .unwrap(),
+    };
+    let block_time = mock_env().block.time.seconds();
+
+    // failure - only the factory owner can update the config
+    let err = execute(
+        deps.as_mut(),
+        env_at(86400),
+        mock_info("addr0000", &[]),
+        start_changing_amp(200, block_time + 2 * 86400),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // failure - amp cannot be changed right after the previous change
+    let err = execute(
+        deps.as_mut(),
+        env_at(100),
+        mock_info("owner0000", &[]),
+        start_changing_amp(200, block_time + 2 * 86400),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MinAmpChangingTimeAssertion {});
+
+    // failure - the ramp is too short
+    let err = execute(
+        deps.as_mut(),
+        env_at(86400),
+        mock_info("owner0000", &[]),
+        start_changing_amp(200, block_time + 86400 + 100),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MinAmpChangingTimeAssertion {});
+
+    // failure - the change is too large
+    let err = execute(
+        deps.as_mut(),
+        env_at(86400),
+        mock_info("owner0000", &[]),
+        start_changing_amp(1001, block_time + 2 * 86400),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxAmpChangeAssertion {});
+
+    let err = execute(
+        deps.as_mut(),
+        env_at(86400),
+        mock_info("owner0000", &[]),
+        start_changing_amp(9, block_time + 2 * 86400),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxAmpChangeAssertion {});
+
+    // failure - invalid amp
+    let err = execute(
+        deps.as_mut(),
+        env_at(86400),
+        mock_info("owner0000", &[]),
+        start_changing_amp(0, block_time + 2 * 86400),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IncorrectAmp {});
+
+    // ramp from 100 to 500 within a day
+    execute(
+        deps.as_mut(),
+        env_at(86400),
+        mock_info("owner0000", &[]),
+        start_changing_amp(500, block_time + 2 * 86400),
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_amp(&deps, env_at(86400)),
+        Decimal::from_ratio(100u128, 1u128)
+    );
+    assert_eq!(
+        query_amp(&deps, env_at(86400 + 43200)),
+        Decimal::from_ratio(300u128, 1u128)
+    );
+    assert_eq!(
+        query_amp(&deps, env_at(3 * 86400)),
+        Decimal::from_ratio(500u128, 1u128)
+    );
+
+    // stop the ramp halfway
+    execute(
+        deps.as_mut(),
+        env_at(86400 + 43200),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::StopChangingAmp {}).unwrap(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_amp(&deps, env_at(3 * 86400)),
+        Decimal::from_ratio(300u128, 1u128)
+    );
+}
+
+#[test]
+fn spot_price_and_price_impact() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(60_000_000_000u128))],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    init_pair(&mut deps, 100);
+
+    // failure - the pool is empty
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SpotPrice {
+            base_asset: AssetInfo::Native("uusd".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientLiquidity {});
+
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[
+                Uint128::from(30_000_000_000u128),
+                Uint128::from(30_000_000_000u128),
+            ],
+        )
+        .unwrap();
+
+    // a balanced pool trades at the peg
+    let res: SpotPriceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SpotPrice {
+                base_asset: AssetInfo::Native("uusd".to_string()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.price > Decimal::permille(999));
+    assert!(res.price <= Decimal::one());
+
+    // failure - the asset is not in the pair
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SpotPrice {
+            base_asset: AssetInfo::Native("uluna".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AssetMismatch {});
+
+    // the price impact includes the commission and the curve
+    let offer_asset = Asset {
+        info: AssetInfo::Native("uusd".to_string()),
+        amount: Uint128::from(1_500_000_000u128),
+    };
+    let res: PriceImpactResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PriceImpact {
+                offer_asset: offer_asset.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let (return_amount, _, _) = compute_swap(
+        Uint128::from(30_000_000_000u128),
+        Uint128::from(30_000_000_000u128),
+        offer_asset.amount,
+        100,
+        Decimal::permille(3),
+    )
+    .unwrap();
+    assert_eq!(
+        res.execution_price,
+        Decimal::from_ratio(return_amount, offer_asset.amount)
+    );
+    assert!(res.price_impact > Decimal::permille(3));
+    assert!(res.price_impact < Decimal::percent(1));
+
+    // the scarce asset is priced above the peg
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[
+                Uint128::from(50_000_000_000u128),
+                Uint128::from(10_000_000_000u128),
+            ],
+        )
+        .unwrap();
+    let price = |deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>, base_asset: AssetInfo| {
+        let res: SpotPriceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::SpotPrice { base_asset },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.price
+    };
+    assert!(price(&deps, AssetInfo::Native("uusd".to_string())) < Decimal::one());
+    assert!(price(&deps, AssetInfo::Cw20(Addr::unchecked("asset0000"))) > Decimal::one());
+}
+
+#[test]
+fn simulate_provide_and_withdraw() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    init_pair(&mut deps, 100);
+
+    let assets = |amount0: u128, amount1: u128| {
+        [
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(amount1),
+            },
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(amount0),
+            },
+        ]
+    };
+
+    // failure - the initial deposit must contain both assets
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SimulateProvide {
+            assets: assets(1_000_000_000, 0),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidZeroAmount {});
+
+    // the initial share is the invariant of the deposits minus the locked liquidity
+    let res: SimulateProvideResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateProvide {
+                assets: assets(1_000_000_000, 1_000_000_000),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.share,
+        Uint128::from(2_000_000_000u128 - MINIMUM_LIQUIDITY_AMOUNT)
+    );
+    assert_eq!(res.slippage, Decimal::zero());
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000_000u128))],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[
+                Uint128::from(1_000_000_000u128),
+                Uint128::from(1_000_000_000u128),
+            ],
+        )
+        .unwrap();
+
+    // a single sided deposit matches the executed provide and reports its slippage
+    let res: SimulateProvideResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateProvide {
+                assets: assets(0, 1_000_000_000),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.slippage > Decimal::zero());
+    assert_eq!(res.excess_assets[0].amount, Uint128::zero());
+    assert_eq!(res.excess_assets[1].amount, Uint128::zero());
+
+    let provide_msg = |slippage_tolerance: Decimal| ExecuteMsg::ProvideLiquidity {
+        assets: assets(0, 1_000_000_000),
+        slippage_tolerance: Some(slippage_tolerance),
+        receiver: None,
+        deadline: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        provide_msg((Decimal256::from(res.slippage) * Decimal256::percent(99)).into()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
+
+    let exec_res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        provide_msg(res.slippage + Decimal::permille(1)),
+    )
+    .unwrap();
+    assert_eq!(
+        exec_res.messages.get(1).expect("no message"),
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: res.share,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+
+    // the withdrawal refunds the share of the reserves
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[
+                Uint128::from(1_000_000_000u128),
+                Uint128::from(1_000_000_000u128),
+            ],
+        )
+        .unwrap();
+    let res: SimulateWithdrawResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateWithdraw {
+                lp_amount: Uint128::from(500_000_000u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.refund_assets,
+        [
+            Asset {
+                info: AssetInfo::Native("uusd".to_string()),
+                amount: Uint128::from(250_000_000u128),
+            },
+            Asset {
+                info: AssetInfo::Cw20(Addr::unchecked("asset0000")),
+                amount: Uint128::from(250_000_000u128),
+            },
+        ]
+    );
+
+    // failure - the amount exceeds the supply
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SimulateWithdraw {
+            lp_amount: Uint128::from(2_000_000_001u128),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientLiquidity {});
+}
+
+#[test]
+fn migration() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(123u128))],
+    )]);
+
+    init_pair(&mut deps, 100);
+
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.contract, CONTRACT_NAME);
+    assert_eq!(version.version, CONTRACT_VERSION);
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    // refuse legacy state, the stable pair has no legacy deployments
+    PAIR_STATUS.remove(deps.as_mut().storage);
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(res, Err(ContractError::LegacyStateMigration {}));
+    PAIR_STATUS
+        .save(deps.as_mut().storage, &PairStatus::default())
+        .unwrap();
+
+    RESERVES.remove(deps.as_mut().storage);
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(res, Err(ContractError::LegacyStateMigration {}));
+    RESERVES
+        .save(deps.as_mut().storage, &[Uint128::zero(); 2])
+        .unwrap();
+
+    // refuse downgrades
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(
+        res,
+        Err(ContractError::MigrationDowngrade {
+            current: "99.0.0".to_string(),
+            new: CONTRACT_VERSION.to_string(),
+        })
+    );
+
+    // refuse other contracts
+    set_contract_version(deps.as_mut().storage, "crates.io:prismswap-pair", "1.0.0").unwrap();
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(
+        res,
+        Err(ContractError::ContractNameMismatch {
+            name: "crates.io:prismswap-pair".to_string(),
+            expected: CONTRACT_NAME.to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_compute_current_amp() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(123u128))],
+    )]);
+
+    init_pair(&mut deps, 100);
+
+    let mut config = CONFIG.load(deps.as_ref().storage).unwrap();
+    config.init_amp = 500;
+    config.init_amp_time = 1000;
+    config.next_amp = 100;
+    config.next_amp_time = 2000;
+
+    // decreasing ramps are interpolated as well
+    assert_eq!(compute_current_amp(&config, 1000), 500);
+    assert_eq!(compute_current_amp(&config, 1250), 400);
+    assert_eq!(compute_current_amp(&config, 1500), 300);
+    assert_eq!(compute_current_amp(&config, 2000), 100);
+    assert_eq!(compute_current_amp(&config, 3000), 100);
+}
+
+#[test]
+fn test_compute_protocol_fee_share() {
+    let reserves_last = [Uint128::from(1_000_000u128), Uint128::from(3_000_000u128)];
+
+    // no fee while the protocol fee is off or before the first liquidity event
+    let reserves = [Uint128::from(1_100_000u128), Uint128::from(3_000_000u128)];
+    assert_eq!(
+        compute_protocol_fee_share(
+            100,
+            &reserves,
+            &reserves_last,
+            Uint128::from(1_000_000u128),
+            Decimal::zero()
+        )
+        .unwrap(),
+        Uint128::zero()
+    );
+    assert_eq!(
+        compute_protocol_fee_share(
+            100,
+            &reserves,
+            &[Uint128::zero(); 2],
+            Uint128::from(1_000_000u128),
+            Decimal::percent(50)
+        )
+        .unwrap(),
+        Uint128::zero()
+    );
+
+    // an amp ramp moves the invariant of unchanged reserves without charging a fee
+    assert!(
+        compute_d(200, reserves_last[0], reserves_last[1]).unwrap()
+            != compute_d(100, reserves_last[0], reserves_last[1]).unwrap()
+    );
+    assert_eq!(
+        compute_protocol_fee_share(
+            200,
+            &reserves_last,
+            &reserves_last,
+            Uint128::from(1_000_000u128),
+            Decimal::percent(50)
+        )
+        .unwrap(),
+        Uint128::zero()
+    );
+
+    // the growth of the invariant is shared
+    assert!(!compute_protocol_fee_share(
+        100,
+        &reserves,
+        &reserves_last,
+        Uint128::from(1_000_000u128),
+        Decimal::percent(50)
+    )
+    .unwrap()
+    .is_zero());
+}
+
+#[test]
+fn test_compute_d() {
+    // a balanced pool has an invariant equal to the sum of the reserves
+    let d = compute_d(
+        100,
+        Uint128::from(1_000_000u128),
+        Uint128::from(1_000_000u128),
+    )
+    .unwrap();
+    assert_eq!(d, Uint256::from(2_000_000u128));
+
+    // an imbalanced pool has an invariant lower than the sum of the reserves
+    let d = compute_d(
+        100,
+        Uint128::from(1_500_000u128),
+        Uint128::from(500_000u128),
+    )
+    .unwrap();
+    assert!(d < Uint256::from(2_000_000u128));
+    assert!(d > Uint256::from(1_990_000u128));
+
+    // matches the curve invariant with the leverage A * n^n
+    assert_eq!(d, Uint256::from(1_998_345u128));
+
+    // empty pool
+    let d = compute_d(100, Uint128::zero(), Uint128::zero()).unwrap();
+    assert_eq!(d, Uint256::zero());
+}
+
+#[test]
+fn test_compute_swap() {
+    let pool = Uint128::from(1_000_000_000_000u128);
+
+    // a high amplification keeps the price close to the peg
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        pool,
+        pool,
+        Uint128::from(1_000_000_000u128),
+        100,
+        Decimal::zero(),
+    )
+    .unwrap();
+    assert_eq!(commission_amount, Uint128::zero());
+    assert!(return_amount > Uint128::from(999_900_000u128));
+    assert_eq!(
+        return_amount + spread_amount,
+        Uint128::from(1_000_000_000u128)
+    );
+
+    // a low amplification behaves closer to the constant product curve
+    let (low_amp_return, _, _) = compute_swap(
+        pool,
+        pool,
+        Uint128::from(1_000_000_000u128),
+        1,
+        Decimal::zero(),
+    )
+    .unwrap();
+    assert!(low_amp_return < return_amount);
+
+    // the commission is deducted from the return amount
+    let (return_amount_with_fee, _, commission_amount) = compute_swap(
+        pool,
+        pool,
+        Uint128::from(1_000_000_000u128),
+        100,
+        Decimal::permille(3),
+    )
+    .unwrap();
+    assert_eq!(return_amount_with_fee + commission_amount, return_amount);
+}
+
+#[test]
+fn test_compute_offer_amount() {
+    let offer_pool = Uint128::from(1_200_000_000_000u128);
+    let ask_pool = Uint128::from(800_000_000_000u128);
+    let ask_amount = Uint128::from(1_000_000_000u128);
+
+    let (offer_amount, _, commission_amount) =
+        compute_offer_amount(offer_pool, ask_pool, ask_amount, 100, Decimal::permille(3)).unwrap();
+
+    // swapping the computed offer amount returns (almost) exactly the ask amount
+    let (return_amount, _, swap_commission_amount) = compute_swap(
+        offer_pool,
+        ask_pool,
+        offer_amount,
+        100,
+        Decimal::permille(3),
+    )
+    .unwrap();
+    assert!(return_amount >= ask_amount);
+    assert!(return_amount - ask_amount < Uint128::from(5u128));
+    assert!((commission_amount.u128() as i128 - swap_commission_amount.u128() as i128).abs() < 5);
+}
@@ -6,11 +6,13 @@ use cosmwasm_std::{
     QueryRequest, Response, StdError, StdResult, Uint128, WasmMsg, WasmQuery,
 };
 
+use crate::error::ContractError;
 use crate::operations::execute_swap_operation;
 use crate::state::{Config, CONFIG};
 
 use cw20::Cw20ReceiveMsg;
-use prismswap::asset::{Asset, AssetInfo, PairInfo, PrismSwapAssetInfo};
+use prismswap::assert::assert_deadline;
+use prismswap::asset::{Asset, AssetInfo, PairInfo, PrismSwapAsset, PrismSwapAssetInfo};
 use prismswap::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
 use prismswap::querier::query_pair_info;
 use prismswap::router::{
@@ -43,14 +45,16 @@ pub fn execute(
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ExecuteSwapOperations {
             operations,
             minimum_receive,
             to,
+            deadline,
         } => {
+            assert_deadline(&env, deadline)?;
             for operation in &operations {
                 if let SwapOperation::PrismSwap {
                     offer_asset_info,
@@ -90,14 +94,16 @@ pub fn receive_cw20(
     env: Env,
     _info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let sender = deps.api.addr_validate(&cw20_msg.sender)?;
     match from_binary(&cw20_msg.msg)? {
         Cw20HookMsg::ExecuteSwapOperations {
             operations,
             minimum_receive,
             to,
+            deadline,
         } => {
+            assert_deadline(&env, deadline)?;
             for operation in &operations {
                 if let SwapOperation::PrismSwap {
                     offer_asset_info,
@@ -120,14 +126,14 @@ pub fn execute_swap_operations(
     operations: Vec<SwapOperation>,
     minimum_receive: Option<Uint128>,
     to: Option<Addr>,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let operations_len = operations.len();
     if operations_len == 0 {
-        return Err(StdError::generic_err("must provide operations"));
+        return Err(StdError::generic_err("must provide operations").into());
     }
 
     if operations_len > MAX_SWAP_OPERATIONS {
-        return Err(StdError::generic_err("exceeded swap operations limit"));
+        return Err(StdError::generic_err("exceeded swap operations limit").into());
     }
 
     // Assert the operations are properly set
@@ -181,7 +187,7 @@ fn assert_minimum_receive(
     prev_balance: Uint128,
     minium_receive: Uint128,
     receiver: Addr,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let receiver_balance = asset_info.query_pool(&deps.querier, &receiver)?;
     let swap_amount = receiver_balance.checked_sub(prev_balance)?;
 
@@ -189,7 +195,8 @@ fn assert_minimum_receive(
         return Err(StdError::generic_err(format!(
             "assertion failed; minimum receive amount: {}, swap amount: {}",
             minium_receive, swap_amount
-        )));
+        ))
+        .into());
     }
 
     Ok(Response::default())
@@ -260,15 +267,17 @@ fn simulate_swap_operations(
                     &[offer_asset_info.clone(), ask_asset_info.clone()],
                 )?;
 
+                // the router pays the stability tax when it sends a native offer asset
+                let offer_asset: Asset = Asset {
+                    info: offer_asset_info,
+                    amount: offer_amount,
+                }
+                .deduct_tax(&deps.querier)?;
+
                 let res: SimulationResponse =
                     deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
                         contract_addr: pair_info.contract_addr.to_string(),
-                        msg: to_binary(&PairQueryMsg::Simulation {
-                            offer_asset: Asset {
-                                info: offer_asset_info,
-                                amount: offer_amount,
-                            },
-                        })?,
+                        msg: to_binary(&PairQueryMsg::Simulation { offer_asset })?,
                     }))?;
 
                 offer_amount = res.return_amount;
@@ -317,7 +326,7 @@ fn test_invalid_operations() {
     assert!(assert_operations(&[]).is_err());
 
     // uluna output
-    assert!(assert_operations(&vec![
+    assert!(assert_operations(&[
         SwapOperation::NativeSwap {
             offer_denom: "uusd".to_string(),
             ask_denom: "uluna".to_string(),
@@ -334,7 +343,7 @@ fn test_invalid_operations() {
     .is_ok());
 
     // asset0002 output
-    assert!(assert_operations(&vec![
+    assert!(assert_operations(&[
         SwapOperation::NativeSwap {
             offer_denom: "uusd".to_string(),
             ask_denom: "uluna".to_string(),
@@ -355,7 +364,7 @@ fn test_invalid_operations() {
     .is_ok());
 
     // multiple output token types error
-    assert!(assert_operations(&vec![
+    assert!(assert_operations(&[
         SwapOperation::NativeSwap {
             offer_denom: "uusd".to_string(),
             ask_denom: "ukrw".to_string(),
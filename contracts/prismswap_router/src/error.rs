@@ -0,0 +1,24 @@
+use cosmwasm_std::{OverflowError, StdError};
+use prismswap::error::AssertError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Transaction deadline has expired")]
+    ExpiredDeadline {},
+}
+
+impl From<AssertError> for ContractError {
+    fn from(err: AssertError) -> Self {
+        match err {
+            AssertError::ExpiredDeadline {} => ContractError::ExpiredDeadline {},
+            err => ContractError::Std(StdError::generic_err(err.to_string())),
+        }
+    }
+}
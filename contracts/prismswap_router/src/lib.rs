@@ -1,6 +1,7 @@
 pub mod contract;
 pub mod state;
 
+pub mod error;
 mod operations;
 
 #[cfg(test)]
@@ -1,5 +1,6 @@
-use cosmwasm_std::{Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdError, StdResult};
+use cosmwasm_std::{Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdError};
 
+use crate::error::ContractError;
 use crate::state::{Config, CONFIG};
 
 use prismswap::asset::{Asset, AssetInfo, PairInfo, PrismSwapAsset};
@@ -15,9 +16,9 @@ pub fn execute_swap_operation(
     info: MessageInfo,
     operation: SwapOperation,
     to: Option<String>,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
     if env.contract.address != info.sender {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(StdError::generic_err("unauthorized").into());
     }
 
     let messages: Vec<CosmosMsg<TerraMsgWrapper>> = match operation {
@@ -70,10 +71,12 @@ pub fn execute_swap_operation(
                     query_token_balance(&deps.querier, &contract_addr, &env.contract.address)?
                 }
             };
+            // the stability tax of a native offer asset is paid out of the router balance
             let offer_asset: Asset = Asset {
                 info: offer_asset_info,
                 amount,
-            };
+            }
+            .deduct_tax(&deps.querier)?;
 
             vec![offer_asset.into_swap_msg(&pair_info.contract_addr, None, to)?]
         }
@@ -1,9 +1,10 @@
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Coin, CosmosMsg, StdError, SubMsg, Uint128, WasmMsg,
+    from_binary, to_binary, Addr, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128, WasmMsg,
 };
 
 use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
 use crate::testing::mock_querier::mock_dependencies;
 
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
@@ -55,15 +56,32 @@ fn execute_swap_operations() {
         operations: vec![],
         minimum_receive: None,
         to: None,
+        deadline: None,
     };
 
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "must provide operations"),
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "must provide operations")
+        }
         _ => panic!("DO NOT ENTER HERE"),
     }
 
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: vec![SwapOperation::NativeSwap {
+            offer_denom: "uusd".to_string(),
+            ask_denom: "ukrw".to_string(),
+        }],
+        minimum_receive: None,
+        to: None,
+        deadline: Some(mock_env().block.time.seconds() - 1),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::ExpiredDeadline {});
+
     let msg = ExecuteMsg::ExecuteSwapOperations {
         operations: vec![
             SwapOperation::NativeSwap {
@@ -85,6 +103,7 @@ fn execute_swap_operations() {
         ],
         minimum_receive: Some(Uint128::from(1000000u128)),
         to: None,
+        deadline: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -178,6 +197,7 @@ fn execute_swap_operations() {
             ],
             minimum_receive: None,
             to: Some(Addr::unchecked("addr0002")),
+            deadline: None,
         })
         .unwrap(),
     });
@@ -260,13 +280,16 @@ fn execute_swap_operations() {
         ],
         minimum_receive: Some(Uint128::from(1000000u128)),
         to: None,
+        deadline: None,
     };
 
     let info = mock_info("addr0000", &[]);
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         err,
-        StdError::generic_err("Invalid input: human address too short")
+        ContractError::Std(StdError::generic_err(
+            "Invalid input: human address too short"
+        ))
     );
 }
 
@@ -302,7 +325,9 @@ fn execute_swap_operation() {
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "unauthorized")
+        }
         _ => panic!("DO NOT ENTER HERE"),
     }
 
@@ -374,12 +399,51 @@ fn execute_swap_operation() {
                     belief_price: None,
                     max_spread: None,
                     to: Some("addr0000".to_string()),
+                    deadline: None,
                 })
                 .unwrap()
             })
             .unwrap()
         }))]
     );
+
+    // the stability tax of a native offer is deducted from the sent amount
+    // 990099 = 1000000 / 1.01
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    let msg = ExecuteMsg::ExecuteSwapOperation {
+        operation: SwapOperation::PrismSwap {
+            offer_asset_info: AssetInfo::Native("uusd".to_string()),
+            ask_asset_info: AssetInfo::Cw20(Addr::unchecked("asset")),
+        },
+        to: None,
+    };
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair".to_string(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(990099u128),
+            }],
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::Native("uusd".to_string()),
+                    amount: Uint128::from(990099u128),
+                },
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+            })
+            .unwrap()
+        }))]
+    );
 }
 
 #[test]
@@ -419,7 +483,7 @@ fn query_buy_with_routes() {
     ]);
 
     let res: SimulateSwapOperationsResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        from_binary(&query(deps.as_ref(), mock_env(), msg.clone()).unwrap()).unwrap();
     assert_eq!(
         res,
         SimulateSwapOperationsResponse {
@@ -427,6 +491,21 @@ fn query_buy_with_routes() {
         }
     );
 
+    // the router pays the tax when it sends ukrw to the pair
+    // 990099 = 1000000 / 1.01
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"ukrw".to_string(), &Uint128::from(1000000u128))],
+    );
+    let res: SimulateSwapOperationsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        SimulateSwapOperationsResponse {
+            amount: Uint128::from(990099u128),
+        }
+    );
+
     let msg = QueryMsg::SimulateSwapOperations {
         offer_amount: Uint128::from(1000000u128),
         operations: vec![
@@ -481,7 +560,7 @@ fn assert_minimum_receive_native_token() {
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
             msg,
             "assertion failed; minimum receive amount: 1000001, swap amount: 1000000"
         ),
@@ -516,7 +595,7 @@ fn assert_minimum_receive_token() {
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
             msg,
             "assertion failed; minimum receive amount: 1000001, swap amount: 1000000"
         ),
@@ -534,6 +613,8 @@ fn assert_minimum_receive_token() {
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         err,
-        StdError::generic_err("Invalid input: human address too short")
+        ContractError::Std(StdError::generic_err(
+            "Invalid input: human address too short"
+        ))
     );
 }
@@ -0,0 +1,117 @@
+use cosmwasm_std::{Addr, Env, QuerierWrapper, StdError, Storage};
+use cw2::ContractVersion;
+use semver::Version;
+
+use crate::asset::{Asset, AssetInfo};
+use crate::error::AssertError;
+use crate::pair_status::{PairStatus, PAIR_STATUS};
+use crate::querier::query_pause_info;
+
+/// ## Description
+/// Checks that the block time has not passed the given deadline.
+/// ## Params
+/// * **env** is the object of type [`Env`].
+///
+/// * **deadline** is the optional block time in seconds after which the operation is rejected.
+pub fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), AssertError> {
+    if let Some(deadline) = deadline {
+        if env.block.time.seconds() > deadline {
+            return Err(AssertError::ExpiredDeadline {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Checks that every refunded asset is at least the minimum amount given for it.
+/// ## Params
+/// * **refund_assets** are the assets withdrawn from the pool.
+///
+/// * **min_assets** are the optional minimum amounts of the assets.
+pub fn assert_min_assets(
+    refund_assets: &[Asset],
+    min_assets: &Option<[Asset; 2]>,
+) -> Result<(), AssertError> {
+    if let Some(min_assets) = min_assets {
+        for min_asset in min_assets.iter() {
+            let refund_asset = refund_assets
+                .iter()
+                .find(|asset| asset.info == min_asset.info)
+                .ok_or(AssertError::AssetMismatch {})?;
+
+            if refund_asset.amount < min_asset.amount {
+                return Err(AssertError::MinWithdrawAssertion {});
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Checks that the pair is still registered in the factory and that swaps and deposits are not
+/// paused on it. The pair status is the one last pushed by the factory, while the global pause
+/// is queried from the factory so that it takes effect on every pair at once.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **querier** is the object of type [`QuerierWrapper`].
+///
+/// * **factory** is the object of type [`Addr`] of the pair factory.
+///
+/// * **asset_infos** are the assets of the pair.
+pub fn assert_pair_active(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    factory: &Addr,
+    asset_infos: &[AssetInfo; 2],
+) -> Result<(), AssertError> {
+    let status: PairStatus = PAIR_STATUS.load(storage)?;
+    if status.deregistered {
+        return Err(AssertError::Deregistered {});
+    }
+
+    if status.paused || query_pause_info(querier, factory, asset_infos)?.global_paused {
+        return Err(AssertError::Paused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Checks that the stored contract can be migrated to the given version of the code.
+/// Returns an [`AssertError`] on a contract name mismatch or a version downgrade.
+/// ## Params
+/// * **stored** is the object of type [`ContractVersion`] saved by the running code.
+///
+/// * **contract_name** is the cw2 name of the contract.
+///
+/// * **contract_version** is the cw2 version of the new code.
+pub fn assert_migration_version(
+    stored: &ContractVersion,
+    contract_name: &str,
+    contract_version: &str,
+) -> Result<(), AssertError> {
+    if stored.contract != contract_name {
+        return Err(AssertError::ContractNameMismatch {
+            name: stored.contract.clone(),
+            expected: contract_name.to_string(),
+        });
+    }
+
+    let current = parse_version(&stored.version)?;
+    let new = parse_version(contract_version)?;
+    if current > new {
+        return Err(AssertError::MigrationDowngrade {
+            current: stored.version.clone(),
+            new: contract_version.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn parse_version(version: &str) -> Result<Version, StdError> {
+    Version::parse(version).map_err(|e| StdError::generic_err(e.to_string()))
+}
@@ -1,10 +1,11 @@
 use cw20::Cw20ExecuteMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use terra_cosmwasm::TerraMsgWrapper;
+use terra_cosmwasm::{TerraMsgWrapper, TerraQuerier};
 
+use crate::factory::PairType;
 use crate::pair::ExecuteMsg as PairExecuteMsg;
-use crate::querier::{query_balance, query_token_balance};
+use crate::querier::{query_balance, query_token_balance, query_token_symbol};
 use cosmwasm_std::{
     to_binary, Addr, Api, Coin, CosmosMsg, Decimal, MessageInfo, QuerierWrapper, StdError,
     StdResult, Uint128, WasmMsg,
@@ -22,6 +23,9 @@ pub struct PairInfo {
     pub contract_addr: Addr,
     /// pair liquidity token
     pub liquidity_token: Addr,
+    /// the type of pair available in [`PairType`], pairs created before the registry are xyk
+    #[serde(default)]
+    pub pair_type: PairType,
 }
 
 impl PairInfo {
@@ -123,8 +127,21 @@ pub trait PrismSwapAsset {
     ) -> StdResult<CosmosMsg<TerraMsgWrapper>>;
     fn assert_sent_native_token_balance(&self, info: &MessageInfo) -> StdResult<()>;
     fn to_string_legacy(&self) -> String;
+    fn compute_tax(&self, querier: &QuerierWrapper) -> StdResult<Uint128>;
+    fn compute_reverse_tax(&self, querier: &QuerierWrapper) -> StdResult<Uint128>;
+    fn deduct_tax(&self, querier: &QuerierWrapper) -> StdResult<Asset>;
+    fn transfer_msg_with_tax<A: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        to: A,
+    ) -> StdResult<CosmosMsg>;
 }
 
+/// The native denom that is exempt from the stability tax
+const TAX_EXEMPT_DENOM: &str = "uluna";
+
+const DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
+
 impl PrismSwapAsset for Asset {
     fn into_swap_msg(
         self,
@@ -147,6 +164,7 @@ impl PrismSwapAsset for Asset {
                     belief_price: None,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             })),
             AssetInfo::Cw20(contract_addr) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -160,6 +178,7 @@ impl PrismSwapAsset for Asset {
                         belief_price: None,
                         max_spread,
                         to,
+                        deadline: None,
                     })?,
                 })?,
             })),
@@ -191,4 +210,137 @@ impl PrismSwapAsset for Asset {
     fn to_string_legacy(&self) -> String {
         format!("{}:{}", self.info.to_string_legacy(), self.amount)
     }
+
+    /// ## Description
+    /// Returns the stability tax deducted from the amount when it is sent by a contract.
+    /// Tokens and the tax exempt denom are not taxed.
+    /// ## Params
+    /// * **self** is the type of the caller object.
+    ///
+    /// * **querier** is the object of type [`QuerierWrapper`].
+    fn compute_tax(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+        match &self.info {
+            AssetInfo::Native(denom) if denom != TAX_EXEMPT_DENOM => {
+                let (tax_rate, tax_cap) = query_tax_params(querier, denom)?;
+                compute_deducted_tax(self.amount, tax_rate, tax_cap)
+            }
+            _ => Ok(Uint128::zero()),
+        }
+    }
+
+    /// ## Description
+    /// Returns the stability tax that must be added on top of the amount so that the recipient
+    /// receives the full amount after the tax is deducted.
+    /// ## Params
+    /// * **self** is the type of the caller object.
+    ///
+    /// * **querier** is the object of type [`QuerierWrapper`].
+    fn compute_reverse_tax(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+        match &self.info {
+            AssetInfo::Native(denom) if denom != TAX_EXEMPT_DENOM => {
+                let (tax_rate, tax_cap) = query_tax_params(querier, denom)?;
+                let mut tax = std::cmp::min(self.amount * tax_rate, tax_cap);
+                // the tax rounds down, so the gross amount may fall one short
+                let deducted_tax = compute_deducted_tax(self.amount + tax, tax_rate, tax_cap)?;
+                if deducted_tax > tax {
+                    tax += Uint128::new(1);
+                }
+                Ok(tax)
+            }
+            _ => Ok(Uint128::zero()),
+        }
+    }
+
+    /// ## Description
+    /// Returns a copy of the asset with the stability tax deducted from its amount.
+    /// ## Params
+    /// * **self** is the type of the caller object.
+    ///
+    /// * **querier** is the object of type [`QuerierWrapper`].
+    fn deduct_tax(&self, querier: &QuerierWrapper) -> StdResult<Asset> {
+        Ok(Asset {
+            info: self.info.clone(),
+            amount: self.amount.checked_sub(self.compute_tax(querier)?)?,
+        })
+    }
+
+    /// ## Description
+    /// Returns a message transferring the asset to the recipient with the stability tax deducted
+    /// from the sent amount, so that the sender is never charged more than the amount.
+    /// ## Params
+    /// * **self** is the type of the caller object.
+    ///
+    /// * **querier** is the object of type [`QuerierWrapper`].
+    ///
+    /// * **to** is the recipient of the transfer.
+    fn transfer_msg_with_tax<A: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        to: A,
+    ) -> StdResult<CosmosMsg> {
+        self.deduct_tax(querier)?.transfer_msg(to)
+    }
+}
+
+/// ## Description
+/// Returns the current stability tax rate and the tax cap of the denom.
+/// ## Params
+/// * **querier** is the object of type [`QuerierWrapper`].
+///
+/// * **denom** is the native denom that is taxed.
+fn query_tax_params(querier: &QuerierWrapper, denom: &str) -> StdResult<(Decimal, Uint128)> {
+    let terra_querier = TerraQuerier::new(querier);
+    let tax_rate: Decimal = terra_querier.query_tax_rate()?.rate;
+    let tax_cap: Uint128 = terra_querier.query_tax_cap(denom.to_string())?.cap;
+    Ok((tax_rate, tax_cap))
+}
+
+/// ## Description
+/// Returns the tax included in the amount, capped by the tax cap.
+/// ## Params
+/// * **amount** is the amount sent including the tax.
+///
+/// * **tax_rate** is the stability tax rate.
+///
+/// * **tax_cap** is the maximum tax charged on a transfer.
+fn compute_deducted_tax(
+    amount: Uint128,
+    tax_rate: Decimal,
+    tax_cap: Uint128,
+) -> StdResult<Uint128> {
+    let amount_without_tax = amount.multiply_ratio(
+        DECIMAL_FRACTION,
+        DECIMAL_FRACTION * tax_rate + DECIMAL_FRACTION,
+    );
+    Ok(std::cmp::min(
+        amount.checked_sub(amount_without_tax)?,
+        tax_cap,
+    ))
+}
+
+// we need 6 for xPRISM
+const TOKEN_SYMBOL_MAX_LENGTH: usize = 6;
+
+/// ## Description
+/// Returns the name of the liquidity token formatted from the symbols of the pool assets.
+/// ## Params
+/// * **asset_infos** is an array that contains two items of type [`AssetInfo`].
+///
+/// * **querier** is the object of type [`QuerierWrapper`].
+pub fn format_lp_token_name(
+    asset_infos: &[AssetInfo; 2],
+    querier: &QuerierWrapper,
+) -> StdResult<String> {
+    let mut short_symbols: Vec<String> = vec![];
+    for asset_info in asset_infos {
+        let short_symbol: String = match asset_info {
+            AssetInfo::Native(denom) => denom.chars().take(TOKEN_SYMBOL_MAX_LENGTH).collect(),
+            AssetInfo::Cw20(contract_addr) => {
+                let token_symbol = query_token_symbol(querier, contract_addr)?;
+                token_symbol.chars().take(TOKEN_SYMBOL_MAX_LENGTH).collect()
+            }
+        };
+        short_symbols.push(short_symbol);
+    }
+    Ok(format!("{}-{}-LP", short_symbols[0], short_symbols[1]).to_uppercase())
 }
@@ -0,0 +1,172 @@
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+use crate::pair::{
+    MAX_AMP, MAX_AMP_CHANGE, MAX_HOOK_GAS, MAX_PAIR_HOOKS, MINIMUM_LIQUIDITY_AMOUNT,
+    MIN_AMP_CHANGING_TIME, MIN_ORDER_AMOUNT,
+};
+
+/// ## Description
+/// This enum describes the errors of the assertions shared by the contracts. The contracts
+/// map them onto their own error variants.
+#[derive(Error, Debug, PartialEq)]
+pub enum AssertError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Asset mismatch")]
+    AssetMismatch {},
+
+    #[error("Min withdraw assertion")]
+    MinWithdrawAssertion {},
+
+    #[error("Transaction deadline has expired")]
+    ExpiredDeadline {},
+
+    #[error("The pair is paused")]
+    Paused {},
+
+    #[error("The pair is deregistered, only withdrawals are allowed")]
+    Deregistered {},
+
+    #[error("Cannot migrate from {name} to {expected}")]
+    ContractNameMismatch { name: String, expected: String },
+
+    #[error("Cannot migrate from version {current} to the older version {new}")]
+    MigrationDowngrade { current: String, new: String },
+}
+
+/// ## Description
+/// This enum describes the errors of the pair contracts.
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid zero amount")]
+    InvalidZeroAmount {},
+
+    #[error("Max spread assertion")]
+    MaxSpreadAssertion {},
+
+    #[error("Max slippage assertion")]
+    MaxSlippageAssertion {},
+
+    #[error("Max offer assertion")]
+    MaxOfferAssertion {},
+
+    #[error("Asset mismatch")]
+    AssetMismatch {},
+
+    #[error("Min withdraw assertion")]
+    MinWithdrawAssertion {},
+
+    #[error("Transaction deadline has expired")]
+    ExpiredDeadline {},
+
+    #[error("The pair is paused")]
+    Paused {},
+
+    #[error("The pair is deregistered, only withdrawals are allowed")]
+    Deregistered {},
+
+    #[error("Too small offer amount")]
+    TooSmallOfferAmount {},
+
+    #[error("Doubling assets in asset infos")]
+    DoublingAssets {},
+
+    #[error("ParseReplyError")]
+    ParseReplyError {},
+
+    #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY_AMOUNT)]
+    MinimumLiquidityAmountError {},
+
+    #[error("Cannot provide a single asset to an empty pool")]
+    EmptyPool {},
+
+    #[error("Insufficient liquidity")]
+    InsufficientLiquidity {},
+
+    #[error("A flash swap is in progress")]
+    FlashSwapInProgress {},
+
+    #[error("The flash swap did not restore the pool invariant")]
+    FlashSwapInvariant {},
+
+    #[error("Operation non supported")]
+    NonSupported {},
+
+    #[error("Hook already registered")]
+    HookAlreadyRegistered {},
+
+    #[error("Hook not found")]
+    HookNotFound {},
+
+    #[error("Cannot register more than {} hooks", MAX_PAIR_HOOKS)]
+    TooManyHooks {},
+
+    #[error("Hook gas limit must be between 1 and {}", MAX_HOOK_GAS)]
+    InvalidHookGasLimit {},
+
+    #[error("Invalid order price")]
+    InvalidOrderPrice {},
+
+    #[error("Order amount must be at least {}", MIN_ORDER_AMOUNT)]
+    OrderTooSmall {},
+
+    #[error("Cannot migrate from {name} to {expected}")]
+    ContractNameMismatch { name: String, expected: String },
+
+    #[error("Cannot migrate from version {current} to the older version {new}")]
+    MigrationDowngrade { current: String, new: String },
+
+    #[error("Cannot migrate a pair without the stable pair state")]
+    LegacyStateMigration {},
+
+    #[error("You need to provide init params")]
+    InitParamsNotFound {},
+
+    #[error(
+        "Amp coefficient must be greater than 0 and less than or equal to {}",
+        MAX_AMP
+    )]
+    IncorrectAmp {},
+
+    #[error(
+        "The difference between the old and new amp value must not exceed {} times",
+        MAX_AMP_CHANGE
+    )]
+    MaxAmpChangeAssertion {},
+
+    #[error(
+        "Amp coefficient cannot be changed more often than once per {} seconds",
+        MIN_AMP_CHANGING_TIME
+    )]
+    MinAmpChangingTimeAssertion {},
+}
+
+impl From<AssertError> for ContractError {
+    fn from(err: AssertError) -> Self {
+        match err {
+            AssertError::Std(err) => ContractError::Std(err),
+            AssertError::AssetMismatch {} => ContractError::AssetMismatch {},
+            AssertError::MinWithdrawAssertion {} => ContractError::MinWithdrawAssertion {},
+            AssertError::ExpiredDeadline {} => ContractError::ExpiredDeadline {},
+            AssertError::Paused {} => ContractError::Paused {},
+            AssertError::Deregistered {} => ContractError::Deregistered {},
+            AssertError::ContractNameMismatch { name, expected } => {
+                ContractError::ContractNameMismatch { name, expected }
+            }
+            AssertError::MigrationDowngrade { current, new } => {
+                ContractError::MigrationDowngrade { current, new }
+            }
+        }
+    }
+}
@@ -1,8 +1,9 @@
+use std::fmt::{Display, Formatter, Result};
 use std::str::FromStr;
 
 use crate::asset::PairInfo;
-use cosmwasm_std::{Addr, Decimal};
-use cw_asset::AssetInfo;
+use cosmwasm_std::{Addr, Binary, Decimal};
+use cw_asset::{Asset, AssetInfo};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -35,20 +36,80 @@ impl Default for FeeConfig {
     }
 }
 
+/// ## Description
+/// This enum describes the available pair types, each one maps to its own pair contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairType {
+    /// constant product pair
+    Xyk {},
+    /// stableswap pair for pegged assets
+    Stable {},
+    /// custom pair type identified by its name
+    Custom(String),
+}
+
+impl Default for PairType {
+    fn default() -> Self {
+        PairType::Xyk {}
+    }
+}
+
+impl Display for PairType {
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        match self {
+            PairType::Xyk {} => fmt.write_str("xyk"),
+            PairType::Stable {} => fmt.write_str("stable"),
+            PairType::Custom(pair_type) => fmt.write_str(format!("custom-{}", pair_type).as_str()),
+        }
+    }
+}
+
+/// ## Description
+/// This structure describes the registry settings of a pair type.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairTypeConfig {
+    /// the type of pair available in [`PairType`]
+    pub pair_type: PairType,
+    /// Pair contract code identifier
+    pub code_id: u64,
+    /// default [`FeeConfig`] settings for pairs of this type
+    pub fee_config: FeeConfig,
+    /// whether new pairs of this type can be created
+    pub allow_creation: bool,
+}
+
+/// ## Description
+/// This enum describes the listing status of an asset for permissionless pair creation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetStatus {
+    /// pairs can be created against the asset
+    Allowed,
+    /// no pair can be created with the asset
+    Denied,
+}
+
 /// ## Description
 /// This structure describes the basic settings for creating a contract.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     /// CW20 token contract code identifier
     pub token_code_id: u64,
-    /// Pair contract code identifier
-    pub pair_code_id: u64,
+    /// the registered pair types, see [`PairTypeConfig`]
+    pub pair_configs: Vec<PairTypeConfig>,
     /// contract address to send fees to
     pub collector: Addr,
     /// address allowed to create pairs and update configuration
     pub owner: Addr,
-    /// address assigned as admin to instantiated pairs
+    /// address allowed to migrate the pairs, the factory itself is the admin of the pairs
     pub pairs_admin: Addr,
+    /// address allowed to pause the pairs
+    pub pauser: Addr,
+    /// whether any address can create pairs
+    pub permissionless: bool,
+    /// fee paid to the collector by non-owner pair creators
+    pub creation_fee: Option<Asset>,
 }
 
 /// ## Description
@@ -60,14 +121,33 @@ pub enum ExecuteMsg {
     UpdateConfig {
         /// CW20 token contract code identifier
         token_code_id: Option<u64>,
-        /// Pair contract code identifier
-        pair_code_id: Option<u64>,
-        /// contract address to send fees to
+        /// contract address to send fees to, pushed to the registered pairs by [`ExecuteMsg::SyncPairs`]
         collector: Option<Addr>,
         /// address allowed to create pairs and update configuration
         owner: Option<Addr>,
-        /// address assigned as admin to instantiated pairs
+        /// address allowed to migrate the pairs, the factory itself is the admin of the pairs
         pairs_admin: Option<Addr>,
+        /// address allowed to pause the pairs
+        pauser: Option<Addr>,
+        /// whether any address can create pairs
+        permissionless: Option<bool>,
+    },
+    /// UpdateCreationFee sets the fee paid by non-owner pair creators, removes it if empty
+    UpdateCreationFee {
+        /// the type of asset available in [`Asset`]
+        creation_fee: Option<Asset>,
+    },
+    /// UpdateAssetStatus allows or denies an asset for permissionless pair creation, clears it if empty
+    UpdateAssetStatus {
+        /// the type of asset info available in [`AssetInfo`]
+        asset_info: AssetInfo,
+        /// the new [`AssetStatus`] of the asset
+        status: Option<AssetStatus>,
+    },
+    /// UpdatePairTypeConfig registers a new pair type or updates an existing one
+    UpdatePairTypeConfig {
+        /// new [`PairTypeConfig`] settings for the pair type
+        config: PairTypeConfig,
     },
     /// UpdatePairConfig updates configs of pair
     UpdatePairConfig {
@@ -78,15 +158,52 @@ pub enum ExecuteMsg {
     },
     /// CreatePair instantiates pair contract
     CreatePair {
+        /// the type of pair available in [`PairType`]
+        pair_type: PairType,
         /// the type of asset infos available in [`AssetInfo`]
         asset_infos: [AssetInfo; 2],
-        /// [`FeeConfig`] settings for pair, default fees if empty
+        /// [`FeeConfig`] settings for pair, default fees of the pair type if empty
         fee_config: Option<FeeConfig>,
+        /// optional binary serialised parameters for custom pool types
+        init_params: Option<Binary>,
     },
-    /// Deregister removes a previously created pair
+    /// MigratePairs migrates a page of the pairs registered with `code_id` to that code. The
+    /// pairs not administered by the factory are skipped, the pairs admin hands the legacy pairs
+    /// over by updating their admin to the factory. Only the pairs admin can execute it
+    MigratePairs {
+        /// the code identifier of a registered pair type to migrate to
+        code_id: u64,
+        /// the item to start reading from. It is an [`Option`] type that accepts two [`AssetInfo`] elements.
+        start_after: Option<[AssetInfo; 2]>,
+        /// the number of items to be read. It is an [`Option`] type.
+        limit: Option<u32>,
+        /// binary serialised migration message sent to every pair
+        msg: Binary,
+    },
+    /// SyncPairs pushes the collector of the config to a page of the registered pairs, the pairs
+    /// keep the previous collector until it has run over every page. Only the owner can execute it
+    SyncPairs {
+        /// the item to start reading from. It is an [`Option`] type that accepts two [`AssetInfo`] elements.
+        start_after: Option<[AssetInfo; 2]>,
+        /// the number of items to be read. It is an [`Option`] type.
+        limit: Option<u32>,
+    },
+    /// UpdatePauseStatus pauses or resumes swaps and deposits on a pair, or on every pair if
+    /// `asset_infos` is empty, in which case the pairs query the pause from the factory. Only
+    /// the pauser can execute it
+    UpdatePauseStatus {
+        /// the assets that identify the pair, all pairs if empty
+        asset_infos: Option<[AssetInfo; 2]>,
+        /// whether swaps and deposits are halted
+        paused: bool,
+    },
+    /// Deregister removes a previously created pair and archives it, the pair only allows
+    /// withdrawals afterwards
     Deregister {
         /// the type of asset infos available in [`AssetInfo`]
         asset_infos: [AssetInfo; 2],
+        /// the reason recorded in the archive
+        reason: Option<String>,
     },
 }
 
@@ -121,10 +238,38 @@ pub enum QueryMsg {
         /// the number of items to be read. It is an [`Option`] type.
         limit: Option<u32>,
     },
+    /// PairCodeIds returns the code identifiers the pairs are running in a [`PairCodeIdsResponse`] object
+    PairCodeIds {
+        /// the item to start reading from. It is an [`Option`] type that accepts two [`AssetInfo`] elements.
+        start_after: Option<[AssetInfo; 2]>,
+        /// the number of items to be read. It is an [`Option`] type.
+        limit: Option<u32>,
+    },
+    /// PairTypes returns the registered pair types in a [`PairTypesResponse`] object
+    PairTypes {},
+    /// AssetStatus returns the listing status of an asset in an [`AssetStatusResponse`] object
+    AssetStatus {
+        /// the type of asset info available in [`AssetInfo`]
+        asset_info: AssetInfo,
+    },
     /// FeeInfo returns settings that specified in custom [`FeeInfoResponse`] structure
     FeeInfo {
         /// the type of asset infos available in [`AssetInfo`]
         asset_infos: [AssetInfo; 2],
+        /// the optional pair address, the settings of a deregistered pair are read from the archive
+        pair_address: Option<String>,
+    },
+    /// PauseInfo returns whether a pair is paused in a [`PauseInfoResponse`] object
+    PauseInfo {
+        /// the type of asset infos available in [`AssetInfo`]
+        asset_infos: [AssetInfo; 2],
+    },
+    /// DeregisteredPairs returns an array of the archived pairs in a [`DeregisteredPairsResponse`] object
+    DeregisteredPairs {
+        /// the pair address to start reading from. It is an [`Option`] type.
+        start_after: Option<String>,
+        /// the number of items to be read. It is an [`Option`] type.
+        limit: Option<u32>,
     },
 }
 
@@ -136,25 +281,66 @@ pub struct ConfigResponse {
     pub owner: Addr,
     /// CW20 token contract code identifier
     pub token_code_id: u64,
-    /// Pair contract code identifier
-    pub pair_code_id: u64,
     /// Contract address to send fees to
     pub collector: Addr,
-    /// Address assigned as admin to instantiated pairs
+    /// Address allowed to migrate the pairs, the factory itself is the admin of the pairs
     pub pairs_admin: Addr,
+    /// Address allowed to pause the pairs
+    pub pauser: Addr,
+    /// Whether any address can create pairs
+    pub permissionless: bool,
+    /// Fee paid to the collector by non-owner pair creators
+    pub creation_fee: Option<Asset>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PairConfigResponse {
     pub pair_info: PairInfo,
     pub fee_config: FeeConfig,
+    pub pair_type: PairType,
+    pub creator: Addr,
+}
+
+/// ## Description
+/// A custom struct for each query response that returns the listing status of an asset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetStatusResponse {
+    pub asset_info: AssetInfo,
+    pub status: Option<AssetStatus>,
+}
+
+/// ## Description
+/// A custom struct for each query response that returns the registered pair types.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairTypesResponse {
+    pub pair_types: Vec<PairTypeConfig>,
+}
+
+/// ## Description
+/// A custom struct for each query response that returns the code identifier of a pair.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairCodeIdResponse {
+    pub pair_address: Addr,
+    pub pair_type: PairType,
+    pub code_id: u64,
+}
+
+/// ## Description
+/// A custom struct for each query response that returns the code identifiers of the pairs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairCodeIdsResponse {
+    pub pairs: Vec<PairCodeIdResponse>,
 }
 
 /// ## Description
 /// This structure describes a migration message.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {
-    pub pairs_admin: Addr,
+    /// the optional new address allowed to migrate the pairs, kept from the config if empty
+    pub pairs_admin: Option<Addr>,
+    /// the number of legacy pairs converted by this migration, the factory is migrated again
+    /// until no legacy pair remains
+    pub limit: Option<u32>,
 }
 
 /// ## Description
@@ -174,3 +360,41 @@ pub struct FeeInfoResponse {
     pub fee_config: FeeConfig,
     pub collector: Addr,
 }
+
+/// ## Description
+/// A custom struct for each query response that returns the pause status of a pair.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseInfoResponse {
+    /// whether every pair is paused
+    pub global_paused: bool,
+    /// whether the pair is paused on its own
+    pub pair_paused: bool,
+    /// the pair currently registered for the assets, empty if there is none
+    pub registered_pair: Option<Addr>,
+}
+
+/// ## Description
+/// A custom struct for each query response that returns an archived pair.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DeregisteredPairResponse {
+    pub pair_address: Addr,
+    pub asset_infos: [AssetInfo; 2],
+    pub pair_type: PairType,
+    pub fee_config: FeeConfig,
+    /// the block height the pair was deregistered at
+    pub deregistered_at: u64,
+    pub reason: Option<String>,
+}
+
+/// ## Description
+/// A custom struct for each query response that returns an array of archived pairs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DeregisteredPairsResponse {
+    pub pairs: Vec<DeregisteredPairResponse>,
+}
+
+impl PauseInfoResponse {
+    pub fn is_paused(&self) -> bool {
+        self.global_paused || self.pair_paused
+    }
+}
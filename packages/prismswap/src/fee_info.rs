@@ -0,0 +1,90 @@
+use cosmwasm_std::{Addr, QuerierWrapper, Response, StdResult, Storage};
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::AssetInfo;
+use crate::factory::{FeeConfig, FeeInfoResponse};
+use crate::querier::query_fee_info;
+
+/// the fee settings pushed by the factory to the pairs
+pub const FEE_INFO: Item<FeeInfo> = Item::new("fee_info");
+
+/// ## Description
+/// This structure holds the fee settings cached by a pair.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeInfo {
+    /// the [`FeeConfig`] settings of the pair
+    pub fee_config: FeeConfig,
+    /// the protocol fee collector address
+    pub collector: Addr,
+}
+
+/// ## Description
+/// Updates the fee settings cached by a pair. The pair checks the message is sent by its
+/// factory before calling it.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **fee_config** is an [`Option`] field of type [`FeeConfig`] with the new fee settings.
+///
+/// * **collector** is an [`Option`] field of type [`Addr`] with the new protocol fee collector.
+pub fn update_fee_config(
+    storage: &mut dyn Storage,
+    fee_config: Option<FeeConfig>,
+    collector: Option<Addr>,
+) -> StdResult<Response> {
+    let mut fee_info: FeeInfo = FEE_INFO.load(storage)?;
+    if let Some(fee_config) = fee_config {
+        fee_info.fee_config = fee_config;
+    }
+
+    if let Some(collector) = collector {
+        fee_info.collector = collector;
+    }
+
+    FEE_INFO.save(storage, &fee_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_fee_config"),
+        ("total_fee", &fee_info.fee_config.total_fee.to_string()),
+        (
+            "protocol_fee",
+            &fee_info.fee_config.protocol_fee.to_string(),
+        ),
+        ("collector", fee_info.collector.as_str()),
+    ]))
+}
+
+/// ## Description
+/// Caches the fee settings of a pair deployed before the factory pushed them to the pairs.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **querier** is the object of type [`QuerierWrapper`].
+///
+/// * **factory** is the object of type [`Addr`] of the pair factory.
+///
+/// * **asset_infos** are the assets of the pair.
+///
+/// * **pair_address** is the object of type [`Addr`] of the pair.
+pub fn migrate_fee_info(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    factory: &Addr,
+    asset_infos: &[AssetInfo; 2],
+    pair_address: &Addr,
+) -> StdResult<()> {
+    if FEE_INFO.may_load(storage)?.is_some() {
+        return Ok(());
+    }
+
+    let fee_info: FeeInfoResponse = query_fee_info(querier, factory, asset_infos, pair_address)?;
+    FEE_INFO.save(
+        storage,
+        &FeeInfo {
+            fee_config: fee_info.fee_config,
+            collector: fee_info.collector,
+        },
+    )
+}
@@ -0,0 +1,123 @@
+use cosmwasm_std::{Addr, DepsMut, MessageInfo, ReplyOn, Response, StdResult, Storage, SubMsg};
+use cw_storage_plus::Item;
+
+use crate::error::ContractError;
+use crate::pair::{Hook, PairHookMsg, MAX_HOOK_GAS, MAX_PAIR_HOOKS};
+use crate::querier::query_factory_config;
+
+/// the contracts receiving a hook message after each swap and liquidity event
+pub const HOOKS: Item<Vec<Hook>> = Item::new("hooks");
+
+/// the reply id of the hook sub-messages, only sent back on failure
+pub const HOOK_REPLY_ID: u64 = 2;
+
+/// ## Description
+/// Returns the sub-messages sending the hook message to each registered hook. A failing hook
+/// is reverted on its own and reported by the reply, the pair action still succeeds.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **msg** is the object of type [`PairHookMsg`] sent to the hooks.
+pub fn hook_messages(storage: &dyn Storage, msg: PairHookMsg) -> StdResult<Vec<SubMsg>> {
+    HOOKS
+        .may_load(storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|hook| {
+            Ok(SubMsg {
+                id: HOOK_REPLY_ID,
+                msg: msg.clone().into_cosmos_msg(hook.contract_addr)?,
+                gas_limit: Some(hook.gas_limit),
+                reply_on: ReplyOn::Error,
+            })
+        })
+        .collect()
+}
+
+/// ## Description
+/// Registers a contract receiving a [`PairHookMsg`] after each swap and liquidity event.
+/// Only the factory owner can execute it.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **factory** is the object of type [`Addr`] of the pair factory.
+///
+/// * **contract_addr** is the hook contract address.
+///
+/// * **gas_limit** is the gas limit of the hook sub-message, capped at [`MAX_HOOK_GAS`].
+pub fn add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    factory: &Addr,
+    contract_addr: String,
+    gas_limit: u64,
+) -> Result<Response, ContractError> {
+    // permission check
+    let factory_config = query_factory_config(&deps.querier, factory)?;
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if gas_limit == 0 || gas_limit > MAX_HOOK_GAS {
+        return Err(ContractError::InvalidHookGasLimit {});
+    }
+
+    let contract_addr = deps.api.addr_validate(&contract_addr)?;
+    let mut hooks: Vec<Hook> = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if hooks.iter().any(|hook| hook.contract_addr == contract_addr) {
+        return Err(ContractError::HookAlreadyRegistered {});
+    }
+    if hooks.len() >= MAX_PAIR_HOOKS {
+        return Err(ContractError::TooManyHooks {});
+    }
+
+    hooks.push(Hook {
+        contract_addr: contract_addr.clone(),
+        gas_limit,
+    });
+    HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "add_hook"),
+        ("contract_addr", contract_addr.as_str()),
+        ("gas_limit", &gas_limit.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Unregisters a hook contract. Only the factory owner can execute it.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **factory** is the object of type [`Addr`] of the pair factory.
+///
+/// * **contract_addr** is the hook contract address.
+pub fn remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    factory: &Addr,
+    contract_addr: String,
+) -> Result<Response, ContractError> {
+    // permission check
+    let factory_config = query_factory_config(&deps.querier, factory)?;
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut hooks: Vec<Hook> = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    let index = hooks
+        .iter()
+        .position(|hook| hook.contract_addr.as_str() == contract_addr)
+        .ok_or(ContractError::HookNotFound {})?;
+    hooks.remove(index);
+    HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "remove_hook"),
+        ("contract_addr", contract_addr.as_str()),
+    ]))
+}
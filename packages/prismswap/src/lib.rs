@@ -1,6 +1,16 @@
+pub mod assert;
 pub mod asset;
+pub mod error;
 pub mod factory;
+pub mod fee_info;
+pub mod hooks;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mock_querier;
 pub mod pair;
+pub mod pair_status;
+pub mod parse_reply;
+pub mod pool;
 pub mod querier;
 pub mod router;
+pub mod stats;
 pub mod token;
@@ -1,15 +1,17 @@
+use crate::factory::{
+    ConfigResponse as FactoryConfigResponse, FeeConfig, FeeInfoResponse, PauseInfoResponse,
+};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     from_binary, from_slice, to_binary, Addr, Coin, ContractResult, Decimal, OwnedDeps, Querier,
     QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
-use prismswap::factory::{FeeConfig, FeeInfoResponse};
 use std::collections::HashMap;
 use std::str::FromStr;
 
+use crate::factory::QueryMsg::{Config as FactoryConfig, FeeInfo, PauseInfo};
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
-use prismswap::factory::QueryMsg::FeeInfo;
-use terra_cosmwasm::TerraQueryWrapper;
+use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
@@ -29,6 +31,10 @@ pub fn mock_dependencies(
 pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     token_querier: TokenQuerier,
+    tax_querier: TaxQuerier,
+    paused: bool,
+    global_paused: bool,
+    deregistered: bool,
 }
 
 #[derive(Clone, Default)]
@@ -60,6 +66,30 @@ pub(crate) fn balances_to_map(
     balances_map
 }
 
+#[derive(Clone, Default)]
+pub struct TaxQuerier {
+    rate: Decimal,
+    // this lets us iterate over all pairs that match the first string
+    caps: HashMap<String, Uint128>,
+}
+
+impl TaxQuerier {
+    pub fn new(rate: Decimal, caps: &[(&String, &Uint128)]) -> Self {
+        TaxQuerier {
+            rate,
+            caps: caps_to_map(caps),
+        }
+    }
+}
+
+pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint128> {
+    let mut owner_map: HashMap<String, Uint128> = HashMap::new();
+    for (denom, cap) in caps.iter() {
+        owner_map.insert(denom.to_string(), **cap);
+    }
+    owner_map
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -79,6 +109,31 @@ impl Querier for WasmMockQuerier {
 impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
         match &request {
+            QueryRequest::Custom(TerraQueryWrapper { route, query_data }) => {
+                if route == &TerraRoute::Treasury {
+                    match query_data {
+                        TerraQuery::TaxRate {} => {
+                            let res = TaxRateResponse {
+                                rate: self.tax_querier.rate,
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        TerraQuery::TaxCap { denom } => {
+                            let cap = self
+                                .tax_querier
+                                .caps
+                                .get(denom)
+                                .copied()
+                                .unwrap_or_default();
+                            let res = TaxCapResponse { cap };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        _ => panic!("DO NOT ENTER HERE"),
+                    }
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            }
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
                 if contract_addr == "factory0000" {
                     match from_binary(msg).unwrap() {
@@ -92,6 +147,30 @@ impl WasmMockQuerier {
                             })
                             .into(),
                         ),
+                        FactoryConfig {} => SystemResult::Ok(
+                            to_binary(&FactoryConfigResponse {
+                                owner: Addr::unchecked("owner0000"),
+                                token_code_id: 10u64,
+                                collector: Addr::unchecked("collector"),
+                                pairs_admin: Addr::unchecked("admin0000"),
+                                pauser: Addr::unchecked("pauser0000"),
+                                permissionless: false,
+                                creation_fee: None,
+                            })
+                            .into(),
+                        ),
+                        PauseInfo { .. } => SystemResult::Ok(
+                            to_binary(&PauseInfoResponse {
+                                global_paused: self.global_paused,
+                                pair_paused: self.paused,
+                                registered_pair: if self.deregistered {
+                                    None
+                                } else {
+                                    Some(Addr::unchecked(MOCK_CONTRACT_ADDR))
+                                },
+                            })
+                            .into(),
+                        ),
                         _ => panic!("DO NOT ENTER HERE"),
                     }
                 } else {
@@ -172,6 +251,10 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             token_querier: TokenQuerier::default(),
+            tax_querier: TaxQuerier::default(),
+            paused: false,
+            global_paused: false,
+            deregistered: false,
         }
     }
 
@@ -180,6 +263,22 @@ impl WasmMockQuerier {
         self.token_querier = TokenQuerier::new(balances);
     }
 
+    pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
+        self.tax_querier = TaxQuerier::new(rate, caps);
+    }
+
+    pub fn with_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn with_global_paused(&mut self, global_paused: bool) {
+        self.global_paused = global_paused;
+    }
+
+    pub fn with_deregistered(&mut self, deregistered: bool) {
+        self.deregistered = deregistered;
+    }
+
     pub fn with_balance(&mut self, balances: &[(&String, Vec<Coin>)]) {
         for (addr, balance) in balances {
             self.base.update_balance(addr.to_string(), balance.clone());
@@ -3,14 +3,33 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::asset::PairInfo;
+use crate::factory::FeeConfig;
 
-use cosmwasm_std::{Addr, Decimal, Uint128};
+use cosmwasm_std::{to_binary, Addr, Binary, CosmosMsg, Decimal, StdResult, Uint128, WasmMsg};
 use cw20::Cw20ReceiveMsg;
 
 /// the default slippage
 pub const DEFAULT_SLIPPAGE: &str = "0.005";
 /// the maximum allowed slippage
 pub const MAX_ALLOWED_SLIPPAGE: &str = "0.5";
+/// the decimal precision used to scale the cumulative prices
+pub const TWAP_PRECISION: u8 = 6;
+/// the amount of LP tokens locked in the pair on the first deposit
+pub const MINIMUM_LIQUIDITY_AMOUNT: u128 = 1_000;
+/// the maximum number of hook contracts registered on a pair
+pub const MAX_PAIR_HOOKS: usize = 10;
+/// the maximum gas limit of a hook sub-message
+pub const MAX_HOOK_GAS: u64 = 2_000_000;
+/// the maximum number of limit orders filled after a swap
+pub const MAX_ORDER_FILLS: usize = 10;
+/// the minimum amount escrowed by a limit order
+pub const MIN_ORDER_AMOUNT: u128 = 1_000;
+/// the maximum allowed amplification coefficient of a stableswap pool
+pub const MAX_AMP: u64 = 1_000_000;
+/// the maximum factor the amplification coefficient can be changed by in a single ramp
+pub const MAX_AMP_CHANGE: u64 = 10;
+/// the minimum duration of an amplification coefficient ramp in seconds
+pub const MIN_AMP_CHANGING_TIME: u64 = 86400;
 
 /// ## Description
 /// This structure describes the basic settings for creating a contract.
@@ -22,6 +41,12 @@ pub struct InstantiateMsg {
     pub token_code_id: u64,
     /// the factory contract address
     pub factory: Addr,
+    /// the [`FeeConfig`] settings of the pair
+    pub fee_config: FeeConfig,
+    /// the protocol fee collector address
+    pub collector: Addr,
+    /// optional binary serialised parameters for custom pool types
+    pub init_params: Option<Binary>,
 }
 
 /// ## Description
@@ -32,7 +57,8 @@ pub enum ExecuteMsg {
     /// ## Description
     /// Receives a message of type [`Cw20ReceiveMsg`]
     Receive(Cw20ReceiveMsg),
-    /// ProvideLiquidity a user provides pool liquidity
+    /// ProvideLiquidity a user provides pool liquidity, the amounts beyond the pool ratio are
+    /// refunded
     ProvideLiquidity {
         /// the type of asset available in [`Asset`]
         assets: [Asset; 2],
@@ -40,6 +66,8 @@ pub enum ExecuteMsg {
         slippage_tolerance: Option<Decimal>,
         /// the receiver of provide liquidity
         receiver: Option<String>,
+        /// the block time in seconds after which the provision is rejected
+        deadline: Option<u64>,
     },
     /// Swap an offer asset to the other
     Swap {
@@ -47,7 +75,205 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// the block time in seconds after which the swap is rejected
+        deadline: Option<u64>,
     },
+    /// SwapExactOutput buys an exact amount of the other asset, refunding the unused offer asset
+    SwapExactOutput {
+        /// the offer asset sent, the part exceeding the required offer is refunded
+        offer_asset: Asset,
+        /// the amount of the other asset to receive
+        ask_amount: Uint128,
+        /// the maximum amount of the offer asset to spend
+        max_offer: Option<Uint128>,
+        to: Option<String>,
+        /// the block time in seconds after which the swap is rejected
+        deadline: Option<u64>,
+    },
+    /// ProvideSingleAsset swaps the optimal part of a single asset and provides the rest as liquidity,
+    /// the stable pair deposits the whole asset and charges the imbalance fee instead
+    ProvideSingleAsset {
+        /// the type of asset available in [`Asset`]
+        asset: Asset,
+        /// the maximum spread of the internal swap, or of the share against the peg on the stable pair
+        max_spread: Option<Decimal>,
+        /// the minimum amount of LP tokens to receive
+        min_share: Option<Uint128>,
+        /// the receiver of provide liquidity
+        receiver: Option<String>,
+        /// the block time in seconds after which the provision is rejected
+        deadline: Option<u64>,
+    },
+    /// Update the pair configuration, the params are specific to the pool type
+    UpdateConfig { params: Binary },
+    /// UpdateFeeConfig updates the cached fee settings, only the factory can execute it
+    UpdateFeeConfig {
+        /// new [`FeeConfig`] settings for the pair
+        fee_config: Option<FeeConfig>,
+        /// new protocol fee collector address
+        collector: Option<Addr>,
+    },
+    /// UpdatePairStatus updates the cached pause and deregistration status, only the factory can
+    /// execute it
+    UpdatePairStatus {
+        /// whether swaps and deposits are halted
+        paused: bool,
+        /// whether the pair is no longer registered in the factory
+        deregistered: bool,
+    },
+    /// Sync sets the stored reserves to the pair balances, only the factory owner can execute it
+    Sync {},
+    /// Skim transfers the pair balances exceeding the stored reserves to `to`
+    Skim { to: String },
+    /// FlashSwap sends `ask_asset` to the caller before being paid, executes a
+    /// [`FlashSwapCallbackMsg`] on the caller and then checks the pool invariant is restored
+    FlashSwap {
+        /// the asset lent to the caller
+        ask_asset: Asset,
+        /// binary serialised message passed back to the caller in the callback
+        msg: Binary,
+        /// the block time in seconds after which the flash swap is rejected
+        deadline: Option<u64>,
+    },
+    /// Internal use
+    /// Checks the pool invariant after fees is restored once the flash swap callback returns
+    FinalizeFlashSwap {},
+    /// AddHook registers a contract receiving a [`PairHookMsg`] after each swap and liquidity
+    /// event, only the factory owner can execute it
+    AddHook {
+        /// the hook contract address
+        contract_addr: String,
+        /// the gas limit of the hook sub-message, between 1 and [`MAX_HOOK_GAS`]
+        gas_limit: u64,
+    },
+    /// RemoveHook unregisters a hook contract, only the factory owner can execute it
+    RemoveHook { contract_addr: String },
+    /// SubmitOrder escrows a native asset in a limit order filled against the pool once the pool
+    /// price crosses the order price, only the constant product pair supports limit orders
+    SubmitOrder {
+        /// the asset sold by the order
+        offer_asset: Asset,
+        /// the minimum amount of the other asset received per unit of the offer asset
+        price: Decimal,
+    },
+    /// CancelOrder refunds the unfilled part of a limit order to its owner
+    CancelOrder { order_id: u64 },
+}
+
+/// ## Description
+/// This structure describes the callback executed on the flash swap caller. It should be
+/// de/serialized under the `FlashSwapCallback()` variant of the caller's ExecuteMsg.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FlashSwapCallbackMsg {
+    /// the asset received by the caller, net of the stability tax. The full lent amount is
+    /// owed to the pool
+    pub ask_asset: Asset,
+    /// the binary serialised message given to the flash swap
+    pub msg: Binary,
+}
+
+impl FlashSwapCallbackMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = FlashSwapReceiverMsg::FlashSwapCallback(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum FlashSwapReceiverMsg {
+    FlashSwapCallback(FlashSwapCallbackMsg),
+}
+
+/// ## Description
+/// This structure describes a hook contract registered on a pair.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Hook {
+    /// the hook contract address
+    pub contract_addr: Addr,
+    /// the gas limit of the hook sub-message
+    pub gas_limit: u64,
+}
+
+/// ## Description
+/// This structure describes the message sent to the hook contracts after a pair action. It should
+/// be de/serialized under the `PairHook()` variant of the hook contract's ExecuteMsg.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairHookMsg {
+    /// Sent after a swap
+    Swap {
+        sender: Addr,
+        receiver: Addr,
+        /// the asset paid into the pool
+        offer_asset: Asset,
+        /// the asset received by the receiver, net of the stability tax
+        return_asset: Asset,
+        /// the reserves after the swap
+        reserves: [Asset; 2],
+    },
+    /// Sent after liquidity is provided
+    ProvideLiquidity {
+        sender: Addr,
+        receiver: Addr,
+        /// the assets deposited into the pool, the refunded excess excluded
+        assets: [Asset; 2],
+        /// the amount of LP tokens minted to the receiver
+        share: Uint128,
+        /// the reserves after the provision
+        reserves: [Asset; 2],
+    },
+    /// Sent after liquidity is withdrawn
+    WithdrawLiquidity {
+        sender: Addr,
+        receiver: Addr,
+        /// the amount of LP tokens burned
+        share: Uint128,
+        /// the assets sent to the receiver
+        refund_assets: [Asset; 2],
+        /// the reserves after the withdrawal
+        reserves: [Asset; 2],
+    },
+}
+
+impl PairHookMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = PairHookReceiverMsg::PairHook(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum PairHookReceiverMsg {
+    PairHook(PairHookMsg),
 }
 
 /// ## Description
@@ -60,9 +286,44 @@ pub enum Cw20HookMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// the block time in seconds after which the swap is rejected
+        deadline: Option<u64>,
+    },
+    /// Escrow the sent tokens in a limit order, only the constant product pair supports it
+    SubmitOrder {
+        /// the minimum amount of the other asset received per sent token
+        price: Decimal,
+    },
+    /// Buy an exact amount of the other asset, the unused tokens are refunded
+    SwapExactOutput {
+        /// the amount of the other asset to receive
+        ask_amount: Uint128,
+        /// the maximum amount of the sent tokens to spend
+        max_offer: Option<Uint128>,
+        to: Option<String>,
+        /// the block time in seconds after which the swap is rejected
+        deadline: Option<u64>,
     },
     /// Withdrawing liquidity from the pool
-    WithdrawLiquidity {},
+    WithdrawLiquidity {
+        /// the minimum amounts of the assets to receive
+        min_assets: Option<[Asset; 2]>,
+        /// the receiver of the withdrawn assets, the LP token sender if empty
+        receiver: Option<String>,
+        /// the block time in seconds after which the withdrawal is rejected
+        deadline: Option<u64>,
+    },
+    /// Withdrawing liquidity from the pool as a single asset, swapping the other leg
+    WithdrawSingleAsset {
+        /// the asset to receive
+        ask_asset: AssetInfo,
+        /// the minimum amount of the asset to receive, net of the stability tax
+        min_receive: Option<Uint128>,
+        /// the receiver of the withdrawn asset, the LP token sender if empty
+        receiver: Option<String>,
+        /// the block time in seconds after which the withdrawal is rejected
+        deadline: Option<u64>,
+    },
 }
 
 /// ## Description
@@ -80,12 +341,57 @@ pub enum QueryMsg {
     Simulation { offer_asset: Asset },
     /// Returns information about the reverse simulation in a [`ReverseSimulationResponse`] object.
     ReverseSimulation { ask_asset: Asset },
+    /// Returns the LP tokens minted for a deposit in a [`SimulateProvideResponse`] object.
+    SimulateProvide { assets: [Asset; 2] },
+    /// Returns the assets refunded for burning LP tokens in a [`SimulateWithdrawResponse`] object.
+    SimulateWithdraw { lp_amount: Uint128 },
+    /// Returns the mid-price of the base asset in the other asset in a [`SpotPriceResponse`] object.
+    SpotPrice { base_asset: AssetInfo },
+    /// Returns the price impact of the swap in a [`PriceImpactResponse`] object.
+    PriceImpact { offer_asset: Asset },
+    /// Returns the cumulative prices of the pool assets in a [`CumulativePricesResponse`] object.
+    CumulativePrices {},
+    /// Returns the protocol fees accrued since the last liquidity event in a
+    /// [`PendingProtocolFeeResponse`] object.
+    PendingProtocolFee {},
+    /// Returns the cumulative trading statistics of the pair in a [`StatsResponse`] object.
+    Stats {},
+    /// Returns the registered hook contracts in a [`HooksResponse`] object.
+    Hooks {},
+    /// Returns a limit order in an [`OrderResponse`] object. The order queries are only
+    /// supported by the constant product pair.
+    Order { order_id: u64 },
+    /// Returns the open limit orders of an owner ordered by id in an [`OrdersResponse`] object.
+    OrdersByOwner {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the open limit orders selling an asset ordered by ascending price in an
+    /// [`OrdersResponse`] object.
+    OrdersByPrice {
+        offer_asset_info: AssetInfo,
+        /// the price and the id of the last order of the previous page
+        start_after: Option<(Decimal, u64)>,
+        limit: Option<u32>,
+    },
 }
 
+/// ## Description
+/// This structure describes a migration message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub pair_info: PairInfo,
     pub factory: Addr,
+    /// the [`FeeConfig`] settings of the pair
+    pub fee_config: FeeConfig,
+    /// the protocol fee collector address
+    pub collector: Addr,
+    /// the pool type specific parameters
+    pub params: Option<Binary>,
 }
 
 /// ## Description
@@ -100,9 +406,12 @@ pub struct PoolResponse {
 /// SimulationResponse returns swap simulation response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct SimulationResponse {
+    /// the amount received after the stability tax is deducted
     pub return_amount: Uint128,
     pub spread_amount: Uint128,
     pub commission_amount: Uint128,
+    /// the stability tax deducted when a native return asset is sent
+    pub tax_amount: Uint128,
 }
 
 /// ## Description
@@ -112,4 +421,153 @@ pub struct ReverseSimulationResponse {
     pub offer_amount: Uint128,
     pub spread_amount: Uint128,
     pub commission_amount: Uint128,
+    /// the stability tax added to the ask amount when a native ask asset is sent
+    pub tax_amount: Uint128,
+}
+
+/// ## Description
+/// SimulateProvideResponse returns the outcome of a deposit.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateProvideResponse {
+    /// the LP tokens minted for the deposit
+    pub share: Uint128,
+    /// the deposited amounts beyond the pool ratio, refunded to the sender
+    pub excess_assets: [Asset; 2],
+    /// the relative difference between the deposit ratio and the pool ratio, the lowest
+    /// slippage tolerance accepted for the deposit
+    pub slippage: Decimal,
+}
+
+/// ## Description
+/// SimulateWithdrawResponse returns the outcome of a withdrawal.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateWithdrawResponse {
+    /// the assets refunded before the stability tax
+    pub refund_assets: [Asset; 2],
+}
+
+/// ## Description
+/// SpotPriceResponse returns the mid-price of the pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpotPriceResponse {
+    /// the amount of the other asset per unit of the base asset, without the commission
+    pub price: Decimal,
+}
+
+/// ## Description
+/// PriceImpactResponse returns the price impact of a swap. The prices are amounts of the ask
+/// asset per unit of the offer asset, before the stability tax.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceImpactResponse {
+    /// the average price of the swap after the commission
+    pub execution_price: Decimal,
+    /// the mid-price of the pool before the swap
+    pub mid_price: Decimal,
+    /// the relative difference between the mid-price and the execution price
+    pub price_impact: Decimal,
+}
+
+/// ## Description
+/// CumulativePricesResponse returns the price accumulators of the pool, scaled by [`TWAP_PRECISION`].
+/// The accumulators wrap on overflow, consumers should compute differences with `wrapping_sub`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CumulativePricesResponse {
+    pub assets: [Asset; 2],
+    pub total_share: Uint128,
+    /// the cumulative price of the first asset denominated in the second asset
+    pub price0_cumulative_last: Uint128,
+    /// the cumulative price of the second asset denominated in the first asset
+    pub price1_cumulative_last: Uint128,
+    /// the block time in seconds of the last accumulation
+    pub block_time_last: u64,
+}
+
+/// ## Description
+/// PendingProtocolFeeResponse returns the LP tokens minted to the collector on the next liquidity event.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingProtocolFeeResponse {
+    /// the amount of LP tokens owed to the collector
+    pub share: Uint128,
+    /// the pool assets backing the owed LP tokens
+    pub assets: [Asset; 2],
+}
+
+/// ## Description
+/// HooksResponse returns the hook contracts registered on the pair.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HooksResponse {
+    pub hooks: Vec<Hook>,
+}
+
+/// ## Description
+/// OrderResponse returns a limit order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OrderResponse {
+    pub order_id: u64,
+    pub owner: Addr,
+    /// the unfilled part of the escrowed asset
+    pub offer_asset: Asset,
+    /// the asset received when the order is filled
+    pub ask_asset_info: AssetInfo,
+    /// the minimum amount of the ask asset received per unit of the offer asset
+    pub price: Decimal,
+    /// the amount of the offer asset sold so far
+    pub filled_amount: Uint128,
+    /// the amount of the ask asset sent to the owner so far
+    pub received_amount: Uint128,
+}
+
+/// ## Description
+/// OrdersResponse returns a page of limit orders.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OrdersResponse {
+    pub orders: Vec<OrderResponse>,
+}
+
+/// ## Description
+/// StatsResponse returns the cumulative trading statistics of the pair.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    /// the cumulative amounts of each pool asset offered to the pair
+    pub volumes: [Asset; 2],
+    /// the cumulative commissions kept by the liquidity providers
+    pub lp_commissions: [Asset; 2],
+    /// the cumulative commissions owed to the protocol
+    pub protocol_commissions: [Asset; 2],
+    /// the number of swaps executed by the pair
+    pub swap_count: u64,
+    /// the block time in seconds of the last swap
+    pub last_trade_time: u64,
+}
+
+/// ## Description
+/// This structure holds the parameters used for creating a stableswap pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StablePoolParams {
+    /// the amplification coefficient of the pool
+    pub amp: u64,
+}
+
+/// ## Description
+/// This structure describes the stableswap pool parameters returned in [`ConfigResponse`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StablePoolConfig {
+    /// the current amplification coefficient of the pool
+    pub amp: Decimal,
+}
+
+/// ## Description
+/// This enum describes the stableswap pool parameters that can be updated by the factory owner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StablePoolUpdateParams {
+    /// Starts ramping the amplification coefficient towards `next_amp`
+    StartChangingAmp {
+        /// the target amplification coefficient
+        next_amp: u64,
+        /// the block time in seconds when `next_amp` is reached
+        next_amp_time: u64,
+    },
+    /// Stops ramping, the current amplification coefficient is kept
+    StopChangingAmp {},
 }
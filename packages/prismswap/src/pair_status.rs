@@ -0,0 +1,86 @@
+use cosmwasm_std::{Addr, MessageInfo, QuerierWrapper, Response, StdResult, Storage};
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::AssetInfo;
+use crate::error::ContractError;
+use crate::factory::PauseInfoResponse;
+use crate::querier::query_pause_info;
+
+/// the pause and deregistration status pushed by the factory to the pairs
+pub const PAIR_STATUS: Item<PairStatus> = Item::new("pair_status");
+
+/// ## Description
+/// This structure holds the status of a pair cached from its factory.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PairStatus {
+    /// whether swaps and deposits are halted on the pair, the global pause is queried from the
+    /// factory instead
+    pub paused: bool,
+    /// whether the pair is no longer registered in the factory
+    pub deregistered: bool,
+}
+
+/// ## Description
+/// Updates the status cached by a pair. Only the factory can execute it.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **factory** is the object of type [`Addr`] of the pair factory.
+///
+/// * **status** is the object of type [`PairStatus`] with the new status.
+pub fn update_pair_status(
+    storage: &mut dyn Storage,
+    info: &MessageInfo,
+    factory: &Addr,
+    status: PairStatus,
+) -> Result<Response, ContractError> {
+    // permission check
+    if info.sender != *factory {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PAIR_STATUS.save(storage, &status)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_pair_status"),
+        ("paused", &status.paused.to_string()),
+        ("deregistered", &status.deregistered.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Caches the status of a pair deployed before the factory pushed it to the pairs.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **querier** is the object of type [`QuerierWrapper`].
+///
+/// * **factory** is the object of type [`Addr`] of the pair factory.
+///
+/// * **asset_infos** are the assets of the pair.
+///
+/// * **pair_address** is the object of type [`Addr`] of the pair.
+pub fn migrate_pair_status(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    factory: &Addr,
+    asset_infos: &[AssetInfo; 2],
+    pair_address: &Addr,
+) -> StdResult<()> {
+    if PAIR_STATUS.may_load(storage)?.is_some() {
+        return Ok(());
+    }
+
+    let pause_info: PauseInfoResponse = query_pause_info(querier, factory, asset_infos)?;
+    PAIR_STATUS.save(
+        storage,
+        &PairStatus {
+            paused: pause_info.pair_paused,
+            deregistered: pause_info.registered_pair.as_ref() != Some(pair_address),
+        },
+    )
+}
@@ -0,0 +1,60 @@
+use cosmwasm_std::{Decimal, Uint128};
+
+use crate::asset::{Asset, AssetInfo};
+
+/// ## Description
+/// Returns the amounts as assets in the order of the pair asset infos.
+/// ## Params
+/// * **asset_infos** are the assets of the pair.
+///
+/// * **amounts** are the amounts of each pool asset.
+pub fn reserve_assets(asset_infos: &[AssetInfo; 2], amounts: &[Uint128; 2]) -> [Asset; 2] {
+    [
+        Asset {
+            info: asset_infos[0].clone(),
+            amount: amounts[0],
+        },
+        Asset {
+            info: asset_infos[1].clone(),
+            amount: amounts[1],
+        },
+    ]
+}
+
+/// ## Description
+/// Returns the reserve amounts refunded for burning `share` LP tokens.
+/// ## Params
+/// * **reserves** are the pool reserves before the withdrawal.
+///
+/// * **share** is the amount of LP tokens burnt.
+///
+/// * **total_share** is the total supply of the LP token, the protocol fee share included.
+pub fn compute_refund_amounts(
+    reserves: &[Uint128; 2],
+    share: Uint128,
+    total_share: Uint128,
+) -> [Uint128; 2] {
+    let share_ratio: Decimal = Decimal::from_ratio(share, total_share);
+
+    [reserves[0] * share_ratio, reserves[1] * share_ratio]
+}
+
+/// ## Description
+/// Returns the reserves left by a balanced deposit minting `share` LP tokens, which excludes the
+/// fee charged on a single asset deposit.
+/// ## Params
+/// * **reserves** are the pool reserves before the deposit.
+///
+/// * **share** is the amount of LP tokens minted.
+///
+/// * **total_share** is the total supply of the LP token, the protocol fee share included.
+pub fn compute_deposit_reserves(
+    reserves: &[Uint128; 2],
+    share: Uint128,
+    total_share: Uint128,
+) -> [Uint128; 2] {
+    [
+        reserves[0].multiply_ratio(total_share + share, total_share),
+        reserves[1].multiply_ratio(total_share + share, total_share),
+    ]
+}
@@ -1,6 +1,6 @@
 use crate::asset::PairInfo;
 use crate::factory::{
-    ConfigResponse as FactoryConfigResponse, FeeInfoResponse, PairsResponse,
+    ConfigResponse as FactoryConfigResponse, FeeInfoResponse, PairsResponse, PauseInfoResponse,
     QueryMsg as FactoryQueryMsg,
 };
 use crate::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
@@ -53,7 +53,7 @@ pub fn query_all_balances(querier: &QuerierWrapper, account_addr: &Addr) -> StdR
 /// * **querier** is the object of type [`QuerierWrapper`].
 ///
 /// * **contract_addr** is the object of type [`Addr`]. Sets the address of the contract for which
-/// the balance will be requested
+///   the balance will be requested
 ///
 /// * **account_addr** is the object of type [`Addr`].
 pub fn query_token_balance(
@@ -130,15 +130,40 @@ pub fn query_factory_config(
 /// * **factory_contract** is the object of type [`Addr`].
 ///
 /// * **asset_infos** is an array that contains two items of type [`AssetInfo`].
+///
+/// * **pair_address** is the object of type [`Addr`] of the pair, which may be deregistered.
 pub fn query_fee_info(
     querier: &QuerierWrapper,
     factory_contract: &Addr,
     asset_infos: &[AssetInfo; 2],
+    pair_address: &Addr,
 ) -> StdResult<FeeInfoResponse> {
     querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: factory_contract.to_string(),
         msg: to_binary(&FactoryQueryMsg::FeeInfo {
             asset_infos: asset_infos.clone(),
+            pair_address: Some(pair_address.to_string()),
+        })?,
+    }))
+}
+
+/// ## Description
+/// Returns the pause status of the specified pair.
+/// ## Params
+/// * **querier** is the object of type [`QuerierWrapper`].
+///
+/// * **factory_contract** is the object of type [`Addr`].
+///
+/// * **asset_infos** is an array that contains two items of type [`AssetInfo`].
+pub fn query_pause_info(
+    querier: &QuerierWrapper,
+    factory_contract: &Addr,
+    asset_infos: &[AssetInfo; 2],
+) -> StdResult<PauseInfoResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: factory_contract.to_string(),
+        msg: to_binary(&FactoryQueryMsg::PauseInfo {
+            asset_infos: asset_infos.clone(),
         })?,
     }))
 }
@@ -58,6 +58,8 @@ pub enum ExecuteMsg {
         operations: Vec<SwapOperation>,
         minimum_receive: Option<Uint128>,
         to: Option<Addr>,
+        /// the block time in seconds after which the swap is rejected
+        deadline: Option<u64>,
     },
 
     /// Internal use
@@ -86,6 +88,8 @@ pub enum Cw20HookMsg {
         minimum_receive: Option<Uint128>,
         /// the recipient
         to: Option<Addr>,
+        /// the block time in seconds after which the swap is rejected
+        deadline: Option<u64>,
     },
 }
 
@@ -0,0 +1,60 @@
+use cosmwasm_std::{Env, StdResult, Storage, Uint128};
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::fee_info::FeeInfo;
+
+/// the cumulative trading statistics, empty until the first swap
+pub const STATS: Item<Stats> = Item::new("stats");
+
+/// ## Description
+/// This structure holds the cumulative trading statistics of a pair.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Stats {
+    /// the cumulative amounts of each asset offered, in the order of the pair asset infos
+    pub volumes: [Uint128; 2],
+    /// the cumulative commissions kept by the liquidity providers
+    pub lp_commissions: [Uint128; 2],
+    /// the cumulative commissions owed to the protocol
+    pub protocol_commissions: [Uint128; 2],
+    /// the number of swaps executed by the pair
+    pub swap_count: u64,
+    /// the block time in seconds of the last swap
+    pub last_trade_time: u64,
+}
+
+/// ## Description
+/// Adds a swap to the trading statistics. The commissions are split between the liquidity
+/// providers and the protocol by the current protocol fee.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **fee_info** is the object of type [`FeeInfo`] with the fee settings of the swap.
+///
+/// * **volumes** are the amounts of each asset offered, in the order of the pair asset infos.
+///
+/// * **commissions** are the commissions charged in each asset.
+pub fn record_trade(
+    storage: &mut dyn Storage,
+    env: &Env,
+    fee_info: &FeeInfo,
+    volumes: [Uint128; 2],
+    commissions: [Uint128; 2],
+) -> StdResult<()> {
+    let mut stats: Stats = STATS.may_load(storage)?.unwrap_or_default();
+    for i in 0..2 {
+        let protocol_commission = commissions[i] * fee_info.fee_config.protocol_fee;
+        stats.volumes[i] = stats.volumes[i].checked_add(volumes[i])?;
+        stats.protocol_commissions[i] =
+            stats.protocol_commissions[i].checked_add(protocol_commission)?;
+        stats.lp_commissions[i] = stats.lp_commissions[i]
+            .checked_add(commissions[i].checked_sub(protocol_commission)?)?;
+    }
+    stats.swap_count += 1;
+    stats.last_trade_time = env.block.time.seconds();
+
+    STATS.save(storage, &stats)
+}